@@ -1,18 +1,31 @@
 //! Application state and core logic.
 
-use std::collections::HashMap;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::path::PathBuf;
 use std::time::{Duration, Instant};
 
 use crossterm::event::{KeyCode, KeyModifiers, MouseButton, MouseEventKind};
-use ratatui::layout::Rect;
+use ratatui::layout::{Position, Rect};
 use ratatui::widgets::TableState;
 use sysinfo::{Components, Disks, Networks, Pid, Signal, System, Users};
+use unicode_width::UnicodeWidthStr;
 
-use crate::metrics::{collect_gpu_metrics, collect_system_metrics, GpuHandle};
+use crate::cli::Args;
+use crate::columns::{Column, ColumnLayout};
+use crate::config::Config;
+use crate::metrics::{
+    apply_gpu_setting, collect_gpu_metrics, collect_system_metrics, drain_gpu_events,
+    process_accounting, GpuHandle, WorkloadCache,
+};
 use crate::types::{
-    ActivePanel, GpuBackend, GpuMetrics, GpuProcessInfo, HistoryData, KillConfirmation,
-    ProcessInfo, SortColumn, SystemMetrics,
+    ActionMenu, ActivePanel, ColumnChooser, ComputeMode, CounterDeltas, EnergyWindow,
+    EventSeverity, FinishedGpuProcess, FollowedProcess, GpuBackend, GpuEvent, GpuEventKind,
+    GpuFault, GpuGraphMetric, GpuInfo, GpuMetrics, GpuProcessInfo, GpuSelector, GpuSettingChange,
+    GpuStatus, HistoryData, IdleGpuProcess, KillConfirmation, PcieLink, PcieReplayTracker,
+    ProcessAction, ProcessInfo, Reading, SettingConfirmation, SettingsPanel, SortColumn,
+    SystemMetrics, TrackedGpuProcess, Workload, XID_FALLEN_OFF_BUS,
 };
+use crate::utils::{format_energy, format_short_duration};
 
 /// Main application state.
 pub struct App {
@@ -27,28 +40,98 @@ pub struct App {
     // Collected metrics
     pub system_metrics: SystemMetrics,
     pub gpu_metrics: Option<GpuMetrics>,
+    // Metrics for every GPU, before the GPU selection is applied
+    pub all_gpu_metrics: Option<GpuMetrics>,
+    // Last reading of every GPU seen, by UUID, to keep lost GPUs listed
+    pub last_seen_gpus: HashMap<String, GpuInfo>,
     pub history: HistoryData,
+    // Per-GPU state below is keyed by `GpuInfo::stable_id`, as indices shift
+    // when a backend comes up late or a GPU disappears.
+    // Time each GPU has spent with its clocks slowed down
+    pub gpu_throttle_time: HashMap<String, Duration>,
+    // PCIe replay counts per GPU, to spot links that keep retrying
+    pub pcie_replays: HashMap<String, PcieReplayTracker>,
+    // Joules consumed per GPU since startup
+    pub gpu_energy: HashMap<String, f64>,
+    pub last_energy_counter: HashMap<String, u64>,
+    pub energy_windows: Vec<EnergyWindow>,
+    pub energy_report: Option<PathBuf>,
+    pub started_at: chrono::DateTime<chrono::Local>,
+    // Driver events (XID errors, ECC, clock changes), oldest first
+    pub gpu_events: VecDeque<GpuEvent>,
+    // Most severe unacknowledged event, flashed in the header
+    pub gpu_alert: Option<(GpuEvent, Instant)>,
+    /// GPU processes seen in the last refresh, by PID.
+    pub running_gpu_processes: HashMap<u32, TrackedGpuProcess>,
+    /// GPU processes that have gone away, most recent first.
+    pub finished_gpu_processes: VecDeque<FinishedGpuProcess>,
+    /// When each (PID, GPU stable ID) last started looking idle.
+    pub gpu_idle_since: HashMap<(u32, String), Instant>,
+    /// How long a process must sit idle on GPU memory to count as wasting it.
+    pub idle_after: Duration,
 
     // State tracking
     pub last_network_stats: HashMap<String, (u64, u64)>,
+    /// Container and job of each process, read once per process.
+    pub workloads: WorkloadCache,
+    pub last_nvlink_counters: HashMap<(u32, u32), (u64, u64)>,
+    /// Cumulative counters of the DRM backends (engine time, RC6, energy).
+    pub drm_counters: CounterDeltas,
     pub last_update: Instant,
 
     // UI state
     pub running: bool,
     pub show_help: bool,
+    pub show_health: bool,
+    pub show_events: bool,
+    pub show_topology: bool,
+    pub show_info: bool,
+    pub show_finished: bool,
+    pub show_idle: bool,
+    pub events_scroll: usize,
+    pub finished_scroll: usize,
     pub active_panel: ActivePanel,
     pub cpu_process_state: TableState,
     pub gpu_process_state: TableState,
+    // Selection is pinned to a PID so it survives re-sorting
+    pub cpu_selected_pid: Option<u32>,
+    pub gpu_selected_pid: Option<u32>,
+    pub followed: Option<FollowedProcess>,
+    pub detail_pid: Option<u32>,
     pub cpu_sort: SortColumn,
     pub gpu_sort: SortColumn,
     pub sort_ascending: bool,
     pub process_filter: String,
+    // GPU whose processes alone are listed, by stable ID
+    pub gpu_process_filter: Option<String>,
+    // Horizontal scroll of the command column, in display columns
+    pub cpu_hscroll: usize,
+    pub gpu_hscroll: usize,
+    pub wrap_command: bool,
+    // Height of the selected row in the active table when its command is wrapped
+    pub selected_row_height: u16,
     pub show_all_processes: bool,
     pub compact_mode: bool,
     pub show_graphs: bool,
+    pub gpu_graph_metric: GpuGraphMetric,
 
     // Settings
     pub refresh_rate: Duration,
+    pub config: Config,
+    pub column_chooser: Option<ColumnChooser>,
+    // GPUs to show by stable ID, in display order; `None` shows all of them
+    pub gpu_selection: Option<Vec<String>>,
+    // Selection list from the command line or environment, resolved once
+    // the GPUs are known
+    pending_gpu_selectors: Option<(Vec<GpuSelector>, &'static str)>,
+    // Cursor of the GPU chooser, when open
+    pub gpu_chooser: Option<usize>,
+    pub settings_panel: Option<SettingsPanel>,
+    pub setting_confirm: Option<SettingConfirmation>,
+    // Whether we run as root, which changing device settings requires
+    pub privileged: bool,
+    // SM clocks locked from the settings panel, by GPU stable ID
+    pub locked_clocks: HashMap<String, u32>,
 
     // Kill confirmation dialog
     pub kill_confirm: Option<KillConfirmation>,
@@ -57,57 +140,170 @@ pub struct App {
     // Track panel areas for mouse support
     pub cpu_process_area: Option<Rect>,
     pub gpu_process_area: Option<Rect>,
+    pub cpu_column_areas: Vec<Rect>,
+    pub gpu_column_areas: Vec<Rect>,
+    pub gpu_card_areas: Vec<(Rect, u32)>,
+    pub last_click: Option<(Instant, u16, u16)>,
+    // Process context menu
+    pub action_menu: Option<ActionMenu>,
+    pub action_menu_area: Option<Rect>,
+}
+
+/// Display columns scrolled per horizontal scroll step.
+const HSCROLL_STEP: usize = 8;
+
+/// Maximum delay between two clicks on a row to count as a double-click.
+const DOUBLE_CLICK_INTERVAL: Duration = Duration::from_millis(400);
+
+/// Number of driver events kept in the log.
+const MAX_GPU_EVENTS: usize = 1000;
+
+/// Number of finished GPU processes kept in the history.
+const MAX_FINISHED_PROCESSES: usize = 500;
+
+/// Utilization at or below which a GPU, or a process on it, counts as idle.
+const IDLE_UTILIZATION: u32 = 5;
+
+/// Default for `--idle-after`.
+const DEFAULT_IDLE_AFTER: Duration = Duration::from_secs(600);
+
+/// How long a warning event stays in the header. Critical events stay until
+/// the events panel is opened.
+const ALERT_DURATION: Duration = Duration::from_secs(10);
+
+/// How long a GPU stays flagged after its PCIe replay counter last grew.
+const PCIE_REPLAY_WINDOW: Duration = Duration::from_secs(300);
+
+/// Power limit change per key press in the settings panel, in watts.
+const POWER_LIMIT_STEP: u32 = 5;
+
+/// Locked clock change per key press in the settings panel, in MHz.
+const CLOCK_LOCK_STEP: u32 = 100;
+
+/// Part of a process table under the mouse cursor.
+enum TableHit {
+    /// Header cell of the visible column at this index.
+    Header(usize),
+    /// Row at this index of the sorted process list.
+    Row(usize),
 }
 
 impl App {
     /// Create a new App instance.
-    pub fn new() -> anyhow::Result<Self> {
+    pub fn new(args: &Args) -> anyhow::Result<Self> {
         let mut system = System::new_all();
         system.refresh_all();
 
-        let networks = Networks::new_with_refreshed_list();
-        let disks = Disks::new_with_refreshed_list();
-        let components = Components::new_with_refreshed_list();
-        let users = Users::new_with_refreshed_list();
+        let mut app = Self::with_gpu_handle(args, system, GpuHandle::new(), Config::load());
+        app.networks.refresh_list();
+        app.disks.refresh_list();
+        app.components.refresh_list();
+        app.users.refresh_list();
+        app.refresh_all()?;
+        // A GPU list that matched nothing is reported first
+        if let (Some(warning), None) = (&args.gpus_warning, &app.status_message) {
+            app.set_status(warning.clone());
+        }
+        if let Some(name) = &args.energy_window {
+            app.energy_windows.push(EnergyWindow::new(name.clone()));
+        }
+
+        Ok(app)
+    }
 
-        let gpu_handle = GpuHandle::new();
+    /// An App reading processes from `system` and GPUs from `gpu_handle`,
+    /// before its first refresh.
+    fn with_gpu_handle(args: &Args, system: System, gpu_handle: GpuHandle, config: Config) -> Self {
+        let privileged = is_privileged(&system);
 
         let mut app = Self {
             system,
-            networks,
-            disks,
-            components,
-            users,
+            networks: Networks::new(),
+            disks: Disks::new(),
+            components: Components::new(),
+            users: Users::new(),
             gpu_handle,
             system_metrics: SystemMetrics::default(),
             gpu_metrics: None,
+            all_gpu_metrics: None,
+            last_seen_gpus: HashMap::new(),
             history: HistoryData::new(),
+            gpu_throttle_time: HashMap::new(),
+            pcie_replays: HashMap::new(),
+            gpu_energy: HashMap::new(),
+            last_energy_counter: HashMap::new(),
+            energy_windows: Vec::new(),
+            energy_report: args.energy_report.clone(),
+            started_at: chrono::Local::now(),
+            gpu_events: VecDeque::new(),
+            gpu_alert: None,
+            running_gpu_processes: HashMap::new(),
+            finished_gpu_processes: VecDeque::new(),
+            gpu_idle_since: HashMap::new(),
+            idle_after: args.idle_after.unwrap_or(DEFAULT_IDLE_AFTER),
             last_network_stats: HashMap::new(),
+            workloads: WorkloadCache::default(),
+            last_nvlink_counters: HashMap::new(),
+            drm_counters: CounterDeltas::default(),
             last_update: Instant::now(),
             running: true,
             show_help: false,
+            show_health: false,
+            show_events: false,
+            show_topology: false,
+            show_info: false,
+            show_finished: false,
+            show_idle: false,
+            events_scroll: 0,
+            finished_scroll: 0,
             active_panel: ActivePanel::CpuProcesses,
             cpu_process_state: TableState::default(),
             gpu_process_state: TableState::default(),
+            cpu_selected_pid: None,
+            gpu_selected_pid: None,
+            followed: None,
+            detail_pid: None,
             cpu_sort: SortColumn::Cpu,
             gpu_sort: SortColumn::GpuMemory,
             sort_ascending: false,
             process_filter: String::new(),
+            gpu_process_filter: None,
+            cpu_hscroll: 0,
+            gpu_hscroll: 0,
+            wrap_command: false,
+            selected_row_height: 1,
             show_all_processes: false,
             compact_mode: false,
             show_graphs: true,
+            gpu_graph_metric: GpuGraphMetric::Utilization,
             refresh_rate: Duration::from_millis(1000),
+            config,
+            column_chooser: None,
+            gpu_selection: None,
+            pending_gpu_selectors: args
+                .gpus
+                .as_ref()
+                .map(|(list, source)| (GpuSelector::parse_list(list), *source)),
+            gpu_chooser: None,
+            settings_panel: None,
+            setting_confirm: None,
+            privileged,
+            locked_clocks: HashMap::new(),
             kill_confirm: None,
             status_message: None,
             cpu_process_area: None,
             gpu_process_area: None,
+            cpu_column_areas: Vec::new(),
+            gpu_column_areas: Vec::new(),
+            gpu_card_areas: Vec::new(),
+            last_click: None,
+            action_menu: None,
+            action_menu_area: None,
         };
 
         app.cpu_process_state.select(Some(0));
         app.gpu_process_state.select(Some(0));
-        app.refresh_all()?;
-
-        Ok(app)
+        app
     }
 
     /// Refresh all metrics.
@@ -129,12 +325,955 @@ impl App {
             &mut self.last_network_stats,
             elapsed,
         );
+        self.workloads.update(&mut self.system_metrics.processes);
+
+        let gpu_trouble = self
+            .all_gpu_metrics
+            .as_ref()
+            .is_some_and(|m| m.gpus.iter().any(|g| g.needs_nvml_reinit()));
+        self.gpu_handle.retry_init(gpu_trouble);
+        self.all_gpu_metrics = collect_gpu_metrics(
+            &self.gpu_handle,
+            &self.system,
+            &self.users,
+            &mut self.last_nvlink_counters,
+            &mut self.drm_counters,
+            elapsed,
+        );
+        self.track_lost_gpus();
+        self.track_gpu_processes();
+        self.track_idle_processes();
+        self.resolve_gpu_selectors();
+        self.apply_gpu_selection();
+
+        // Tracked for every GPU so nothing is missed while one is deselected
+        if let Some(ref gpu_metrics) = self.all_gpu_metrics {
+            for gpu in &gpu_metrics.gpus {
+                if gpu.throttle_reasons.iter().any(|r| r.is_slowdown()) {
+                    *self.gpu_throttle_time.entry(gpu.stable_id()).or_default() += elapsed;
+                }
+                if let Some(count) = gpu.pcie.replay_count {
+                    let tracker =
+                        self.pcie_replays
+                            .entry(gpu.stable_id())
+                            .or_insert(PcieReplayTracker {
+                                baseline: count,
+                                last: count,
+                                last_increase: None,
+                            });
+                    if count > tracker.last {
+                        tracker.last_increase = Some(Instant::now());
+                    }
+                    tracker.last = count;
+                }
+            }
+        }
+
+        self.accumulate_energy(elapsed);
+        self.record_gpu_events();
+        self.update_history();
+        self.check_followed_process();
+        self.sync_selection();
+
+        Ok(())
+    }
+
+    /// Turn the command-line or environment GPU list into GPU stable IDs.
+    fn resolve_gpu_selectors(&mut self) {
+        let Some(gpus) = self
+            .all_gpu_metrics
+            .as_ref()
+            .map(|m| &m.gpus)
+            .filter(|g| !g.is_empty())
+        else {
+            return;
+        };
+        let Some((selectors, source)) = self.pending_gpu_selectors.take() else {
+            return;
+        };
+
+        let mut order: Vec<String> = Vec::new();
+        let mut missing = Vec::new();
+        for selector in &selectors {
+            match gpus
+                .iter()
+                .find(|g| selector.matches(g) && !order.contains(&g.stable_id()))
+            {
+                Some(gpu) => order.push(gpu.stable_id()),
+                None => missing.push(selector.label()),
+            }
+        }
+
+        if !missing.is_empty() {
+            self.set_status(format!(
+                "No GPU matches {} from {}",
+                missing.join(", "),
+                source
+            ));
+        }
+        if !order.is_empty() {
+            self.gpu_selection = Some(order);
+        }
+    }
+
+    /// Show only the selected GPUs, their processes and graphs.
+    fn apply_gpu_selection(&mut self) {
+        self.gpu_metrics = self.all_gpu_metrics.clone();
+        if let (Some(metrics), Some(order)) = (self.gpu_metrics.as_mut(), &self.gpu_selection) {
+            let indices: Vec<u32> = order
+                .iter()
+                .filter_map(|id| metrics.gpus.iter().find(|g| g.stable_id() == *id))
+                .map(|g| g.index)
+                .collect();
+            metrics.select(&indices);
+        }
+    }
+
+    /// Follow GPU processes across refreshes and move the ones that have gone
+    /// away to the finished-process history.
+    fn track_gpu_processes(&mut self) {
+        let Some(metrics) = &self.all_gpu_metrics else {
+            return;
+        };
+        let now = chrono::Local::now();
+        let mut memory: HashMap<u32, u64> = HashMap::new();
+        for p in &metrics.processes {
+            let tracked = self
+                .running_gpu_processes
+                .entry(p.pid)
+                .or_insert_with(|| TrackedGpuProcess::new(p, now));
+            tracked.last_seen = now;
+            if !tracked.gpus.contains(&p.gpu_index) {
+                tracked.gpus.push(p.gpu_index);
+            }
+            if let Some(gpu) = metrics.gpus.iter().find(|g| g.index == p.gpu_index) {
+                let id = gpu.stable_id();
+                if !tracked.gpu_ids.contains(&id) {
+                    tracked.gpu_ids.push(id);
+                }
+            }
+            if let Some(sm) = p.sm_utilization {
+                tracked.sm_total += sm as u64;
+                tracked.sm_samples += 1;
+            }
+            *memory.entry(p.pid).or_default() += p.gpu_memory;
+        }
+        for (pid, bytes) in memory {
+            if let Some(tracked) = self.running_gpu_processes.get_mut(&pid) {
+                tracked.peak_memory = tracked.peak_memory.max(bytes);
+            }
+        }
+
+        // Processes on a GPU that stopped responding may well still be running
+        let troubled: HashSet<String> = metrics
+            .gpus
+            .iter()
+            .filter(|g| !g.status.is_ok())
+            .map(|g| g.stable_id())
+            .collect();
+        let gone: Vec<u32> = self
+            .running_gpu_processes
+            .iter()
+            .filter(|(_, t)| {
+                t.last_seen != now && !t.gpu_ids.iter().any(|id| troubled.contains(id))
+            })
+            .map(|(&pid, _)| pid)
+            .collect();
+        for pid in gone {
+            if let Some(tracked) = self.running_gpu_processes.remove(&pid) {
+                let finished = self.finished_process(pid, tracked);
+                self.finished_gpu_processes.push_front(finished);
+            }
+        }
+        self.finished_gpu_processes.truncate(MAX_FINISHED_PROCESSES);
+    }
+
+    /// Summary of a process that has gone away. NVML accounting, where it is
+    /// enabled, covers the whole lifetime; otherwise the samples taken while
+    /// the process was visible are used.
+    fn finished_process(&self, pid: u32, tracked: TrackedGpuProcess) -> FinishedGpuProcess {
+        let mut gpus = tracked.gpus;
+        gpus.sort_unstable();
+        // NVML GPUs' stable IDs are their UUIDs
+        let accounting: Vec<_> = self
+            .all_gpus()
+            .iter()
+            .filter(|g| g.backend == GpuBackend::Nvml)
+            .filter_map(|g| g.uuid.as_deref())
+            .filter(|uuid| tracked.gpu_ids.iter().any(|id| id == uuid))
+            .filter_map(|uuid| process_accounting(&self.gpu_handle, uuid, pid))
+            // A record that started after we last saw the PID is a later process
+            .filter(|a| a.start <= tracked.last_seen)
+            .collect();
+
+        let mut finished = FinishedGpuProcess {
+            pid,
+            name: tracked.name,
+            user: tracked.user,
+            command: tracked.command,
+            gpus,
+            start: tracked.first_seen,
+            end: tracked.last_seen,
+            peak_memory: tracked.peak_memory,
+            avg_sm_utilization: (tracked.sm_samples > 0)
+                .then(|| (tracked.sm_total / tracked.sm_samples as u64) as u32),
+            accounted: false,
+        };
+        if let Some(start) = accounting.iter().map(|a| a.start).min() {
+            finished.start = start;
+            if let Some(end) = accounting
+                .iter()
+                .filter_map(|a| Some(a.start + chrono::TimeDelta::from_std(a.duration).ok()?))
+                .max()
+            {
+                finished.end = end.max(start);
+            }
+            let max_memory: u64 = accounting.iter().filter_map(|a| a.max_memory).sum();
+            finished.peak_memory = finished.peak_memory.max(max_memory);
+            let utilization: Vec<u32> = accounting
+                .iter()
+                .filter_map(|a| a.gpu_utilization)
+                .collect();
+            if !utilization.is_empty() {
+                finished.avg_sm_utilization =
+                    Some(utilization.iter().sum::<u32>() / utilization.len() as u32);
+            }
+            finished.accounted = true;
+        }
+        finished
+    }
+
+    /// Note when each compute process on each GPU went idle. A process counts
+    /// as idle when its own SM utilization is near zero, or where that is not
+    /// reported, its GPU's utilization.
+    fn track_idle_processes(&mut self) {
+        let Some(metrics) = &self.all_gpu_metrics else {
+            self.gpu_idle_since.clear();
+            return;
+        };
+        let now = Instant::now();
+        let mut idle = HashSet::new();
+        for p in &metrics.processes {
+            // Graphics clients such as the X server always hold some memory
+            if p.gpu_memory == 0 || !p.process_type.contains('C') {
+                continue;
+            }
+            let Some(gpu) = metrics.gpus.iter().find(|g| g.index == p.gpu_index) else {
+                continue;
+            };
+            let utilization = p.sm_utilization.or(gpu.gpu_utilization.value());
+            if utilization.is_some_and(|u| u <= IDLE_UTILIZATION) {
+                idle.insert((p.pid, gpu.stable_id()));
+            }
+        }
+        self.gpu_idle_since.retain(|key, _| idle.contains(key));
+        for key in idle {
+            self.gpu_idle_since.entry(key).or_insert(now);
+        }
+    }
+
+    /// Processes that have held GPU memory on an idle GPU for at least
+    /// `idle_after`, holding the most memory first.
+    pub fn idle_gpu_processes(&self) -> Vec<IdleGpuProcess> {
+        let Some(metrics) = &self.all_gpu_metrics else {
+            return Vec::new();
+        };
+        let mut idle: Vec<IdleGpuProcess> = metrics
+            .processes
+            .iter()
+            .filter_map(|p| {
+                let gpu = metrics.gpus.iter().find(|g| g.index == p.gpu_index)?;
+                let since = self.gpu_idle_since.get(&(p.pid, gpu.stable_id()))?;
+                let idle_for = since.elapsed();
+                (idle_for >= self.idle_after).then(|| IdleGpuProcess {
+                    pid: p.pid,
+                    name: p.name.clone(),
+                    user: p.user.clone(),
+                    command: p.command.clone(),
+                    gpu_index: p.gpu_index,
+                    gpu_memory: p.gpu_memory,
+                    idle_for,
+                })
+            })
+            .collect();
+        idle.sort_by_key(|p| std::cmp::Reverse(p.gpu_memory));
+        idle
+    }
+
+    fn handle_finished_key(&mut self, code: KeyCode) {
+        let last = self.finished_gpu_processes.len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('h') => self.show_finished = false,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.finished_scroll = (self.finished_scroll + 1).min(last)
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.finished_scroll = self.finished_scroll.saturating_sub(1)
+            }
+            KeyCode::PageDown => self.finished_scroll = (self.finished_scroll + 10).min(last),
+            KeyCode::PageUp => self.finished_scroll = self.finished_scroll.saturating_sub(10),
+            KeyCode::Home => self.finished_scroll = 0,
+            KeyCode::End => self.finished_scroll = last,
+            _ => {}
+        }
+    }
+
+    /// Every GPU the driver reports, whether selected or not.
+    pub fn all_gpus(&self) -> &[GpuInfo] {
+        self.all_gpu_metrics
+            .as_ref()
+            .map(|m| m.gpus.as_slice())
+            .unwrap_or_default()
+    }
+
+    fn handle_gpu_chooser_key(&mut self, mut cursor: usize, code: KeyCode) {
+        let last = self.all_gpus().len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('F') => {
+                self.gpu_chooser = None;
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => cursor = cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => cursor = (cursor + 1).min(last),
+            KeyCode::Char(' ') | KeyCode::Enter => self.toggle_gpu_selected(cursor),
+            KeyCode::Char('a') => {
+                self.gpu_selection = None;
+                self.apply_gpu_selection();
+            }
+            _ => {}
+        }
+        self.gpu_chooser = Some(cursor);
+    }
+
+    /// Show or hide the GPU at this position of the full GPU list.
+    fn toggle_gpu_selected(&mut self, pos: usize) {
+        let all: Vec<String> = self.all_gpus().iter().map(|g| g.stable_id()).collect();
+        let Some(id) = all.get(pos) else {
+            return;
+        };
+
+        let mut order = self.gpu_selection.clone().unwrap_or_else(|| all.clone());
+        if let Some(i) = order.iter().position(|o| o == id) {
+            if order.len() == 1 {
+                self.set_status("At least one GPU must stay selected".to_string());
+                return;
+            }
+            order.remove(i);
+        } else {
+            order.push(id.clone());
+        }
+
+        if self
+            .gpu_process_filter
+            .as_ref()
+            .is_some_and(|filter| !order.contains(filter))
+        {
+            self.gpu_process_filter = None;
+        }
+        self.gpu_selection = if order == all { None } else { Some(order) };
+        self.apply_gpu_selection();
+        self.sync_selection();
+    }
+
+    /// Add the energy used since the last refresh to the session and the open
+    /// window. Every GPU counts, selected or not. GPUs without an energy
+    /// counter fall back to integrating power.
+    fn accumulate_energy(&mut self, elapsed: Duration) {
+        let Some(ref gpu_metrics) = self.all_gpu_metrics else {
+            return;
+        };
+
+        for gpu in gpu_metrics.gpus.iter().filter(|g| g.status.is_ok()) {
+            let joules = match gpu.energy_consumption {
+                Some(mj) => match self.last_energy_counter.insert(gpu.stable_id(), mj) {
+                    Some(last) => mj.saturating_sub(last) as f64 / 1000.0,
+                    None => 0.0,
+                },
+                None => match gpu.power_usage {
+                    Reading::Value(watts) => watts as f64 * elapsed.as_secs_f64(),
+                    _ => continue,
+                },
+            };
+
+            *self.gpu_energy.entry(gpu.stable_id()).or_default() += joules;
+            if let Some(window) = self.energy_windows.last_mut().filter(|w| w.is_open()) {
+                *window.joules.entry(gpu.stable_id()).or_default() += joules;
+            }
+        }
+    }
+
+    /// Energy window currently being recorded, if any.
+    pub fn open_energy_window(&self) -> Option<&EnergyWindow> {
+        self.energy_windows.last().filter(|w| w.is_open())
+    }
+
+    /// Start a new energy window, or close the open one.
+    fn toggle_energy_window(&mut self) {
+        if let Some(window) = self.energy_windows.last_mut().filter(|w| w.is_open()) {
+            window.ended = Some(chrono::Local::now());
+            let msg = format!(
+                "Energy window '{}' closed: {}",
+                window.name,
+                format_energy(window.total())
+            );
+            self.set_status(msg);
+        } else {
+            let name = format!("window {}", self.energy_windows.len() + 1);
+            self.set_status(format!("Energy window '{}' started", name));
+            self.energy_windows.push(EnergyWindow::new(name));
+        }
+    }
+
+    /// Plain-text energy report for the session and every window, or `None`
+    /// when no GPU energy was recorded.
+    pub fn energy_summary(&self) -> Option<String> {
+        if self.gpu_energy.is_empty() {
+            return None;
+        }
+
+        let now = chrono::Local::now();
+        // GPUs are listed under their current index; one that is gone by its ID
+        let label_of = |id: &str| {
+            self.all_gpus()
+                .iter()
+                .find(|g| g.stable_id() == id)
+                .map_or_else(
+                    || (u32::MAX, format!("- {}", id)),
+                    |g| (g.index, format!("{} {}", g.index, g.name)),
+                )
+        };
+        let span = |start: chrono::DateTime<chrono::Local>,
+                    end: chrono::DateTime<chrono::Local>| {
+            format!(
+                "{} - {}, {}",
+                start.format("%Y-%m-%d %H:%M:%S"),
+                end.format("%Y-%m-%d %H:%M:%S"),
+                format_short_duration((end - start).num_seconds().max(0) as u64)
+            )
+        };
+        let per_gpu = |out: &mut String, joules: &HashMap<String, f64>| {
+            let mut gpus: Vec<((u32, String), f64)> =
+                joules.iter().map(|(id, &j)| (label_of(id), j)).collect();
+            gpus.sort_by(|a, b| a.0.cmp(&b.0));
+            for ((_, label), j) in gpus {
+                out.push_str(&format!(
+                    "  GPU {:<26} {:>12}  {:.4} kWh\n",
+                    label,
+                    format_energy(j),
+                    j / 3_600_000.0
+                ));
+            }
+            let total: f64 = joules.values().sum();
+            out.push_str(&format!(
+                "  Total {:>37}  {:.4} kWh\n",
+                format_energy(total),
+                total / 3_600_000.0
+            ));
+        };
+
+        let mut out = format!(
+            "nvglances GPU energy summary\n\nSession ({})\n",
+            span(self.started_at, now)
+        );
+        per_gpu(&mut out, &self.gpu_energy);
+
+        for window in &self.energy_windows {
+            out.push_str(&format!(
+                "\nWindow '{}' ({})\n",
+                window.name,
+                span(window.started, window.ended.unwrap_or(now))
+            ));
+            per_gpu(&mut out, &window.joules);
+        }
+
+        Some(out)
+    }
+
+    /// Append new driver events to the log and raise a header alert for
+    /// anything above informational severity.
+    fn record_gpu_events(&mut self) {
+        for event in drain_gpu_events(&mut self.gpu_handle) {
+            self.push_gpu_event(event);
+        }
+    }
+
+    /// Log an event, raising it in the header if it is the most severe.
+    fn push_gpu_event(&mut self, event: GpuEvent) {
+        let severity = event.kind.severity();
+        let replaces_alert = match &self.gpu_alert {
+            Some((current, _)) => severity >= current.kind.severity(),
+            None => true,
+        };
+        if severity > EventSeverity::Info && replaces_alert {
+            self.gpu_alert = Some((event.clone(), Instant::now()));
+        }
+
+        self.gpu_events.push_back(event);
+        if self.gpu_events.len() > MAX_GPU_EVENTS {
+            self.gpu_events.pop_front();
+        }
+    }
+
+    /// Keep lost GPUs listed under their last known identity, including ones
+    /// that no longer enumerate, and log when GPUs are lost or come back.
+    fn track_lost_gpus(&mut self) {
+        if self.all_gpu_metrics.is_none() && self.last_seen_gpus.is_empty() {
+            return;
+        }
+        // Without any backend every GPU seen so far is lost
+        let metrics = self.all_gpu_metrics.get_or_insert_with(GpuMetrics::default);
+
+        // An NVML device that cannot be opened reports no identity of its
+        // own; NVML indices do not shift with other backends' GPUs
+        let identified: HashSet<String> = metrics
+            .gpus
+            .iter()
+            .filter(|g| g.uuid.is_some())
+            .map(|g| g.stable_id())
+            .collect();
+        for gpu in metrics
+            .gpus
+            .iter_mut()
+            .filter(|g| g.backend == GpuBackend::Nvml && !g.status.is_ok() && g.uuid.is_none())
+        {
+            let last = self.last_seen_gpus.iter().find(|(id, l)| {
+                l.backend == gpu.backend && l.index == gpu.index && !identified.contains(*id)
+            });
+            if let Some((_, last)) = last {
+                *gpu = identity_of(last, gpu.status.clone());
+            }
+        }
+
+        // Indices of the other backends follow the GPUs present now, so ones
+        // that are gone are numbered after every present GPU
+        let present: HashSet<String> = metrics.gpus.iter().map(|g| g.stable_id()).collect();
+        let mut missing: Vec<&GpuInfo> = self
+            .last_seen_gpus
+            .iter()
+            .filter(|(id, _)| !present.contains(*id))
+            .map(|(_, last)| last)
+            .collect();
+        missing.sort_by_key(|g| g.index);
+        let next_index = metrics.gpus.iter().map(|g| g.index + 1).max().unwrap_or(0);
+        metrics
+            .gpus
+            .extend(missing.into_iter().zip(next_index..).map(|(last, index)| {
+                let status = GpuStatus::Failed {
+                    fault: GpuFault::NotFound,
+                    code: "NotFound".into(),
+                };
+                GpuInfo {
+                    index,
+                    ..identity_of(last, status)
+                }
+            }));
+
+        let mut events = Vec::new();
+        for gpu in metrics.gpus.iter().filter(|g| g.uuid.is_some()) {
+            let id = gpu.stable_id();
+            let kind = match self.last_seen_gpus.get(&id).map(|l| l.status.is_ok()) {
+                Some(true) if !gpu.status.is_ok() => Some(GpuEventKind::DeviceLost),
+                Some(false) if gpu.status.is_ok() => Some(GpuEventKind::DeviceRecovered),
+                _ => None,
+            };
+            if let Some(kind) = kind {
+                events.push(GpuEvent {
+                    time: chrono::Local::now(),
+                    gpu_index: gpu.index,
+                    gpu_id: id.clone(),
+                    kind,
+                });
+            }
+
+            // Keep the last good reading of a lost GPU, with its new status
+            match self.last_seen_gpus.get_mut(&id) {
+                Some(last) if !gpu.status.is_ok() => last.status = gpu.status.clone(),
+                _ => {
+                    self.last_seen_gpus.insert(id, gpu.clone());
+                }
+            }
+        }
+
+        for event in events {
+            self.push_gpu_event(event);
+        }
+    }
+
+    /// Event to flash in the header, if any.
+    pub fn header_alert(&self) -> Option<&GpuEvent> {
+        let (event, raised) = self.gpu_alert.as_ref()?;
+        (event.kind.severity() == EventSeverity::Critical || raised.elapsed() < ALERT_DURATION)
+            .then_some(event)
+    }
+
+    /// Driver events shown in the events panel, newest first, limited to the
+    /// GPU whose card is selected.
+    pub fn visible_gpu_events(&self) -> Vec<&GpuEvent> {
+        self.gpu_events
+            .iter()
+            .rev()
+            .filter(|e| {
+                self.gpu_process_filter
+                    .as_ref()
+                    .is_none_or(|id| e.gpu_id == *id)
+            })
+            .collect()
+    }
+
+    /// Current index of the GPU the process table is restricted to.
+    pub fn gpu_filter_index(&self) -> Option<u32> {
+        let id = self.gpu_process_filter.as_ref()?;
+        self.all_gpus()
+            .iter()
+            .find(|g| g.stable_id() == *id)
+            .map(|g| g.index)
+    }
+
+    /// Whether the PCIe replay counter of this GPU grew recently.
+    pub fn pcie_replays_rising(&self, gpu: &GpuInfo) -> bool {
+        self.pcie_replays
+            .get(&gpu.stable_id())
+            .and_then(|t| t.last_increase)
+            .is_some_and(|t| t.elapsed() < PCIE_REPLAY_WINDOW)
+    }
+
+    /// Whether the driver reported that this GPU fell off the bus.
+    pub fn gpu_fell_off_bus(&self, gpu: &GpuInfo) -> bool {
+        let id = gpu.stable_id();
+        self.gpu_events
+            .iter()
+            .any(|e| e.gpu_id == id && e.kind == GpuEventKind::Xid(Some(XID_FALLEN_OFF_BUS)))
+    }
+
+    fn open_events(&mut self) {
+        self.show_events = true;
+        self.events_scroll = 0;
+        self.gpu_alert = None;
+    }
+
+    fn handle_events_key(&mut self, code: KeyCode) {
+        let last = self.visible_gpu_events().len().saturating_sub(1);
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('e') => self.show_events = false,
+            KeyCode::Down | KeyCode::Char('j') => {
+                self.events_scroll = (self.events_scroll + 1).min(last)
+            }
+            KeyCode::Up | KeyCode::Char('k') => {
+                self.events_scroll = self.events_scroll.saturating_sub(1)
+            }
+            KeyCode::PageDown => self.events_scroll = (self.events_scroll + 10).min(last),
+            KeyCode::PageUp => self.events_scroll = self.events_scroll.saturating_sub(10),
+            KeyCode::Home => self.events_scroll = 0,
+            KeyCode::End => self.events_scroll = last,
+            _ => {}
+        }
+    }
+
+    /// Open the settings panel on the filtered GPU, or the first one.
+    fn open_settings(&mut self) {
+        let index = self.gpu_filter_index().or_else(|| {
+            self.gpu_metrics
+                .as_ref()
+                .and_then(|m| m.gpus.first())
+                .map(|g| g.index)
+        });
+        match index {
+            Some(index) => self.settings_panel = self.settings_panel_for(index, 0),
+            None => self.set_status("No GPU to configure".to_string()),
+        }
+    }
+
+    /// Settings panel for a GPU, with edited values starting at the current ones.
+    fn settings_panel_for(&self, gpu_index: u32, cursor: usize) -> Option<SettingsPanel> {
+        let gpu = self
+            .gpu_metrics
+            .as_ref()?
+            .gpus
+            .iter()
+            .find(|g| g.index == gpu_index)?;
+        Some(SettingsPanel {
+            gpu_index,
+            cursor,
+            power_limit: gpu
+                .power_limit
+                .value()
+                .or(gpu.settings.power_limit_default)
+                .unwrap_or(0),
+            compute_mode: gpu.settings.compute_mode.unwrap_or(ComputeMode::Default),
+            clock_lock: self.locked_clocks.get(&gpu.stable_id()).copied(),
+        })
+    }
+
+    fn handle_settings_key(&mut self, mut panel: SettingsPanel, code: KeyCode) {
+        if let Some(confirm) = self.setting_confirm.take() {
+            match code {
+                KeyCode::Char('y') | KeyCode::Char('Y') => self.execute_setting_change(&confirm),
+                KeyCode::Char('n') | KeyCode::Char('N') | KeyCode::Esc => {
+                    self.set_status("Setting change cancelled".to_string())
+                }
+                _ => self.setting_confirm = Some(confirm),
+            }
+            return;
+        }
+
+        let Some(gpu) = self
+            .gpu_metrics
+            .as_ref()
+            .and_then(|m| m.gpus.iter().find(|g| g.index == panel.gpu_index))
+        else {
+            self.settings_panel = None;
+            return;
+        };
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('S') => {
+                self.settings_panel = None;
+                return;
+            }
+            KeyCode::Up | KeyCode::Char('k') => panel.cursor = panel.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => {
+                panel.cursor = (panel.cursor + 1).min(SettingsPanel::ROWS - 1)
+            }
+            KeyCode::Tab => {
+                let gpus = &self
+                    .gpu_metrics
+                    .as_ref()
+                    .map(|m| m.gpus.as_slice())
+                    .unwrap_or_default();
+                let pos = gpus
+                    .iter()
+                    .position(|g| g.index == panel.gpu_index)
+                    .unwrap_or(0);
+                let next = gpus[(pos + 1) % gpus.len()].index;
+                if let Some(next_panel) = self.settings_panel_for(next, panel.cursor) {
+                    panel = next_panel;
+                }
+            }
+            KeyCode::Left | KeyCode::Char('h') => Self::adjust_setting(&mut panel, gpu, false),
+            KeyCode::Right | KeyCode::Char('l') => Self::adjust_setting(&mut panel, gpu, true),
+            KeyCode::Enter => {
+                if gpu.backend != GpuBackend::Nvml {
+                    self.set_status("Device settings can only be changed on NVIDIA GPUs".into());
+                } else if !self.privileged {
+                    self.set_status("Changing device settings requires root".to_string());
+                } else if let Some(uuid) = &gpu.uuid {
+                    self.setting_confirm = Some(SettingConfirmation {
+                        gpu_index: gpu.index,
+                        gpu_uuid: uuid.clone(),
+                        gpu_name: gpu.name.clone(),
+                        change: panel.change(),
+                    });
+                } else {
+                    self.set_status("GPU reports no UUID to address it by".to_string());
+                }
+            }
+            _ => {}
+        }
+
+        self.settings_panel = Some(panel);
+    }
+
+    /// Step the edited value under the cursor up or down.
+    fn adjust_setting(panel: &mut SettingsPanel, gpu: &GpuInfo, up: bool) {
+        match panel.cursor {
+            0 => {
+                let min = gpu.settings.power_limit_min.unwrap_or(panel.power_limit);
+                let max = gpu.settings.power_limit_max.unwrap_or(panel.power_limit);
+                panel.power_limit = if up {
+                    panel.power_limit.saturating_add(POWER_LIMIT_STEP)
+                } else {
+                    panel.power_limit.saturating_sub(POWER_LIMIT_STEP)
+                }
+                .clamp(min, max.max(min));
+            }
+            1 => panel.compute_mode = panel.compute_mode.cycle(up),
+            _ => {
+                // Clock steps run up to the maximum, then past it to unlocked
+                let max = gpu.max_sm_clock.or(gpu.sm_clock.value()).unwrap_or(0) / CLOCK_LOCK_STEP
+                    * CLOCK_LOCK_STEP;
+                panel.clock_lock = match (panel.clock_lock, up) {
+                    (None, true) => None,
+                    (None, false) => Some(max),
+                    (Some(mhz), true) if mhz + CLOCK_LOCK_STEP > max => None,
+                    (Some(mhz), true) => Some(mhz + CLOCK_LOCK_STEP),
+                    (Some(mhz), false) => {
+                        Some(mhz.saturating_sub(CLOCK_LOCK_STEP).max(CLOCK_LOCK_STEP))
+                    }
+                };
+            }
+        }
+    }
+
+    /// Apply a confirmed setting change to the GPU it was confirmed for, by
+    /// UUID, and report the outcome.
+    fn execute_setting_change(&mut self, confirm: &SettingConfirmation) {
+        let (gpu_index, change) = (confirm.gpu_index, confirm.change);
+        match apply_gpu_setting(&self.gpu_handle, &confirm.gpu_uuid, change) {
+            Ok(()) => {
+                if let GpuSettingChange::LockClocks(lock) = change {
+                    // The driver does not report locked clocks back; the
+                    // UUID is the GPU's stable ID
+                    match lock {
+                        Some(mhz) => self.locked_clocks.insert(confirm.gpu_uuid.clone(), mhz),
+                        None => self.locked_clocks.remove(&confirm.gpu_uuid),
+                    };
+                }
+                self.set_status(format!("GPU {}: {}", gpu_index, change.describe()));
+            }
+            Err(e) => self.set_status(format!(
+                "GPU {}: {} failed: {:#}",
+                gpu_index,
+                change.describe(),
+                e
+            )),
+        }
+    }
+
+    /// Re-select the pinned PID in both process tables after the lists change.
+    ///
+    /// If the pinned process is no longer listed, the selection stays at the
+    /// same row index and pins whichever process now occupies it. A followed
+    /// process hidden by a filter is no longer followed.
+    pub fn sync_selection(&mut self) {
+        let cpu_pids: Vec<u32> = self
+            .get_sorted_cpu_processes()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+        let gpu_pids: Vec<u32> = self
+            .get_sorted_gpu_processes()
+            .iter()
+            .map(|p| p.pid)
+            .collect();
+
+        if let Some(followed) = self.followed.clone() {
+            let (pids, selected_pid) = match followed.panel {
+                ActivePanel::CpuProcesses => (&cpu_pids, &mut self.cpu_selected_pid),
+                ActivePanel::GpuProcesses => (&gpu_pids, &mut self.gpu_selected_pid),
+            };
+            if pids.contains(&followed.pid) {
+                *selected_pid = Some(followed.pid);
+            } else {
+                // Still running (exits are reported by `check_followed_process`)
+                // but a filter hides its row
+                self.followed = None;
+                self.set_status(format!(
+                    "Stopped following {} (PID {}): hidden by the current filter",
+                    followed.name, followed.pid
+                ));
+            }
+        }
+
+        Self::sync_table(
+            &mut self.cpu_process_state,
+            &mut self.cpu_selected_pid,
+            &cpu_pids,
+        );
+        Self::sync_table(
+            &mut self.gpu_process_state,
+            &mut self.gpu_selected_pid,
+            &gpu_pids,
+        );
+    }
+
+    /// Resolve a pinned PID to its row index in a single table.
+    fn sync_table(state: &mut TableState, selected_pid: &mut Option<u32>, pids: &[u32]) {
+        if pids.is_empty() {
+            return;
+        }
+
+        let idx = selected_pid
+            .and_then(|pid| pids.iter().position(|&p| p == pid))
+            .unwrap_or_else(|| state.selected().unwrap_or(0).min(pids.len() - 1));
+
+        state.select(Some(idx));
+        *selected_pid = Some(pids[idx]);
+    }
+
+    /// Pin the PID at the current row of the active table.
+    fn remember_selection(&mut self) {
+        match self.active_panel {
+            ActivePanel::CpuProcesses => {
+                let idx = self.cpu_process_state.selected().unwrap_or(0);
+                self.cpu_selected_pid = self.get_sorted_cpu_processes().get(idx).map(|p| p.pid);
+            }
+            ActivePanel::GpuProcesses => {
+                let idx = self.gpu_process_state.selected().unwrap_or(0);
+                self.gpu_selected_pid = self.get_sorted_gpu_processes().get(idx).map(|p| p.pid);
+            }
+        }
+    }
+
+    /// Start following the selected process, or stop if already following.
+    fn toggle_follow(&mut self) {
+        if let Some(followed) = self.followed.take() {
+            self.set_status(format!("Stopped following PID {}", followed.pid));
+            return;
+        }
+
+        let selected = match self.active_panel {
+            ActivePanel::CpuProcesses => {
+                let idx = self.cpu_process_state.selected().unwrap_or(0);
+                self.get_sorted_cpu_processes()
+                    .get(idx)
+                    .map(|p| (p.pid, p.name.clone()))
+            }
+            ActivePanel::GpuProcesses => {
+                let idx = self.gpu_process_state.selected().unwrap_or(0);
+                self.get_sorted_gpu_processes()
+                    .get(idx)
+                    .map(|p| (p.pid, p.name.clone()))
+            }
+        };
+
+        if let Some((pid, name)) = selected {
+            self.set_status(format!("Following {} (PID {})", name, pid));
+            self.followed = Some(FollowedProcess {
+                pid,
+                name,
+                panel: self.active_panel,
+            });
+        }
+    }
+
+    /// Stop following and notify if the followed process has exited, or
+    /// for a follow from the GPU table, no longer uses a GPU.
+    fn check_followed_process(&mut self) {
+        let Some(ref followed) = self.followed else {
+            return;
+        };
 
-        self.gpu_metrics = collect_gpu_metrics(&self.gpu_handle, &self.system, &self.users);
+        let alive = self
+            .system_metrics
+            .processes
+            .iter()
+            .any(|p| p.pid == followed.pid);
+        let on_gpu = self
+            .all_gpu_metrics
+            .as_ref()
+            .is_some_and(|m| m.processes.iter().any(|p| p.pid == followed.pid));
 
-        self.update_history();
+        let what = if !alive && !on_gpu {
+            "exited"
+        } else if followed.panel == ActivePanel::GpuProcesses && !on_gpu {
+            "released its GPU"
+        } else {
+            return;
+        };
+        let msg = format!(
+            "Followed process {} (PID {}) {} at {}",
+            followed.name,
+            followed.pid,
+            what,
+            chrono::Local::now().format("%H:%M:%S")
+        );
+        self.followed = None;
+        self.set_status(msg);
+    }
 
-        Ok(())
+    /// Whether the given table is currently following a process.
+    pub fn is_following(&self, panel: ActivePanel) -> bool {
+        self.followed.as_ref().is_some_and(|f| f.panel == panel)
     }
 
     /// Update history data for graphs.
@@ -150,23 +1289,80 @@ impl App {
         self.history.push_memory(mem_pct);
 
         if let Some(ref gpu_metrics) = self.gpu_metrics {
-            for (i, gpu) in gpu_metrics.gpus.iter().enumerate() {
-                self.history.push_gpu_util(i, gpu.gpu_utilization as f64);
+            // A lost GPU has no readings; its graph pauses rather than drops to zero
+            for gpu in gpu_metrics.gpus.iter().filter(|g| g.status.is_ok()) {
+                let id = gpu.stable_id();
+                // Unsupported or failed readings leave a gap in the graph
+                // rather than a real-looking zero
+                let value = |reading: Reading<u32>| reading.value().map_or(f64::NAN, f64::from);
+                self.history.push_gpu_util(&id, value(gpu.gpu_utilization));
                 let mem_pct = if gpu.memory_total > 0 {
                     (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0
                 } else {
-                    0.0
+                    f64::NAN
                 };
-                self.history.push_gpu_mem(i, mem_pct);
+                self.history.push_gpu_mem(&id, mem_pct);
+                let power_pct = match (gpu.power_usage, gpu.power_limit) {
+                    (Reading::Value(usage), Reading::Value(limit)) if limit > 0 => {
+                        usage as f64 / limit as f64 * 100.0
+                    }
+                    _ => f64::NAN,
+                };
+                self.history.push_gpu_sensors(
+                    &id,
+                    power_pct,
+                    value(gpu.temperature),
+                    value(gpu.sm_clock),
+                );
+                let bytes = |reading: Reading<u64>| reading.value().map_or(f64::NAN, |b| b as f64);
+                self.history
+                    .push_gpu_pcie(&id, bytes(gpu.pcie_rx), bytes(gpu.pcie_tx));
             }
         }
 
+        self.update_process_history();
+
         let total_rx: f64 = self.system_metrics.networks.iter().map(|n| n.rx_rate).sum();
         let total_tx: f64 = self.system_metrics.networks.iter().map(|n| n.tx_rate).sum();
         self.history
             .push_network(total_rx / 1024.0 / 1024.0, total_tx / 1024.0 / 1024.0);
     }
 
+    /// Record per-process samples and evict processes that have exited.
+    fn update_process_history(&mut self) {
+        let mut gpu_memory: HashMap<u32, u64> = HashMap::new();
+        if let Some(ref gpu_metrics) = self.gpu_metrics {
+            for p in &gpu_metrics.processes {
+                *gpu_memory.entry(p.pid).or_default() += p.gpu_memory;
+            }
+        }
+
+        let mut alive = HashSet::new();
+        for p in &self.system_metrics.processes {
+            let gpu_mem = gpu_memory.remove(&p.pid).unwrap_or(0);
+            self.history
+                .push_process(p.pid, p.cpu_usage as f64, p.memory_bytes, gpu_mem);
+            alive.insert(p.pid);
+        }
+
+        // GPU processes that sysinfo cannot see (e.g. other PID namespaces)
+        for (pid, gpu_mem) in gpu_memory {
+            self.history.push_process(pid, 0.0, 0, gpu_mem);
+            alive.insert(pid);
+        }
+
+        self.history.retain_processes(&alive);
+    }
+
+    /// Container and job of a process, if the system lists it.
+    pub fn workload_of(&self, pid: u32) -> Option<&Workload> {
+        self.system_metrics
+            .processes
+            .iter()
+            .find(|p| p.pid == pid)
+            .map(|p| &p.workload)
+    }
+
     /// Get sorted CPU processes based on current sort settings.
     pub fn get_sorted_cpu_processes(&self) -> Vec<ProcessInfo> {
         let mut procs = if self.show_all_processes {
@@ -175,7 +1371,12 @@ impl App {
             self.system_metrics
                 .processes
                 .iter()
-                .filter(|p| p.cpu_usage > 0.0 || p.memory_usage > 0.1)
+                // A followed process stays listed while it idles
+                .filter(|p| {
+                    p.cpu_usage > 0.0
+                        || p.memory_usage > 0.1
+                        || self.followed.as_ref().is_some_and(|f| f.pid == p.pid)
+                })
                 .cloned()
                 .collect()
         };
@@ -221,6 +1422,11 @@ impl App {
 
         let mut procs = gpu_metrics.processes.clone();
 
+        if self.gpu_process_filter.is_some() {
+            let index = self.gpu_filter_index();
+            procs.retain(|p| Some(p.gpu_index) == index);
+        }
+
         if !self.process_filter.is_empty() {
             let filter = self.process_filter.to_lowercase();
             procs.retain(|p| {
@@ -271,6 +1477,61 @@ impl App {
             return;
         }
 
+        if self.detail_pid.is_some() {
+            self.detail_pid = None;
+            return;
+        }
+
+        if self.show_health {
+            self.show_health = false;
+            return;
+        }
+
+        if self.show_events {
+            self.handle_events_key(code);
+            return;
+        }
+
+        if self.show_topology {
+            self.show_topology = false;
+            return;
+        }
+
+        if self.show_finished {
+            self.handle_finished_key(code);
+            return;
+        }
+
+        if self.show_idle {
+            self.show_idle = false;
+            return;
+        }
+
+        if self.show_info {
+            self.show_info = false;
+            return;
+        }
+
+        if let Some(cursor) = self.gpu_chooser {
+            self.handle_gpu_chooser_key(cursor, code);
+            return;
+        }
+
+        if let Some(panel) = self.settings_panel {
+            self.handle_settings_key(panel, code);
+            return;
+        }
+
+        if let Some(menu) = self.action_menu {
+            self.handle_action_menu_key(menu, code);
+            return;
+        }
+
+        if let Some(chooser) = self.column_chooser {
+            self.handle_column_chooser_key(chooser, code, modifiers);
+            return;
+        }
+
         // Check for ctrl-modified keys first
         if modifiers.contains(KeyModifiers::CONTROL) {
             match code {
@@ -302,7 +1563,7 @@ impl App {
                 let is_metal = self
                     .gpu_metrics
                     .as_ref()
-                    .map(|m| m.backend == GpuBackend::Metal)
+                    .map(|m| m.is_metal())
                     .unwrap_or(false);
 
                 if !is_metal {
@@ -313,8 +1574,24 @@ impl App {
                 }
                 // On Metal, Tab does nothing (stays on CPU processes)
             }
-            KeyCode::Char('a') => self.show_all_processes = !self.show_all_processes,
+            KeyCode::Char('a') => {
+                self.show_all_processes = !self.show_all_processes;
+                self.sync_selection();
+            }
             KeyCode::Char('g') => self.show_graphs = !self.show_graphs,
+            KeyCode::Char('G') => self.gpu_graph_metric = self.gpu_graph_metric.next(),
+            KeyCode::Char('H') => self.show_health = true,
+            KeyCode::Char('e') => self.open_events(),
+            KeyCode::Char('t') => self.show_topology = true,
+            KeyCode::Char('i') => self.show_info = true,
+            KeyCode::Char('h') => {
+                self.show_finished = true;
+                self.finished_scroll = 0;
+            }
+            KeyCode::Char('W') => self.show_idle = true,
+            KeyCode::Char('E') => self.toggle_energy_window(),
+            KeyCode::Char('S') => self.open_settings(),
+            KeyCode::Char('F') => self.gpu_chooser = Some(0),
             KeyCode::Char('c') => self.compact_mode = !self.compact_mode,
             KeyCode::Char('1') => self.set_sort(SortColumn::Pid),
             KeyCode::Char('2') => self.set_sort(SortColumn::Name),
@@ -322,14 +1599,29 @@ impl App {
             KeyCode::Char('4') => self.set_sort(SortColumn::Cpu),
             KeyCode::Char('5') => self.set_sort(SortColumn::Memory),
             KeyCode::Char('6') => self.set_sort(SortColumn::GpuMemory),
-            KeyCode::Char('r') => self.sort_ascending = !self.sort_ascending,
+            KeyCode::Char('r') => {
+                self.sort_ascending = !self.sort_ascending;
+                self.sync_selection();
+            }
             KeyCode::Char('/') => {
                 self.process_filter.clear();
+                self.sync_selection();
+            }
+            KeyCode::Char('f') => self.toggle_follow(),
+            KeyCode::Char('o') => {
+                self.column_chooser = Some(ColumnChooser {
+                    panel: self.active_panel,
+                    cursor: 0,
+                });
             }
+            KeyCode::Enter => self.open_detail(),
             KeyCode::Down | KeyCode::Char('j') => self.move_selection(1),
             KeyCode::Up | KeyCode::Char('k') => self.move_selection(-1),
             KeyCode::PageDown => self.move_selection(10),
             KeyCode::PageUp => self.move_selection(-10),
+            KeyCode::Left => self.scroll_command(-(HSCROLL_STEP as i32)),
+            KeyCode::Right => self.scroll_command(HSCROLL_STEP as i32),
+            KeyCode::Char('w') => self.wrap_command = !self.wrap_command,
             KeyCode::Home => self.move_selection_to(0),
             KeyCode::End => self.move_selection_to(usize::MAX),
             KeyCode::Char('+') | KeyCode::Char('=') => {
@@ -347,6 +1639,60 @@ impl App {
         }
     }
 
+    /// Handle keyboard input while the column chooser is open.
+    fn handle_column_chooser_key(
+        &mut self,
+        mut chooser: ColumnChooser,
+        code: KeyCode,
+        modifiers: KeyModifiers,
+    ) {
+        let shift = modifiers.contains(KeyModifiers::SHIFT);
+
+        match chooser.panel {
+            ActivePanel::CpuProcesses => {
+                Self::edit_columns(&mut self.config.cpu_columns, &mut chooser, code, shift)
+            }
+            ActivePanel::GpuProcesses => {
+                Self::edit_columns(&mut self.config.gpu_columns, &mut chooser, code, shift)
+            }
+        }
+
+        match code {
+            KeyCode::Esc | KeyCode::Char('o') | KeyCode::Char('q') => {
+                self.column_chooser = None;
+                match self.config.save() {
+                    Ok(()) => self.set_status("Column layout saved".to_string()),
+                    Err(e) => self.set_status(format!("Failed to save config: {:#}", e)),
+                }
+            }
+            _ => self.column_chooser = Some(chooser),
+        }
+    }
+
+    /// Apply a column chooser key to a single column layout.
+    fn edit_columns<C: Column>(
+        layout: &mut ColumnLayout<C>,
+        chooser: &mut ColumnChooser,
+        code: KeyCode,
+        shift: bool,
+    ) {
+        let last = layout.columns.len().saturating_sub(1);
+
+        match code {
+            KeyCode::Up if shift => chooser.cursor = layout.shift(chooser.cursor, true),
+            KeyCode::Down if shift => chooser.cursor = layout.shift(chooser.cursor, false),
+            KeyCode::Char('K') => chooser.cursor = layout.shift(chooser.cursor, true),
+            KeyCode::Char('J') => chooser.cursor = layout.shift(chooser.cursor, false),
+            KeyCode::Up | KeyCode::Char('k') => chooser.cursor = chooser.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => chooser.cursor = (chooser.cursor + 1).min(last),
+            KeyCode::Char(' ') | KeyCode::Enter => layout.toggle(chooser.cursor),
+            KeyCode::Left | KeyCode::Char('h') => layout.resize(chooser.cursor, -1),
+            KeyCode::Right | KeyCode::Char('l') => layout.resize(chooser.cursor, 1),
+            KeyCode::Char('d') => *layout = ColumnLayout::default(),
+            _ => {}
+        }
+    }
+
     /// Request to kill a process (shows confirmation dialog).
     fn request_kill(&mut self, signal: Signal) {
         let (pid, name) = match self.active_panel {
@@ -409,45 +1755,83 @@ impl App {
 
     /// Handle mouse input.
     pub fn handle_mouse(&mut self, kind: MouseEventKind, column: u16, row: u16) {
+        if let Some(menu) = self.action_menu {
+            self.handle_action_menu_mouse(menu, kind, column, row);
+            return;
+        }
+
+        if self.show_events {
+            match kind {
+                MouseEventKind::ScrollDown => self.handle_events_key(KeyCode::Down),
+                MouseEventKind::ScrollUp => self.handle_events_key(KeyCode::Up),
+                _ => {}
+            }
+            return;
+        }
+
+        if self.show_finished {
+            match kind {
+                MouseEventKind::ScrollDown => self.handle_finished_key(KeyCode::Down),
+                MouseEventKind::ScrollUp => self.handle_finished_key(KeyCode::Up),
+                _ => {}
+            }
+            return;
+        }
+
+        // Other dialogs are keyboard-driven
+        if self.kill_confirm.is_some()
+            || self.show_help
+            || self.detail_pid.is_some()
+            || self.show_health
+            || self.show_topology
+            || self.show_idle
+            || self.show_info
+            || self.settings_panel.is_some()
+            || self.gpu_chooser.is_some()
+            || self.column_chooser.is_some()
+        {
+            return;
+        }
+
         match kind {
             MouseEventKind::Down(MouseButton::Left) => {
-                // Check if click is in CPU process area
-                if let Some(area) = self.cpu_process_area {
-                    if column >= area.x
-                        && column < area.x + area.width
-                        && row >= area.y
-                        && row < area.y + area.height
-                    {
-                        self.active_panel = ActivePanel::CpuProcesses;
-                        let relative_row = row.saturating_sub(area.y + 2);
-                        let procs = self.get_sorted_cpu_processes();
-                        if (relative_row as usize) < procs.len() {
-                            self.cpu_process_state.select(Some(relative_row as usize));
-                        }
-                        return;
-                    }
+                if let Some(gpu_index) = self.gpu_card_at(column, row) {
+                    self.toggle_gpu_process_filter(gpu_index);
+                    return;
                 }
-                // Check if click is in GPU process area (skip on Metal)
-                let is_metal = self
-                    .gpu_metrics
-                    .as_ref()
-                    .map(|m| m.backend == GpuBackend::Metal)
-                    .unwrap_or(false);
 
-                if !is_metal {
-                    if let Some(area) = self.gpu_process_area {
-                        if column >= area.x
-                            && column < area.x + area.width
-                            && row >= area.y
-                            && row < area.y + area.height
-                        {
-                            self.active_panel = ActivePanel::GpuProcesses;
-                            let relative_row = row.saturating_sub(area.y + 2);
-                            let procs = self.get_sorted_gpu_processes();
-                            if (relative_row as usize) < procs.len() {
-                                self.gpu_process_state.select(Some(relative_row as usize));
-                            }
+                match self.table_hit(column, row) {
+                    Some((panel, TableHit::Header(col))) => {
+                        self.active_panel = panel;
+                        self.sort_by_header(col);
+                    }
+                    Some((panel, TableHit::Row(idx))) => {
+                        self.active_panel = panel;
+                        if !self.select_row(idx) {
+                            return;
                         }
+                        let double_click = self.last_click.is_some_and(|(time, c, r)| {
+                            time.elapsed() < DOUBLE_CLICK_INTERVAL && c == column && r == row
+                        });
+                        if double_click {
+                            self.last_click = None;
+                            self.open_detail();
+                        } else {
+                            self.last_click = Some((Instant::now(), column, row));
+                        }
+                    }
+                    None => {}
+                }
+            }
+            MouseEventKind::Down(MouseButton::Right) => {
+                if let Some((panel, TableHit::Row(idx))) = self.table_hit(column, row) {
+                    self.active_panel = panel;
+                    if self.select_row(idx) {
+                        self.action_menu = Some(ActionMenu {
+                            x: column,
+                            y: row,
+                            cursor: 0,
+                        });
                     }
                 }
             }
@@ -457,8 +1841,228 @@ impl App {
             MouseEventKind::ScrollUp => {
                 self.move_selection(-3);
             }
+            MouseEventKind::ScrollLeft => {
+                self.scroll_command(-(HSCROLL_STEP as i32));
+            }
+            MouseEventKind::ScrollRight => {
+                self.scroll_command(HSCROLL_STEP as i32);
+            }
+            _ => {}
+        }
+    }
+
+    /// Find which process table header or row is under the cursor.
+    fn table_hit(&self, column: u16, row: u16) -> Option<(ActivePanel, TableHit)> {
+        let is_metal = self
+            .gpu_metrics
+            .as_ref()
+            .map(|m| m.is_metal())
+            .unwrap_or(false);
+
+        let mut tables = vec![(
+            ActivePanel::CpuProcesses,
+            self.cpu_process_area,
+            &self.cpu_column_areas,
+            self.cpu_process_state.offset(),
+        )];
+        // The GPU process table is only a placeholder on Metal
+        if !is_metal {
+            tables.push((
+                ActivePanel::GpuProcesses,
+                self.gpu_process_area,
+                &self.gpu_column_areas,
+                self.gpu_process_state.offset(),
+            ));
+        }
+
+        let pos = Position::new(column, row);
+        for (panel, area, column_areas, offset) in tables {
+            let Some(area) = area else {
+                continue;
+            };
+            if !area.contains(pos) {
+                continue;
+            }
+
+            // Top border, then the header row, then data rows
+            let header_y = area.y + 1;
+            if row == header_y {
+                let col = column_areas
+                    .iter()
+                    .position(|c| column >= c.x && column < c.x + c.width)?;
+                return Some((panel, TableHit::Header(col)));
+            }
+            if row > header_y && row < area.y + area.height.saturating_sub(1) {
+                let idx = self.row_index_at(panel, offset, (row - header_y - 1) as usize);
+                return Some((panel, TableHit::Row(idx)));
+            }
+            return None;
+        }
+
+        None
+    }
+
+    /// Map a screen line below the header to a row index, accounting for a wrapped row.
+    fn row_index_at(&self, panel: ActivePanel, offset: usize, line: usize) -> usize {
+        let selected = match panel {
+            ActivePanel::CpuProcesses => self.cpu_process_state.selected(),
+            ActivePanel::GpuProcesses => self.gpu_process_state.selected(),
+        };
+        let height = self.selected_row_height.max(1) as usize;
+
+        match selected {
+            Some(sel) if panel == self.active_panel && height > 1 && sel >= offset => {
+                let sel_line = sel - offset;
+                if line < sel_line {
+                    offset + line
+                } else if line < sel_line + height {
+                    sel
+                } else {
+                    offset + line - (height - 1)
+                }
+            }
+            _ => offset + line,
+        }
+    }
+
+    /// Find which GPU card is under the cursor.
+    fn gpu_card_at(&self, column: u16, row: u16) -> Option<u32> {
+        let pos = Position::new(column, row);
+        self.gpu_card_areas
+            .iter()
+            .find(|(area, _)| area.contains(pos))
+            .map(|&(_, index)| index)
+    }
+
+    /// Select a row of the active table; returns false if it is out of range.
+    fn select_row(&mut self, idx: usize) -> bool {
+        let (len, state) = match self.active_panel {
+            ActivePanel::CpuProcesses => (
+                self.get_sorted_cpu_processes().len(),
+                &mut self.cpu_process_state,
+            ),
+            ActivePanel::GpuProcesses => (
+                self.get_sorted_gpu_processes().len(),
+                &mut self.gpu_process_state,
+            ),
+        };
+
+        if idx >= len {
+            return false;
+        }
+        state.select(Some(idx));
+        self.selection_moved();
+        true
+    }
+
+    /// Sort the active table by the visible column at `col`, if it is sortable.
+    fn sort_by_header(&mut self, col: usize) {
+        let sort = match self.active_panel {
+            ActivePanel::CpuProcesses => self
+                .config
+                .cpu_columns
+                .visible()
+                .nth(col)
+                .and_then(|s| s.column.sort_column()),
+            ActivePanel::GpuProcesses => self
+                .config
+                .gpu_columns
+                .visible()
+                .nth(col)
+                .and_then(|s| s.column.sort_column()),
+        };
+
+        if let Some(sort) = sort {
+            self.set_sort(sort);
+        }
+    }
+
+    /// Restrict the GPU process table to one GPU, or clear the restriction.
+    fn toggle_gpu_process_filter(&mut self, gpu_index: u32) {
+        let Some(id) = self
+            .all_gpus()
+            .iter()
+            .find(|g| g.index == gpu_index)
+            .map(|g| g.stable_id())
+        else {
+            return;
+        };
+        if self.gpu_process_filter.as_ref() == Some(&id) {
+            self.gpu_process_filter = None;
+            self.set_status("Showing processes on all GPUs".to_string());
+        } else {
+            self.gpu_process_filter = Some(id);
+            self.set_status(format!("Showing processes on GPU {}", gpu_index));
+        }
+        self.sync_selection();
+    }
+
+    /// Open the detail view for the selected process of the active table.
+    fn open_detail(&mut self) {
+        self.detail_pid = match self.active_panel {
+            ActivePanel::CpuProcesses => self.cpu_selected_pid,
+            ActivePanel::GpuProcesses => self.gpu_selected_pid,
+        };
+    }
+
+    /// Handle keyboard input while the process context menu is open.
+    fn handle_action_menu_key(&mut self, mut menu: ActionMenu, code: KeyCode) {
+        let last = ProcessAction::ALL.len() - 1;
+        match code {
+            KeyCode::Up | KeyCode::Char('k') => menu.cursor = menu.cursor.saturating_sub(1),
+            KeyCode::Down | KeyCode::Char('j') => menu.cursor = (menu.cursor + 1).min(last),
+            KeyCode::Enter => {
+                self.action_menu = None;
+                self.execute_action(ProcessAction::ALL[menu.cursor]);
+                return;
+            }
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.action_menu = None;
+                return;
+            }
             _ => {}
         }
+        self.action_menu = Some(menu);
+    }
+
+    /// Handle mouse input while the process context menu is open.
+    fn handle_action_menu_mouse(
+        &mut self,
+        menu: ActionMenu,
+        kind: MouseEventKind,
+        column: u16,
+        row: u16,
+    ) {
+        let MouseEventKind::Down(_) = kind else {
+            return;
+        };
+
+        self.action_menu = None;
+        let Some(area) = self.action_menu_area else {
+            return;
+        };
+        if !area.contains(Position::new(column, row)) {
+            return;
+        }
+
+        // Entries start below the top border
+        let idx = row.saturating_sub(area.y + 1) as usize;
+        if let Some(&action) = ProcessAction::ALL.get(idx) {
+            self.execute_action(action);
+        } else {
+            self.action_menu = Some(menu);
+        }
+    }
+
+    /// Run a context menu action on the selected process.
+    fn execute_action(&mut self, action: ProcessAction) {
+        match action {
+            ProcessAction::Details => self.open_detail(),
+            ProcessAction::Follow => self.toggle_follow(),
+            ProcessAction::Terminate => self.request_kill(Signal::Term),
+            ProcessAction::Kill => self.request_kill(Signal::Kill),
+            ProcessAction::Interrupt => self.request_kill(Signal::Interrupt),
+        }
     }
 
     /// Set the sort column for the active panel.
@@ -481,6 +2085,7 @@ impl App {
                 }
             }
         }
+        self.sync_selection();
     }
 
     /// Move the selection by a delta.
@@ -506,6 +2111,40 @@ impl App {
             current.saturating_sub((-delta) as usize)
         };
         state.select(Some(new));
+        self.selection_moved();
+    }
+
+    /// Pin the newly selected row and stop following if the user navigated away.
+    fn selection_moved(&mut self) {
+        if self.is_following(self.active_panel) {
+            self.followed = None;
+        }
+        self.remember_selection();
+    }
+
+    /// Scroll the command column of the active table horizontally.
+    fn scroll_command(&mut self, delta: i32) {
+        let longest = match self.active_panel {
+            ActivePanel::CpuProcesses => self
+                .get_sorted_cpu_processes()
+                .iter()
+                .map(|p| p.command.width())
+                .max(),
+            ActivePanel::GpuProcesses => self
+                .get_sorted_gpu_processes()
+                .iter()
+                .map(|p| p.command.width())
+                .max(),
+        }
+        .unwrap_or(0);
+
+        let hscroll = match self.active_panel {
+            ActivePanel::CpuProcesses => &mut self.cpu_hscroll,
+            ActivePanel::GpuProcesses => &mut self.gpu_hscroll,
+        };
+        *hscroll = hscroll
+            .saturating_add_signed(delta as isize)
+            .min(longest.saturating_sub(1));
     }
 
     /// Move the selection to a specific position.
@@ -525,5 +2164,349 @@ impl App {
         };
 
         state.select(Some(pos.min(len - 1)));
+        self.selection_moved();
+    }
+}
+
+/// Whether we run as root.
+#[cfg(unix)]
+fn is_privileged(system: &System) -> bool {
+    sysinfo::get_current_pid()
+        .ok()
+        .and_then(|pid| system.process(pid))
+        .and_then(|process| process.effective_user_id())
+        .is_some_and(|uid| **uid == 0)
+}
+
+/// Administrator rights are left for the driver to check.
+#[cfg(not(unix))]
+fn is_privileged(_system: &System) -> bool {
+    true
+}
+
+/// A GPU with only the identity of `last`, for a GPU that cannot be read.
+fn identity_of(last: &GpuInfo, status: GpuStatus) -> GpuInfo {
+    GpuInfo {
+        index: last.index,
+        name: last.name.clone(),
+        backend: last.backend,
+        uuid: last.uuid.clone(),
+        serial: last.serial.clone(),
+        board_part_number: last.board_part_number.clone(),
+        vbios_version: last.vbios_version.clone(),
+        minor_number: last.minor_number,
+        pcie: PcieLink {
+            bus_id: last.pcie.bus_id.clone(),
+            ..Default::default()
+        },
+        status,
+        ..Default::default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn gpu(index: u32, utilization: u32) -> GpuInfo {
+        GpuInfo {
+            index,
+            uuid: Some(format!("GPU-{}", index)),
+            gpu_utilization: Reading::Value(utilization),
+            ..Default::default()
+        }
+    }
+
+    fn process(
+        pid: u32,
+        gpu_index: u32,
+        memory: u64,
+        sm: Option<u32>,
+        kind: &str,
+    ) -> GpuProcessInfo {
+        GpuProcessInfo {
+            pid,
+            gpu_index,
+            gpu_memory: memory,
+            sm_utilization: sm,
+            process_type: kind.into(),
+            ..Default::default()
+        }
+    }
+
+    fn app_with(processes: Vec<GpuProcessInfo>) -> App {
+        let mut app = App::with_gpu_handle(
+            &Args::default(),
+            System::new(),
+            GpuHandle::empty(),
+            Config::default(),
+        );
+        app.all_gpu_metrics = Some(GpuMetrics {
+            gpus: vec![gpu(0, 0), gpu(1, 90)],
+            processes,
+            ..Default::default()
+        });
+        app
+    }
+
+    fn following(app: &mut App, pid: u32, panel: ActivePanel) {
+        app.system_metrics.processes = vec![ProcessInfo {
+            pid,
+            name: "train".into(),
+            cpu_usage: 50.0,
+            ..Default::default()
+        }];
+        app.followed = Some(FollowedProcess {
+            pid,
+            name: "train".into(),
+            panel,
+        });
+    }
+
+    #[test]
+    fn follow_stops_when_process_releases_gpu() {
+        let mut app = app_with(vec![process(7, 0, 100, None, "C")]);
+        following(&mut app, 7, ActivePanel::GpuProcesses);
+        app.check_followed_process();
+        assert!(app.followed.is_some());
+
+        // Still running, but no longer on a GPU
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.processes.clear();
+        }
+        app.check_followed_process();
+        assert!(app.followed.is_none());
+        let (status, _) = app.status_message.as_ref().unwrap();
+        assert!(status.contains("released its GPU"), "{}", status);
+    }
+
+    #[test]
+    fn follow_from_cpu_table_survives_gpu_release() {
+        let mut app = app_with(Vec::new());
+        following(&mut app, 7, ActivePanel::CpuProcesses);
+        app.check_followed_process();
+        assert!(app.followed.is_some());
+
+        app.system_metrics.processes.clear();
+        app.check_followed_process();
+        assert!(app.followed.is_none());
+        let (status, _) = app.status_message.as_ref().unwrap();
+        assert!(status.contains("exited"), "{}", status);
+    }
+
+    #[test]
+    fn follow_stops_when_filtered_out() {
+        let mut app = app_with(Vec::new());
+        following(&mut app, 7, ActivePanel::CpuProcesses);
+        app.sync_selection();
+        assert_eq!(app.cpu_selected_pid, Some(7));
+
+        app.process_filter = "no such process".into();
+        app.sync_selection();
+        assert!(app.followed.is_none());
+    }
+
+    fn idle_pids(app: &App) -> Vec<u32> {
+        app.idle_gpu_processes().iter().map(|p| p.pid).collect()
+    }
+
+    #[test]
+    fn idle_processes_need_memory_compute_and_low_utilization() {
+        let mut app = app_with(vec![
+            // Idle GPU, no per-process figure
+            process(1, 0, 100, None, "C"),
+            // Busy GPU, but this process itself is idle
+            process(2, 1, 500, Some(1), "C"),
+            // Busy GPU, no per-process figure
+            process(3, 1, 50, None, "C"),
+            // Graphics client on the idle GPU
+            process(4, 0, 9, None, "G"),
+            // Holds no memory
+            process(5, 0, 0, None, "C"),
+            // Busy process on the idle GPU
+            process(6, 0, 100, Some(40), "C"),
+        ]);
+        app.idle_after = Duration::ZERO;
+        app.track_idle_processes();
+        // Most memory first
+        assert_eq!(idle_pids(&app), vec![2, 1]);
+    }
+
+    #[test]
+    fn idle_processes_wait_for_idle_after() {
+        let mut app = app_with(vec![process(1, 0, 100, None, "C")]);
+        app.idle_after = Duration::from_secs(600);
+        app.track_idle_processes();
+        assert!(idle_pids(&app).is_empty());
+
+        // Idle since long enough ago; a later refresh keeps the start time
+        let since = Instant::now()
+            .checked_sub(Duration::from_secs(601))
+            .unwrap();
+        app.gpu_idle_since.insert((1, "GPU-0".into()), since);
+        app.track_idle_processes();
+        assert_eq!(idle_pids(&app), vec![1]);
+        assert!(app.idle_gpu_processes()[0].idle_for >= Duration::from_secs(601));
+    }
+
+    #[test]
+    fn gpu_state_follows_stable_id_when_indices_shift() {
+        // A DRM GPU has no UUID, only its bus ID
+        let drm = GpuInfo {
+            index: 0,
+            backend: GpuBackend::Drm,
+            pcie: PcieLink {
+                bus_id: Some("0000:03:00.0".into()),
+                ..Default::default()
+            },
+            energy_consumption: Some(1000),
+            ..Default::default()
+        };
+        assert_eq!(drm.stable_id(), "0000:03:00.0");
+        assert_eq!(GpuInfo::default().stable_id(), "#0");
+
+        let mut app = app_with(Vec::new());
+        app.all_gpu_metrics = Some(GpuMetrics {
+            gpus: vec![drm.clone()],
+            ..Default::default()
+        });
+        app.gpu_selection = Some(vec![drm.stable_id()]);
+        app.gpu_process_filter = Some(drm.stable_id());
+        app.apply_gpu_selection();
+        app.accumulate_energy(Duration::from_secs(1));
+
+        // NVML comes up late and its GPU takes index 0
+        app.all_gpu_metrics = Some(GpuMetrics {
+            gpus: vec![
+                gpu(0, 50),
+                GpuInfo {
+                    index: 1,
+                    energy_consumption: Some(3000),
+                    ..drm
+                },
+            ],
+            ..Default::default()
+        });
+        app.apply_gpu_selection();
+        app.accumulate_energy(Duration::from_secs(1));
+
+        let shown: Vec<u32> = app
+            .gpu_metrics
+            .as_ref()
+            .unwrap()
+            .gpus
+            .iter()
+            .map(|g| g.index)
+            .collect();
+        assert_eq!(shown, vec![1]);
+        assert_eq!(app.gpu_filter_index(), Some(1));
+        assert_eq!(app.gpu_energy.get("0000:03:00.0"), Some(&2.0));
+        assert!(!app.gpu_energy.contains_key("GPU-0"));
+    }
+
+    #[test]
+    fn processes_on_a_lost_gpu_are_kept_running() {
+        let mut app = app_with(vec![process(7, 1, 100, None, "C")]);
+        app.track_gpu_processes();
+        assert_eq!(app.running_gpu_processes[&7].gpu_ids, vec!["GPU-1"]);
+
+        // The GPU is lost and moves to another index; its process is not listed
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.processes.clear();
+            metrics.gpus[1].status = GpuStatus::Failed {
+                fault: GpuFault::Lost,
+                code: "GpuLost".into(),
+            };
+            metrics.gpus.swap(0, 1);
+            metrics.gpus[0].index = 0;
+            metrics.gpus[1].index = 1;
+        }
+        app.track_gpu_processes();
+        assert!(app.running_gpu_processes.contains_key(&7));
+        assert!(app.finished_gpu_processes.is_empty());
+
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.gpus[0].status = GpuStatus::Ok;
+        }
+        app.track_gpu_processes();
+        assert_eq!(app.finished_gpu_processes[0].pid, 7);
+    }
+
+    #[test]
+    fn lost_gpus_do_not_take_indices_of_present_ones() {
+        let mut app = app_with(Vec::new());
+        let amd = |index, status| GpuInfo {
+            index,
+            backend: GpuBackend::Amd,
+            pcie: PcieLink {
+                bus_id: Some("0000:c3:00.0".into()),
+                ..Default::default()
+            },
+            status,
+            ..Default::default()
+        };
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.gpus.push(amd(2, GpuStatus::Ok));
+        }
+        app.track_lost_gpus();
+
+        // NVIDIA 1 stops enumerating and the AMD card follows NVIDIA 0
+        let failed = GpuStatus::Failed {
+            fault: GpuFault::Other,
+            code: "VRAM info unreadable".into(),
+        };
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.gpus = vec![gpu(0, 0), amd(1, failed)];
+        }
+        app.track_lost_gpus();
+        let gpus = &app.all_gpu_metrics.as_ref().unwrap().gpus;
+        let ids: Vec<(u32, String)> = gpus.iter().map(|g| (g.index, g.stable_id())).collect();
+        assert_eq!(
+            ids,
+            vec![
+                (0, "GPU-0".to_string()),
+                (1, "0000:c3:00.0".to_string()),
+                (2, "GPU-1".to_string()),
+            ]
+        );
+        assert!(gpus[1].backend == GpuBackend::Amd && !gpus[1].status.is_ok());
+    }
+
+    #[test]
+    fn energy_counts_gpus_outside_the_selection() {
+        let mut app = app_with(Vec::new());
+        let set_counters = |app: &mut App, mj: u64| {
+            for gpu in &mut app.all_gpu_metrics.as_mut().unwrap().gpus {
+                gpu.name = format!("Board {}", gpu.index);
+                gpu.energy_consumption = Some(mj);
+            }
+            app.apply_gpu_selection();
+        };
+        app.gpu_selection = Some(vec!["GPU-0".into()]);
+        set_counters(&mut app, 1000);
+        app.accumulate_energy(Duration::from_secs(1));
+        set_counters(&mut app, 3000);
+        app.accumulate_energy(Duration::from_secs(1));
+
+        assert_eq!(app.gpu_energy.get("GPU-0"), Some(&2.0));
+        assert_eq!(app.gpu_energy.get("GPU-1"), Some(&2.0));
+        let summary = app.energy_summary().unwrap();
+        assert!(summary.contains("GPU 0 Board 0"), "{}", summary);
+        assert!(summary.contains("GPU 1 Board 1"), "{}", summary);
+    }
+
+    #[test]
+    fn idle_processes_reset_when_busy() {
+        let mut app = app_with(vec![process(1, 0, 100, Some(0), "C")]);
+        app.idle_after = Duration::ZERO;
+        app.track_idle_processes();
+        assert_eq!(idle_pids(&app), vec![1]);
+
+        if let Some(metrics) = app.all_gpu_metrics.as_mut() {
+            metrics.processes[0].sm_utilization = Some(80);
+        }
+        app.track_idle_processes();
+        assert!(idle_pids(&app).is_empty());
+        assert!(app.gpu_idle_since.is_empty());
     }
 }
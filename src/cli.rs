@@ -0,0 +1,210 @@
+//! Command-line arguments.
+
+use std::path::PathBuf;
+use std::time::Duration;
+
+use anyhow::{bail, Context, Result};
+
+use crate::types::GpuSelector;
+
+const USAGE: &str = "\
+Usage: nvglances [OPTIONS]
+
+Options:
+      --gpus <LIST>           Only show these GPUs, in this order: indices, UUIDs
+                              or PCI bus IDs separated by commas. Defaults to
+                              CUDA_VISIBLE_DEVICES, then the UUIDs in
+                              NVIDIA_VISIBLE_DEVICES
+      --energy-window <NAME>  Start a named energy accounting window at launch
+      --energy-report <FILE>  Also write the energy summary to FILE on exit
+      --idle-after <TIME>     List processes holding GPU memory on an idle GPU
+                              for this long as wasting it, e.g. 90s, 10m or 2h
+                              [default: 10m]
+  -h, --help                  Print help
+  -V, --version               Print version";
+
+/// Options given on the command line.
+#[derive(Default)]
+pub struct Args {
+    /// GPU selection list and where it came from
+    pub gpus: Option<(String, &'static str)>,
+    /// Why the selection list taken from the environment may be off
+    pub gpus_warning: Option<String>,
+    pub energy_window: Option<String>,
+    pub energy_report: Option<PathBuf>,
+    pub idle_after: Option<Duration>,
+}
+
+impl Args {
+    /// Parse the process arguments, exiting for `--help` and `--version`.
+    pub fn parse() -> Result<Self> {
+        let mut args = Self::default();
+        let mut iter = std::env::args().skip(1);
+
+        while let Some(arg) = iter.next() {
+            // Accept both `--flag value` and `--flag=value`
+            let (flag, inline) = match arg.split_once('=') {
+                Some((flag, value)) if flag.starts_with("--") => {
+                    (flag.to_string(), Some(value.to_string()))
+                }
+                _ => (arg.clone(), None),
+            };
+            let mut value = || {
+                inline
+                    .clone()
+                    .or_else(|| iter.next())
+                    .with_context(|| format!("{} requires a value\n\n{}", flag, USAGE))
+            };
+
+            match flag.as_str() {
+                "--gpus" => args.gpus = Some((value()?, "--gpus")),
+                "--energy-window" => args.energy_window = Some(value()?),
+                "--energy-report" => args.energy_report = Some(PathBuf::from(value()?)),
+                "--idle-after" => args.idle_after = Some(parse_duration(&value()?)?),
+                "-h" | "--help" => {
+                    println!("{}", USAGE);
+                    std::process::exit(0);
+                }
+                "-V" | "--version" => {
+                    println!("nvglances {}", env!("CARGO_PKG_VERSION"));
+                    std::process::exit(0);
+                }
+                _ => bail!("Unknown argument: {}\n\n{}", arg, USAGE),
+            }
+        }
+
+        if args.gpus.is_none() {
+            let var = |name| std::env::var(name).ok();
+            (args.gpus, args.gpus_warning) = visible_devices(
+                var("CUDA_VISIBLE_DEVICES"),
+                var("NVIDIA_VISIBLE_DEVICES"),
+                var("CUDA_DEVICE_ORDER"),
+            );
+        }
+
+        Ok(args)
+    }
+}
+
+/// Parse a span of time given in seconds, or with an `s`, `m` or `h` suffix.
+fn parse_duration(value: &str) -> Result<Duration> {
+    let value = value.trim();
+    let invalid = || format!("Invalid duration: {} (use e.g. 90s, 10m or 2h)", value);
+    let (number, unit) = match value.find(|c: char| !c.is_ascii_digit()) {
+        Some(at) => value.split_at(at),
+        None => (value, "s"),
+    };
+    let scale = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 3600,
+        _ => bail!(invalid()),
+    };
+    let number: u64 = number.parse().with_context(invalid)?;
+    let secs = number.checked_mul(scale).with_context(invalid)?;
+    Ok(Duration::from_secs(secs))
+}
+
+/// GPU list from the variables CUDA and the NVIDIA container runtime use,
+/// given the values of `CUDA_VISIBLE_DEVICES`, `NVIDIA_VISIBLE_DEVICES` and
+/// `CUDA_DEVICE_ORDER`, with a warning when its indices may not be CUDA's.
+///
+/// Indices are matched against NVML's PCI bus order, which is what CUDA uses
+/// with `CUDA_DEVICE_ORDER=PCI_BUS_ID`; by default CUDA puts the fastest GPU
+/// first. The container runtime has already applied the indices in
+/// `NVIDIA_VISIBLE_DEVICES` by the time we run, so only its UUIDs are used.
+fn visible_devices(
+    cuda: Option<String>,
+    nvidia: Option<String>,
+    order: Option<String>,
+) -> (Option<(String, &'static str)>, Option<String>) {
+    // The container runtime's keywords do not name specific GPUs
+    let names_gpus = |value: &String| !matches!(value.trim(), "" | "all" | "none" | "void");
+
+    if let Some(cuda) = cuda.filter(names_gpus) {
+        let by_index = GpuSelector::parse_list(&cuda)
+            .iter()
+            .any(|s| matches!(s, GpuSelector::Index(_)));
+        let warning = (by_index && order.as_deref() != Some("PCI_BUS_ID")).then(|| {
+            "CUDA_DEVICE_ORDER is not PCI_BUS_ID: CUDA_VISIBLE_DEVICES indices are taken \
+             in PCI bus order and may not match CUDA's"
+                .to_string()
+        });
+        return (Some((cuda, "CUDA_VISIBLE_DEVICES")), warning);
+    }
+
+    let uuids: Vec<&str> = nvidia
+        .as_ref()
+        .filter(|value| names_gpus(value))
+        .map(|value| {
+            value
+                .split(',')
+                .map(str::trim)
+                .filter(|e| !e.is_empty() && !e.bytes().all(|b| b.is_ascii_digit()))
+                .collect()
+        })
+        .unwrap_or_default();
+    let gpus = (!uuids.is_empty()).then(|| (uuids.join(","), "NVIDIA_VISIBLE_DEVICES"));
+    (gpus, None)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_units() {
+        assert_eq!(parse_duration("90").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(parse_duration("10m").unwrap(), Duration::from_secs(600));
+        assert_eq!(parse_duration(" 2h ").unwrap(), Duration::from_secs(7200));
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid() {
+        for value in ["", "m", "5d", "1.5h", "-3s", "10 m"] {
+            assert!(parse_duration(value).is_err(), "{:?}", value);
+        }
+    }
+
+    #[test]
+    fn visible_devices_from_cuda() {
+        let some = |v: &str| Some(v.to_string());
+        let (gpus, warning) = visible_devices(some("1,0"), some("GPU-aa"), some("PCI_BUS_ID"));
+        assert_eq!(gpus, Some(("1,0".to_string(), "CUDA_VISIBLE_DEVICES")));
+        assert_eq!(warning, None);
+
+        // CUDA's default order is fastest first, which NVML does not know
+        let (_, warning) = visible_devices(some("1"), None, None);
+        assert!(warning.is_some());
+        let (_, warning) = visible_devices(some("1"), None, some("FASTEST_FIRST"));
+        assert!(warning.is_some());
+        let (_, warning) = visible_devices(some("GPU-3f2a"), None, None);
+        assert_eq!(warning, None);
+    }
+
+    #[test]
+    fn visible_devices_from_container_runtime() {
+        let some = |v: &str| Some(v.to_string());
+        // Indices were applied by the runtime and are meaningless inside
+        assert_eq!(visible_devices(None, some("2,3"), None), (None, None));
+        assert_eq!(
+            visible_devices(None, some("GPU-3f2a, 1,GPU-77b0"), None).0,
+            Some(("GPU-3f2a,GPU-77b0".to_string(), "NVIDIA_VISIBLE_DEVICES"))
+        );
+        for keyword in ["all", "none", "void", ""] {
+            assert_eq!(visible_devices(None, some(keyword), None), (None, None));
+        }
+        // An empty CUDA list does not hide the runtime's
+        assert_eq!(
+            visible_devices(some(""), some("GPU-aa"), None).0,
+            Some(("GPU-aa".to_string(), "NVIDIA_VISIBLE_DEVICES"))
+        );
+    }
+
+    #[test]
+    fn parse_duration_rejects_overflow() {
+        assert!(parse_duration(&format!("{}h", u64::MAX / 60)).is_err());
+        assert!(parse_duration("99999999999999999999").is_err());
+    }
+}
@@ -0,0 +1,385 @@
+//! Process table column definitions and user-configurable column layouts.
+
+use crate::types::SortColumn;
+
+/// Static description of a process table column.
+pub trait Column: Copy + PartialEq + 'static {
+    /// Every column in default display order.
+    const ALL: &'static [Self];
+
+    /// Stable identifier used in the config file.
+    fn key(self) -> &'static str;
+    /// Header label.
+    fn header(self) -> &'static str;
+    /// Width used when the config does not override it.
+    fn default_width(self) -> u16;
+    /// Whether the column is shown out of the box.
+    fn default_visible(self) -> bool;
+    /// Sort column activated by this header, if any.
+    fn sort_column(self) -> Option<SortColumn> {
+        None
+    }
+    /// Whether the column grows to fill the remaining width.
+    fn flexible(self) -> bool {
+        false
+    }
+}
+
+/// Columns available in the CPU process table.
+#[derive(Clone, Copy, PartialEq)]
+pub enum CpuColumn {
+    Pid,
+    User,
+    Cpu,
+    CpuHistory,
+    MemoryPercent,
+    Memory,
+    Status,
+    Threads,
+    IoRead,
+    IoWrite,
+    StartTime,
+    Container,
+    Job,
+    Name,
+    Command,
+}
+
+impl Column for CpuColumn {
+    const ALL: &'static [Self] = &[
+        Self::Pid,
+        Self::User,
+        Self::Cpu,
+        Self::CpuHistory,
+        Self::MemoryPercent,
+        Self::Memory,
+        Self::Status,
+        Self::Threads,
+        Self::IoRead,
+        Self::IoWrite,
+        Self::StartTime,
+        Self::Container,
+        Self::Job,
+        Self::Name,
+        Self::Command,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Pid => "pid",
+            Self::User => "user",
+            Self::Cpu => "cpu",
+            Self::CpuHistory => "cpu_hist",
+            Self::MemoryPercent => "mem_pct",
+            Self::Memory => "mem",
+            Self::Status => "status",
+            Self::Threads => "threads",
+            Self::IoRead => "io_read",
+            Self::IoWrite => "io_write",
+            Self::StartTime => "start",
+            Self::Container => "container",
+            Self::Job => "job",
+            Self::Name => "name",
+            Self::Command => "command",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Pid => "PID",
+            Self::User => "USER",
+            Self::Cpu => "CPU%",
+            Self::CpuHistory => "CPU HIST",
+            Self::MemoryPercent => "MEM%",
+            Self::Memory => "MEM",
+            Self::Status => "STATUS",
+            Self::Threads => "THR",
+            Self::IoRead => "READ/s",
+            Self::IoWrite => "WRITE/s",
+            Self::StartTime => "START",
+            Self::Container => "CONTAINER",
+            Self::Job => "JOB",
+            Self::Name => "NAME",
+            Self::Command => "COMMAND",
+        }
+    }
+
+    fn default_width(self) -> u16 {
+        match self {
+            Self::Pid => 7,
+            Self::User => 10,
+            Self::Cpu => 6,
+            Self::CpuHistory => 8,
+            Self::MemoryPercent => 6,
+            Self::Memory => 9,
+            Self::Status => 8,
+            Self::Threads => 5,
+            Self::IoRead | Self::IoWrite => 10,
+            Self::StartTime => 8,
+            Self::Container => 12,
+            Self::Job => 10,
+            Self::Name => 15,
+            Self::Command => 20,
+        }
+    }
+
+    fn default_visible(self) -> bool {
+        !matches!(
+            self,
+            Self::Threads
+                | Self::IoRead
+                | Self::IoWrite
+                | Self::StartTime
+                | Self::Container
+                | Self::Job
+        )
+    }
+
+    fn sort_column(self) -> Option<SortColumn> {
+        match self {
+            Self::Pid => Some(SortColumn::Pid),
+            Self::User => Some(SortColumn::User),
+            Self::Cpu => Some(SortColumn::Cpu),
+            Self::MemoryPercent => Some(SortColumn::Memory),
+            Self::Name => Some(SortColumn::Name),
+            _ => None,
+        }
+    }
+
+    fn flexible(self) -> bool {
+        self == Self::Command
+    }
+}
+
+/// Columns available in the GPU process table.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GpuColumn {
+    Pid,
+    Gpu,
+    Type,
+    User,
+    Utilization,
+    GpuMemory,
+    GpuMemoryHistory,
+    Container,
+    Job,
+    Name,
+    Command,
+}
+
+impl Column for GpuColumn {
+    const ALL: &'static [Self] = &[
+        Self::Pid,
+        Self::Gpu,
+        Self::Type,
+        Self::User,
+        Self::Utilization,
+        Self::GpuMemory,
+        Self::GpuMemoryHistory,
+        Self::Container,
+        Self::Job,
+        Self::Name,
+        Self::Command,
+    ];
+
+    fn key(self) -> &'static str {
+        match self {
+            Self::Pid => "pid",
+            Self::Gpu => "gpu",
+            Self::Type => "type",
+            Self::User => "user",
+            Self::Utilization => "sm",
+            Self::GpuMemory => "gpu_mem",
+            Self::GpuMemoryHistory => "mem_hist",
+            Self::Container => "container",
+            Self::Job => "job",
+            Self::Name => "name",
+            Self::Command => "command",
+        }
+    }
+
+    fn header(self) -> &'static str {
+        match self {
+            Self::Pid => "PID",
+            Self::Gpu => "GPU",
+            Self::Type => "TYPE",
+            Self::User => "USER",
+            Self::Utilization => "SM%",
+            Self::GpuMemory => "GPU_MEM",
+            Self::GpuMemoryHistory => "MEM HIST",
+            Self::Container => "CONTAINER",
+            Self::Job => "JOB",
+            Self::Name => "NAME",
+            Self::Command => "COMMAND",
+        }
+    }
+
+    fn default_width(self) -> u16 {
+        match self {
+            Self::Pid => 7,
+            Self::Gpu => 6,
+            Self::Type => 5,
+            Self::User => 10,
+            Self::Utilization => 5,
+            Self::GpuMemory => 10,
+            Self::GpuMemoryHistory => 8,
+            Self::Container => 12,
+            Self::Job => 10,
+            Self::Name => 15,
+            Self::Command => 20,
+        }
+    }
+
+    fn default_visible(self) -> bool {
+        !matches!(self, Self::Container | Self::Job)
+    }
+
+    fn sort_column(self) -> Option<SortColumn> {
+        match self {
+            Self::Pid => Some(SortColumn::Pid),
+            Self::User => Some(SortColumn::User),
+            Self::Utilization => Some(SortColumn::Cpu),
+            Self::GpuMemory => Some(SortColumn::GpuMemory),
+            Self::Name => Some(SortColumn::Name),
+            _ => None,
+        }
+    }
+
+    fn flexible(self) -> bool {
+        self == Self::Command
+    }
+}
+
+/// Visibility and width of one column in a layout.
+#[derive(Clone, Copy)]
+pub struct ColumnSetting<C: Column> {
+    pub column: C,
+    pub visible: bool,
+    pub width: u16,
+}
+
+/// Ordered column settings for one process table.
+#[derive(Clone)]
+pub struct ColumnLayout<C: Column> {
+    pub columns: Vec<ColumnSetting<C>>,
+}
+
+/// Narrowest width a column can be resized to.
+const MIN_WIDTH: u16 = 3;
+/// Widest width a column can be resized to.
+const MAX_WIDTH: u16 = 80;
+
+impl<C: Column> Default for ColumnLayout<C> {
+    fn default() -> Self {
+        Self {
+            columns: C::ALL
+                .iter()
+                .map(|&column| ColumnSetting {
+                    column,
+                    visible: column.default_visible(),
+                    width: column.default_width(),
+                })
+                .collect(),
+        }
+    }
+}
+
+impl<C: Column> ColumnLayout<C> {
+    /// Parse a layout from its config representation.
+    ///
+    /// The format is a comma-separated list of `key:width` entries in display
+    /// order, with hidden columns prefixed by `!`. Unknown keys are ignored and
+    /// columns missing from the list are appended with their defaults.
+    pub fn parse(value: &str) -> Self {
+        let mut columns: Vec<ColumnSetting<C>> = Vec::new();
+
+        for entry in value.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            let (visible, entry) = match entry.strip_prefix('!') {
+                Some(rest) => (false, rest),
+                None => (true, entry),
+            };
+            let (key, width) = match entry.split_once(':') {
+                Some((key, width)) => (key.trim(), width.trim().parse::<u16>().ok()),
+                None => (entry, None),
+            };
+
+            let Some(&column) = C::ALL.iter().find(|c| c.key() == key) else {
+                continue;
+            };
+            if columns.iter().any(|s| s.column == column) {
+                continue;
+            }
+
+            columns.push(ColumnSetting {
+                column,
+                visible,
+                width: width
+                    .unwrap_or(column.default_width())
+                    .clamp(MIN_WIDTH, MAX_WIDTH),
+            });
+        }
+
+        for &column in C::ALL {
+            if !columns.iter().any(|s| s.column == column) {
+                columns.push(ColumnSetting {
+                    column,
+                    visible: column.default_visible(),
+                    width: column.default_width(),
+                });
+            }
+        }
+
+        Self { columns }
+    }
+
+    /// Serialize the layout for the config file.
+    pub fn to_config_string(&self) -> String {
+        self.columns
+            .iter()
+            .map(|s| {
+                format!(
+                    "{}{}:{}",
+                    if s.visible { "" } else { "!" },
+                    s.column.key(),
+                    s.width
+                )
+            })
+            .collect::<Vec<_>>()
+            .join(",")
+    }
+
+    /// Visible columns in display order.
+    pub fn visible(&self) -> impl Iterator<Item = &ColumnSetting<C>> {
+        self.columns.iter().filter(|s| s.visible)
+    }
+
+    /// Show or hide the column at `idx`.
+    pub fn toggle(&mut self, idx: usize) {
+        if let Some(setting) = self.columns.get_mut(idx) {
+            setting.visible = !setting.visible;
+        }
+    }
+
+    /// Move the column at `idx` one position up or down; returns the new index.
+    pub fn shift(&mut self, idx: usize, up: bool) -> usize {
+        if up && idx > 0 && idx < self.columns.len() {
+            self.columns.swap(idx, idx - 1);
+            idx - 1
+        } else if !up && idx + 1 < self.columns.len() {
+            self.columns.swap(idx, idx + 1);
+            idx + 1
+        } else {
+            idx
+        }
+    }
+
+    /// Grow or shrink the column at `idx`.
+    pub fn resize(&mut self, idx: usize, delta: i16) {
+        if let Some(setting) = self.columns.get_mut(idx) {
+            setting.width = setting
+                .width
+                .saturating_add_signed(delta)
+                .clamp(MIN_WIDTH, MAX_WIDTH);
+        }
+    }
+}
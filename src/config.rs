@@ -0,0 +1,81 @@
+//! Persistent user configuration.
+//!
+//! Settings live in a plain `key = value` file at
+//! `$XDG_CONFIG_HOME/nvglances/config` (or `~/.config/nvglances/config`).
+//! Blank lines and lines starting with `#` are ignored, as are unknown keys.
+
+use std::fs;
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+
+use crate::columns::{ColumnLayout, CpuColumn, GpuColumn};
+
+/// User settings persisted between runs.
+#[derive(Clone, Default)]
+pub struct Config {
+    pub cpu_columns: ColumnLayout<CpuColumn>,
+    pub gpu_columns: ColumnLayout<GpuColumn>,
+}
+
+impl Config {
+    /// Location of the config file, if a home directory can be determined.
+    pub fn path() -> Option<PathBuf> {
+        let base = std::env::var_os("XDG_CONFIG_HOME")
+            .filter(|v| !v.is_empty())
+            .map(PathBuf::from)
+            .or_else(|| std::env::var_os("APPDATA").map(PathBuf::from))
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".config")))?;
+        Some(base.join("nvglances").join("config"))
+    }
+
+    /// Load the config file, falling back to defaults if it is missing or unreadable.
+    pub fn load() -> Self {
+        Self::path()
+            .and_then(|path| fs::read_to_string(path).ok())
+            .map(|contents| Self::parse(&contents))
+            .unwrap_or_default()
+    }
+
+    /// Parse config file contents.
+    pub fn parse(contents: &str) -> Self {
+        let mut config = Self::default();
+
+        for line in contents.lines() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            match key.trim() {
+                "cpu_columns" => config.cpu_columns = ColumnLayout::parse(value),
+                "gpu_columns" => config.gpu_columns = ColumnLayout::parse(value),
+                _ => {}
+            }
+        }
+
+        config
+    }
+
+    /// Write the config file, creating its directory if needed.
+    pub fn save(&self) -> Result<()> {
+        let path = Self::path().context("Could not determine config directory")?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)
+                .with_context(|| format!("Failed to create {}", dir.display()))?;
+        }
+
+        let contents = format!(
+            "# nvglances configuration\n\
+             cpu_columns = {}\n\
+             gpu_columns = {}\n",
+            self.cpu_columns.to_config_string(),
+            self.gpu_columns.to_config_string(),
+        );
+
+        fs::write(&path, contents).with_context(|| format!("Failed to write {}", path.display()))
+    }
+}
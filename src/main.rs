@@ -3,11 +3,15 @@
 //! Combines the best of glances and nvitop into a single terminal application.
 
 mod app;
+mod cli;
+mod columns;
+mod config;
 mod metrics;
 mod types;
 mod ui;
 mod utils;
 
+use std::fs;
 use std::io;
 use std::time::{Duration, Instant};
 
@@ -20,9 +24,12 @@ use crossterm::{
 use ratatui::{backend::CrosstermBackend, Terminal};
 
 use app::App;
+use cli::Args;
 use ui::render_ui;
 
 fn main() -> Result<()> {
+    let args = Args::parse()?;
+
     // Setup terminal
     enable_raw_mode().context("Failed to enable raw mode")?;
     let mut stdout = io::stdout();
@@ -32,7 +39,7 @@ fn main() -> Result<()> {
     let mut terminal = Terminal::new(backend).context("Failed to create terminal")?;
 
     // Create app
-    let mut app = App::new().context("Failed to initialize application")?;
+    let mut app = App::new(&args).context("Failed to initialize application")?;
 
     // Main loop
     let result = run_app(&mut terminal, &mut app);
@@ -47,6 +54,14 @@ fn main() -> Result<()> {
     .context("Failed to leave alternate screen")?;
     terminal.show_cursor().context("Failed to show cursor")?;
 
+    if let Some(summary) = app.energy_summary() {
+        print!("{}", summary);
+        if let Some(path) = &app.energy_report {
+            fs::write(path, &summary)
+                .with_context(|| format!("Failed to write {}", path.display()))?;
+        }
+    }
+
     result
 }
 
@@ -0,0 +1,432 @@
+//! AMD GPU metrics from amdgpu sysfs and DRM fdinfo (Linux).
+//!
+//! Device metrics come from `/sys/class/drm/cardN/device` and its hwmon
+//! directory, per-process usage from the `drm-*` keys amdgpu writes to
+//! `/proc/<pid>/fdinfo`. Both roots, and that of the ROCm install the
+//! version is read from, are configurable so the parsers can run against a
+//! copy of the tree.
+
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{System, Users};
+
+use super::drm::{self, read_string, read_u64, DrmCard};
+use crate::types::{
+    BackendInfo, CounterDeltas, GpuBackend, GpuFault, GpuInfo, GpuMetrics, GpuSettings, GpuStatus,
+    PcieLink, Reading, TempThresholds,
+};
+
+/// AMD devices and where to read them from.
+pub struct AmdHandle {
+    sysfs: PathBuf,
+    procfs: PathBuf,
+    /// ROCm install, for its version; `ROCM_PATH` or `/opt/rocm`.
+    rocm: PathBuf,
+    cards: Vec<DrmCard>,
+}
+
+impl AmdHandle {
+    /// Find amdgpu cards on this machine.
+    pub fn new() -> Option<Self> {
+        let rocm = std::env::var_os("ROCM_PATH").unwrap_or_else(|| "/opt/rocm".into());
+        Some(Self::discover(Path::new("/sys"), Path::new("/proc"))?.with_rocm(Path::new(&rocm)))
+    }
+
+    /// Find amdgpu cards under `sysfs`; processes are read from `procfs`.
+    pub fn discover(sysfs: &Path, procfs: &Path) -> Option<Self> {
+        let cards = drm::find_cards(sysfs, |driver| driver == "amdgpu");
+        if cards.is_empty() {
+            return None;
+        }
+        Some(Self {
+            sysfs: sysfs.to_path_buf(),
+            procfs: procfs.to_path_buf(),
+            rocm: PathBuf::from("/opt/rocm"),
+            cards,
+        })
+    }
+
+    /// Read the ROCm version from the install at `rocm`.
+    pub fn with_rocm(self, rocm: &Path) -> Self {
+        Self {
+            rocm: rocm.to_path_buf(),
+            ..self
+        }
+    }
+}
+
+/// Collect metrics of every amdgpu card.
+///
+/// `counters` keeps each DRM client's cumulative engine time between calls
+/// so per-process utilization can be derived over `elapsed`.
+pub fn collect_amd_metrics(
+    handle: &AmdHandle,
+    system: &System,
+    users: &Users,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
+) -> Option<GpuMetrics> {
+    let gpus: Vec<GpuInfo> = handle
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| collect_card(i as u32, card))
+        .collect();
+    let mut processes = drm::collect_processes(
+        &handle.procfs,
+        &handle.cards,
+        system,
+        users,
+        counters,
+        elapsed,
+    )
+    .processes;
+    // ROCm processes register with the compute driver (KFD) as well
+    for process in &mut processes {
+        let kfd = format!("class/kfd/kfd/proc/{}", process.pid);
+        if handle.sysfs.join(kfd).exists() {
+            process.process_type = "C".into();
+        }
+    }
+
+    let driver_version = drm::driver_version(&handle.sysfs, &handle.procfs, "amdgpu");
+    let rocm_version =
+        read_string(&handle.rocm.join(".info/version")).unwrap_or_else(|| "N/A".into());
+
+    Some(GpuMetrics {
+        topology: vec![vec![None; gpus.len()]; gpus.len()],
+        gpus,
+        processes,
+        drivers: vec![BackendInfo {
+            backend: GpuBackend::Amd,
+            driver_version,
+            api_version: rocm_version,
+        }],
+    })
+}
+
+fn collect_card(index: u32, card: &DrmCard) -> GpuInfo {
+    let device = &card.device;
+    let name = read_string(&device.join("product_name"))
+        .filter(|name| !name.is_empty())
+        .or_else(|| {
+            read_string(&device.join("device"))
+                .map(|id| format!("AMD GPU {}", id.trim_start_matches("0x")))
+        })
+        .unwrap_or_else(|| "AMD GPU".into());
+
+    let (Some(memory_total), Some(memory_used)) = (
+        read_u64(&device.join("mem_info_vram_total")),
+        read_u64(&device.join("mem_info_vram_used")),
+    ) else {
+        return GpuInfo {
+            index,
+            name,
+            backend: GpuBackend::Amd,
+            pcie: PcieLink {
+                bus_id: Some(card.bus_id.clone()),
+                ..Default::default()
+            },
+            status: GpuStatus::Failed {
+                fault: GpuFault::Other,
+                code: "VRAM info unreadable".into(),
+            },
+            ..Default::default()
+        };
+    };
+
+    let hwmon = drm::hwmon_dir(device);
+    let sensors = hwmon.as_deref().map(read_temperatures).unwrap_or_default();
+    let hwmon_value = |file: &str| hwmon.as_ref().and_then(|dir| read_u64(&dir.join(file)));
+    let microwatts_to_watts = |file: &str| hwmon_value(file).map(|uw| (uw / 1_000_000) as u32);
+
+    // Newer boards report instantaneous power, older ones an average
+    let power_usage =
+        microwatts_to_watts("power1_input").or_else(|| microwatts_to_watts("power1_average"));
+    let (sm_clock, max_sm_clock, sm_level) = read_dpm(&device.join("pp_dpm_sclk"));
+    let (mem_clock, max_mem_clock, _) = read_dpm(&device.join("pp_dpm_mclk"));
+
+    GpuInfo {
+        index,
+        name,
+        backend: GpuBackend::Amd,
+        uuid: read_string(&device.join("unique_id")),
+        serial: read_string(&device.join("serial_number")),
+        board_part_number: read_string(&device.join("product_number")),
+        vbios_version: read_string(&device.join("vbios_version")),
+        minor_number: None,
+        // Data center boards have no edge sensor, only the junction hotspot
+        temperature: Reading::from(sensors.edge.or(sensors.junction).map(|t| t.current)),
+        memory_temperature: sensors.mem.map(|t| t.current),
+        thresholds: TempThresholds {
+            slowdown: sensors.edge.or(sensors.junction).and_then(|t| t.critical),
+            shutdown: sensors.edge.or(sensors.junction).and_then(|t| t.emergency),
+            memory_max: sensors.mem.and_then(|t| t.critical),
+        },
+        fan_speeds: match &hwmon {
+            Some(dir) => Reading::Value(read_fans(dir)),
+            None => Reading::NotSupported,
+        },
+        power_usage: Reading::from(power_usage),
+        power_limit: Reading::from(microwatts_to_watts("power1_cap")),
+        gpu_utilization: Reading::from(
+            read_u64(&device.join("gpu_busy_percent")).map(|p| p as u32),
+        ),
+        memory_utilization: Reading::from(
+            read_u64(&device.join("mem_busy_percent")).map(|p| p as u32),
+        ),
+        memory_used,
+        memory_total,
+        encoder_utilization: Reading::NotSupported,
+        decoder_utilization: Reading::NotSupported,
+        pcie_rx: Reading::NotSupported,
+        pcie_tx: Reading::NotSupported,
+        sm_clock: Reading::from(sm_clock),
+        mem_clock: Reading::from(mem_clock),
+        max_sm_clock,
+        max_mem_clock,
+        boost_clock: None,
+        // The DPM level plays the role of NVIDIA's performance state
+        pstate: Reading::from(sm_level.map(|level| format!("DPM{}", level))),
+        pcie: drm::pcie_link(card),
+        numa_node: drm::numa_node(device),
+        cpu_affinity: read_string(&device.join("local_cpulist")),
+        // hwmon reports microjoules, GpuInfo millijoules
+        energy_consumption: hwmon_value("energy1_input").map(|uj| uj / 1000),
+        settings: GpuSettings {
+            power_limit_min: microwatts_to_watts("power1_cap_min"),
+            power_limit_max: microwatts_to_watts("power1_cap_max"),
+            power_limit_default: microwatts_to_watts("power1_cap_default"),
+            ..Default::default()
+        },
+        status: GpuStatus::Ok,
+        ..Default::default()
+    }
+}
+
+/// A hwmon temperature sensor in °C.
+#[derive(Clone, Copy)]
+struct TempSensor {
+    current: u32,
+    critical: Option<u32>,
+    emergency: Option<u32>,
+}
+
+#[derive(Default)]
+struct Temperatures {
+    edge: Option<TempSensor>,
+    junction: Option<TempSensor>,
+    mem: Option<TempSensor>,
+}
+
+/// Read the labeled temperature sensors; hwmon reports millidegrees.
+fn read_temperatures(hwmon: &Path) -> Temperatures {
+    let mut temps = Temperatures::default();
+    for n in 1..=8 {
+        let Some(label) = read_string(&hwmon.join(format!("temp{}_label", n))) else {
+            continue;
+        };
+        let degrees = |suffix: &str| {
+            read_u64(&hwmon.join(format!("temp{}_{}", n, suffix))).map(|m| (m / 1000) as u32)
+        };
+        let Some(current) = degrees("input") else {
+            continue;
+        };
+        let sensor = Some(TempSensor {
+            current,
+            critical: degrees("crit"),
+            emergency: degrees("emergency"),
+        });
+        match label.as_str() {
+            "edge" => temps.edge = sensor,
+            "junction" => temps.junction = sensor,
+            "mem" => temps.mem = sensor,
+            _ => {}
+        }
+    }
+    temps
+}
+
+/// Fan duty cycles in percent, from the 0-255 PWM values.
+fn read_fans(hwmon: &Path) -> Vec<u32> {
+    (1..=8)
+        .map_while(|n| read_u64(&hwmon.join(format!("pwm{}", n))))
+        .map(|pwm| (pwm * 100 / 255) as u32)
+        .collect()
+}
+
+/// Current clock, highest clock and current level from a DPM table such as
+/// `pp_dpm_sclk`, where each line reads `1: 1200Mhz` and the active one ends
+/// in `*`.
+fn read_dpm(path: &Path) -> (Option<u32>, Option<u32>, Option<u32>) {
+    let Some(table) = read_string(path) else {
+        return (None, None, None);
+    };
+    let mut current = None;
+    let mut max = None;
+    let mut level = None;
+    for line in table.lines() {
+        let Some((index, rest)) = line.split_once(':') else {
+            continue;
+        };
+        let Some(mhz) = rest
+            .trim()
+            .to_lowercase()
+            .split("mhz")
+            .next()
+            .and_then(|mhz| mhz.trim().parse::<u32>().ok())
+        else {
+            continue;
+        };
+        max = max.max(Some(mhz));
+        if rest.trim_end().ends_with('*') {
+            current = Some(mhz);
+            level = index.trim().parse().ok();
+        }
+    }
+    (current, max, level)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::drm::fixture::Fixture;
+    use super::*;
+
+    /// An RX 7900 XTX at 0000:03:00.0 running at DPM level 1.
+    fn amd_card(fixture: &Fixture) {
+        let device = fixture.card("card0", "amdgpu", "0000:03:00.0");
+        let files = [
+            ("product_name", "Radeon RX 7900 XTX\n"),
+            ("unique_id", "1a2b3c4d5e6f7081\n"),
+            ("mem_info_vram_total", "25753026560\n"),
+            ("mem_info_vram_used", "1073741824\n"),
+            ("gpu_busy_percent", "37\n"),
+            ("pp_dpm_sclk", "0: 500Mhz \n1: 1800Mhz *\n2: 2526Mhz \n"),
+            ("pp_dpm_mclk", "0: 96Mhz \n1: 456Mhz \n2: 1249Mhz *\n"),
+            ("current_link_speed", "16.0 GT/s PCIe\n"),
+            ("max_link_speed", "16.0 GT/s PCIe\n"),
+            ("current_link_width", "16\n"),
+            ("max_link_width", "16\n"),
+            ("hwmon/hwmon3/temp1_label", "edge\n"),
+            ("hwmon/hwmon3/temp1_input", "45000\n"),
+            ("hwmon/hwmon3/temp1_crit", "100000\n"),
+            ("hwmon/hwmon3/power1_average", "52000000\n"),
+            ("hwmon/hwmon3/pwm1", "51\n"),
+        ];
+        for (file, contents) in files {
+            fixture.write(device.join(file), contents);
+        }
+    }
+
+    #[test]
+    fn discover_only_amdgpu_cards() {
+        let fixture = Fixture::new("amd-discover");
+        assert!(AmdHandle::discover(&fixture.sysfs(), &fixture.procfs()).is_none());
+
+        fixture.card("card0", "i915", "0000:00:02.0");
+        assert!(AmdHandle::discover(&fixture.sysfs(), &fixture.procfs()).is_none());
+
+        amd_card(&fixture);
+        fixture.card("card1", "amdgpu", "0000:0c:00.0");
+        let handle = AmdHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let ids: Vec<&str> = handle.cards.iter().map(|c| c.bus_id.as_str()).collect();
+        assert_eq!(ids, ["0000:03:00.0", "0000:0c:00.0"]);
+    }
+
+    #[test]
+    fn dpm_table_active_level() {
+        let fixture = Fixture::new("amd-dpm");
+        fixture.write("sclk", "0: 500Mhz \n1: 1800Mhz *\n2: 2526Mhz \n");
+        fixture.write("fixed", "0: 1000MHz *\n");
+        fixture.write("garbage", "S: 1200Mhz\nnot a table\n");
+
+        assert_eq!(
+            read_dpm(&fixture.path("sclk")),
+            (Some(1800), Some(2526), Some(1))
+        );
+        assert_eq!(
+            read_dpm(&fixture.path("fixed")),
+            (Some(1000), Some(1000), Some(0))
+        );
+        assert_eq!(read_dpm(&fixture.path("garbage")), (None, Some(1200), None));
+        assert_eq!(read_dpm(&fixture.path("missing")), (None, None, None));
+    }
+
+    #[test]
+    fn card_metrics_and_process_usage() {
+        let fixture = Fixture::new("amd-metrics");
+        amd_card(&fixture);
+        fixture.write("sys/module/amdgpu/version", "6.7.0\n");
+        fixture.write("rocm/.info/version", "6.2.1-112\n");
+        fixture.write("sys/class/kfd/kfd/proc/4242/pasid", "32769\n");
+        let handle = AmdHandle::discover(&fixture.sysfs(), &fixture.procfs())
+            .unwrap()
+            .with_rocm(&fixture.path("rocm"));
+
+        let (system, users) = (System::new(), Users::new());
+        let mut counters = CounterDeltas::default();
+        let mut sample = |gfx_ns: u64| {
+            fixture.fdinfo(
+                4242,
+                5,
+                &format!(
+                    "drm-driver:\tamdgpu\ndrm-pdev:\t0000:03:00.0\ndrm-client-id:\t11\n\
+                     drm-memory-vram:\t524288 KiB\ndrm-engine-gfx:\t{} ns\n",
+                    gfx_ns
+                ),
+            );
+            let metrics = collect_amd_metrics(
+                &handle,
+                &system,
+                &users,
+                &mut counters,
+                Duration::from_millis(500),
+            )
+            .unwrap();
+            counters.finish();
+            metrics
+        };
+
+        let metrics = sample(2_000_000_000);
+        let gpu = &metrics.gpus[0];
+        assert_eq!(gpu.name, "Radeon RX 7900 XTX");
+        assert_eq!(gpu.memory_total, 25_753_026_560);
+        assert_eq!(gpu.memory_used, 1 << 30);
+        assert_eq!(gpu.gpu_utilization.value(), Some(37));
+        assert_eq!(gpu.sm_clock.value(), Some(1800));
+        assert_eq!(gpu.max_sm_clock, Some(2526));
+        assert_eq!(gpu.mem_clock.value(), Some(1249));
+        assert_eq!(gpu.pstate.clone().value().as_deref(), Some("DPM1"));
+        assert_eq!(
+            (gpu.pcie.current_gen, gpu.pcie.current_width),
+            (Some(4), Some(16))
+        );
+        assert_eq!(gpu.temperature.value(), Some(45));
+        assert_eq!(gpu.thresholds.slowdown, Some(100));
+        assert_eq!(gpu.power_usage.value(), Some(52));
+        assert_eq!(gpu.fan_speeds.clone().value(), Some(vec![20]));
+        assert_eq!(metrics.drivers[0].driver_version, "6.7.0");
+        assert_eq!(metrics.drivers[0].api_version, "6.2.1-112");
+
+        let process = &metrics.processes[0];
+        assert_eq!((process.pid, process.gpu_memory), (4242, 512 << 20));
+        // Registered with KFD, so a ROCm compute process
+        assert_eq!(process.process_type, "C");
+        assert_eq!(process.sm_utilization, None);
+
+        // 100 ms of gfx time over a 500 ms refresh
+        let metrics = sample(2_100_000_000);
+        assert_eq!(metrics.processes[0].sm_utilization, Some(20));
+    }
+
+    #[test]
+    fn unreadable_vram_marks_the_card_failed() {
+        let fixture = Fixture::new("amd-novram");
+        fixture.card("card0", "amdgpu", "0000:03:00.0");
+        let handle = AmdHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let gpu = collect_card(0, &handle.cards[0]);
+        assert_eq!(gpu.name, "AMD GPU");
+        assert_eq!(gpu.pcie.bus_id.as_deref(), Some("0000:03:00.0"));
+        assert!(matches!(gpu.status, GpuStatus::Failed { .. }));
+    }
+}
@@ -0,0 +1,719 @@
+//! GPUs driven through the kernel DRM subsystem (Linux): card discovery and
+//! attributes in sysfs, per-client usage from the `drm-*` keys drivers write
+//! to `/proc/<pid>/fdinfo`, and a vendor-neutral backend built on them for
+//! drivers without one of their own.
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{System, Users};
+
+use super::gpu::get_process_info;
+use crate::types::{
+    BackendInfo, CounterDeltas, GpuBackend, GpuInfo, GpuMetrics, GpuProcessInfo, PcieLink, Reading,
+};
+
+/// Drivers with a backend of their own, or whose GPUs NVML covers.
+const VENDOR_DRIVERS: [&str; 5] = ["amdgpu", "i915", "xe", "nvidia", "nvidia-drm"];
+
+/// A DRM card found in sysfs.
+pub struct DrmCard {
+    /// `/sys/class/drm/cardN`.
+    pub card: PathBuf,
+    /// `/sys/class/drm/cardN/device`.
+    pub device: PathBuf,
+    /// PCI bus ID, or the device name for platform devices, as in `drm-pdev`.
+    pub bus_id: String,
+    /// Kernel driver name, as in `drm-driver`.
+    pub driver: String,
+}
+
+/// Cards whose driver is accepted by `wanted`, in PCI bus order like NVML.
+pub fn find_cards(sysfs: &Path, wanted: impl Fn(&str) -> bool) -> Vec<DrmCard> {
+    let Ok(entries) = fs::read_dir(sysfs.join("class/drm")) else {
+        return Vec::new();
+    };
+    let mut cards: Vec<DrmCard> = entries
+        .flatten()
+        .filter(|entry| is_card_name(&entry.file_name().to_string_lossy()))
+        .filter_map(|entry| {
+            let device = entry.path().join("device");
+            let uevent = read_uevent(&device.join("uevent"));
+            let driver = uevent.get("DRIVER")?;
+            if !wanted(driver) {
+                return None;
+            }
+            // SoC GPUs are platform devices, named after their node
+            let bus_id = match uevent.get("PCI_SLOT_NAME") {
+                Some(slot) => slot.to_lowercase(),
+                None => fs::canonicalize(&device)
+                    .ok()?
+                    .file_name()?
+                    .to_string_lossy()
+                    .into_owned(),
+            };
+            Some(DrmCard {
+                card: entry.path(),
+                bus_id,
+                driver: driver.clone(),
+                device,
+            })
+        })
+        .collect();
+    cards.sort_by(|a, b| a.bus_id.cmp(&b.bus_id));
+    cards.dedup_by(|a, b| a.bus_id == b.bus_id);
+    cards
+}
+
+/// `card0` but not connectors such as `card0-DP-1`.
+fn is_card_name(name: &str) -> bool {
+    name.strip_prefix("card")
+        .is_some_and(|n| !n.is_empty() && n.bytes().all(|b| b.is_ascii_digit()))
+}
+
+/// `KEY=value` lines of a sysfs `uevent` file.
+fn read_uevent(path: &Path) -> HashMap<String, String> {
+    fs::read_to_string(path)
+        .unwrap_or_default()
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| (key.to_string(), value.to_string()))
+        .collect()
+}
+
+/// Version of a kernel driver. In-tree drivers have none of their own and
+/// ship with the kernel, so its release is used instead.
+pub fn driver_version(sysfs: &Path, procfs: &Path, driver: &str) -> String {
+    read_string(&sysfs.join(format!("module/{}/version", driver)))
+        .or_else(|| read_string(&procfs.join("sys/kernel/osrelease")))
+        .unwrap_or_else(|| "N/A".into())
+}
+
+/// The hwmon directory of a device, if its driver registered one.
+pub fn hwmon_dir(device: &Path) -> Option<PathBuf> {
+    fs::read_dir(device.join("hwmon"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| {
+            path.file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("hwmon"))
+        })
+}
+
+/// PCIe address and link state of a card. Integrated GPUs have no link.
+pub fn pcie_link(card: &DrmCard) -> PcieLink {
+    let device = &card.device;
+    PcieLink {
+        bus_id: Some(card.bus_id.clone()),
+        current_gen: read_link_gen(&device.join("current_link_speed")),
+        max_gen: read_link_gen(&device.join("max_link_speed")),
+        current_width: read_u64(&device.join("current_link_width")).map(|w| w as u32),
+        max_width: read_u64(&device.join("max_link_width")).map(|w| w as u32),
+        replay_count: None,
+    }
+}
+
+/// PCIe generation from a sysfs link speed such as `16.0 GT/s PCIe`.
+fn read_link_gen(path: &Path) -> Option<u32> {
+    let speed = read_string(path)?;
+    let gts: f64 = speed.split_whitespace().next()?.parse().ok()?;
+    match gts as u32 {
+        2 => Some(1),
+        5 => Some(2),
+        8 => Some(3),
+        16 => Some(4),
+        32 => Some(5),
+        64 => Some(6),
+        _ => None,
+    }
+}
+
+/// NUMA node of a device; -1 means the platform has no NUMA information.
+pub fn numa_node(device: &Path) -> Option<u32> {
+    read_string(&device.join("numa_node"))?
+        .parse::<i32>()
+        .ok()?
+        .try_into()
+        .ok()
+}
+
+/// Cumulative busy time of one engine class of a client.
+struct EngineTime {
+    /// Nanoseconds, or GPU cycles when `total` is set.
+    busy: u64,
+    /// Cycles elapsed on the GPU, for drivers that report cycles (xe).
+    total: Option<u64>,
+    /// Engines of this class the time is summed over.
+    capacity: u32,
+}
+
+/// A DRM client as described by one fdinfo file.
+pub struct DrmClient {
+    pub driver: String,
+    pub pdev: String,
+    pub client_id: u64,
+    /// Resident memory per region, in bytes.
+    pub memory: HashMap<String, u64>,
+    engines: HashMap<String, EngineTime>,
+}
+
+impl DrmClient {
+    /// Memory in the GPU's own regions (`vram`, `local0`, `vram0`), or in any
+    /// region for integrated GPUs, which have none.
+    pub fn device_memory(&self) -> u64 {
+        let local = |region: &String| region.starts_with("vram") || region.starts_with("local");
+        if self.memory.keys().any(local) {
+            self.memory
+                .iter()
+                .filter(|(region, _)| local(region))
+                .map(|(_, bytes)| bytes)
+                .sum()
+        } else {
+            self.memory.values().sum()
+        }
+    }
+
+    /// Whether the client has used the compute engine (`ccs` on xe).
+    pub fn uses_compute(&self) -> bool {
+        ["compute", "ccs"]
+            .iter()
+            .any(|engine| self.engines.get(*engine).is_some_and(|e| e.busy > 0))
+    }
+}
+
+/// Parse the `drm-*` keys of an fdinfo file, or `None` if the file is not
+/// a DRM client.
+pub fn parse_fdinfo(text: &str) -> Option<DrmClient> {
+    let keys: HashMap<&str, &str> = text
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim(), value.trim()))
+        .collect();
+
+    // drm-memory-* is the older name for drm-resident-*
+    let mut memory = HashMap::new();
+    for prefix in ["drm-memory-", "drm-resident-"] {
+        for (key, value) in &keys {
+            if let (Some(region), Some(bytes)) = (key.strip_prefix(prefix), parse_size(value)) {
+                memory.insert(region.to_string(), bytes);
+            }
+        }
+    }
+
+    let capacity = |engine: &str| {
+        keys.get(format!("drm-engine-capacity-{}", engine).as_str())
+            .and_then(|n| n.parse().ok())
+            .unwrap_or(1)
+    };
+    let mut engines = HashMap::new();
+    for (key, value) in &keys {
+        if let Some(engine) = key
+            .strip_prefix("drm-engine-")
+            .filter(|e| !e.starts_with("capacity-"))
+        {
+            if let Some(ns) = value.strip_suffix("ns").and_then(|n| n.trim().parse().ok()) {
+                engines.insert(
+                    engine.to_string(),
+                    EngineTime {
+                        busy: ns,
+                        total: None,
+                        capacity: capacity(engine),
+                    },
+                );
+            }
+        } else if let Some(engine) = key.strip_prefix("drm-cycles-") {
+            let total = keys
+                .get(format!("drm-total-cycles-{}", engine).as_str())
+                .and_then(|n| n.parse().ok());
+            if let (Ok(cycles), Some(total)) = (value.parse(), total) {
+                engines.insert(
+                    engine.to_string(),
+                    EngineTime {
+                        busy: cycles,
+                        total: Some(total),
+                        capacity: capacity(engine),
+                    },
+                );
+            }
+        }
+    }
+
+    Some(DrmClient {
+        driver: keys.get("drm-driver")?.to_string(),
+        pdev: keys.get("drm-pdev")?.to_lowercase(),
+        client_id: keys.get("drm-client-id")?.parse().ok()?,
+        memory,
+        engines,
+    })
+}
+
+/// Bytes in a DRM memory value such as `1024 KiB`.
+fn parse_size(value: &str) -> Option<u64> {
+    let mut parts = value.split_whitespace();
+    let amount: u64 = parts.next()?.parse().ok()?;
+    let scale = match parts.next() {
+        None => 1,
+        Some("KiB") => 1 << 10,
+        Some("MiB") => 1 << 20,
+        Some("GiB") => 1 << 30,
+        Some(_) => return None,
+    };
+    Some(amount * scale)
+}
+
+/// What the DRM clients on a set of cards did since the last refresh.
+#[derive(Default)]
+pub struct DrmUsage {
+    pub processes: Vec<GpuProcessInfo>,
+    /// Busy percentage of each engine class per GPU index, over all clients.
+    pub engines: HashMap<u32, HashMap<String, f64>>,
+}
+
+impl DrmUsage {
+    /// Load of a GPU: its busiest engine class, as engines run concurrently.
+    /// `None` if no client reported engine time.
+    pub fn gpu_utilization(&self, gpu_index: u32) -> Option<u32> {
+        self.engines
+            .get(&gpu_index)?
+            .values()
+            .copied()
+            .reduce(f64::max)
+            .map(|pct| pct.min(100.0) as u32)
+    }
+}
+
+/// Processes with clients open on `cards`, with the device memory they hold
+/// and how busy they kept each GPU since the last refresh. A process on
+/// several cards is listed once per card; `gpu_index` is the position in
+/// `cards`.
+pub fn collect_processes(
+    procfs: &Path,
+    cards: &[DrmCard],
+    system: &System,
+    users: &Users,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
+) -> DrmUsage {
+    let mut usage = DrmUsage::default();
+    let Ok(entries) = fs::read_dir(procfs) else {
+        return usage;
+    };
+    let elapsed_ns = elapsed.as_nanos().max(1) as u64;
+
+    for entry in entries.flatten() {
+        let Ok(pid) = entry.file_name().to_string_lossy().parse::<u32>() else {
+            continue;
+        };
+        // fdinfo of other users' processes is only readable as root
+        let Ok(fds) = fs::read_dir(entry.path().join("fdinfo")) else {
+            continue;
+        };
+
+        // Each fd onto the same client repeats its numbers; count it once
+        let mut clients: HashMap<(String, u64), DrmClient> = HashMap::new();
+        for fd in fds.flatten() {
+            if let Some(client) = fs::read_to_string(fd.path())
+                .ok()
+                .and_then(|text| parse_fdinfo(&text))
+            {
+                clients.insert((client.pdev.clone(), client.client_id), client);
+            }
+        }
+
+        let mut per_gpu: HashMap<u32, (u64, bool, HashMap<&str, f64>)> = HashMap::new();
+        for client in clients.values() {
+            let Some(gpu_index) = cards
+                .iter()
+                .position(|c| c.bus_id == client.pdev && c.driver == client.driver)
+            else {
+                continue;
+            };
+            let (memory, compute, busy) = per_gpu.entry(gpu_index as u32).or_default();
+            *memory += client.device_memory();
+            *compute |= client.uses_compute();
+
+            for (engine, time) in &client.engines {
+                let key = format!("drm/{}/{}/{}", pid, client.client_id, engine);
+                let busy_delta = counters.delta(key.clone() + "/busy", time.busy);
+                let total_delta = match time.total {
+                    Some(total) => counters.delta(key + "/total", total),
+                    None => Some(elapsed_ns),
+                };
+                if let (Some(busy_delta), Some(total_delta)) = (busy_delta, total_delta) {
+                    *busy.entry(engine).or_default() += busy_delta as f64
+                        / (total_delta.max(1) * time.capacity.max(1) as u64) as f64
+                        * 100.0;
+                }
+            }
+        }
+
+        for (gpu_index, (memory, compute, busy)) in per_gpu {
+            let gpu_engines = usage.engines.entry(gpu_index).or_default();
+            for (engine, pct) in &busy {
+                *gpu_engines.entry(engine.to_string()).or_default() += pct;
+            }
+            let (name, user, command) = get_process_info(system, users, pid);
+            usage.processes.push(GpuProcessInfo {
+                pid,
+                name,
+                user,
+                gpu_index,
+                gpu_memory: memory,
+                // The busiest engine, as engines run concurrently
+                sm_utilization: busy
+                    .values()
+                    .copied()
+                    .reduce(f64::max)
+                    .map(|pct| pct.min(100.0) as u32),
+                command,
+                process_type: if compute { "C" } else { "G" }.into(),
+                mig_instance: None,
+            });
+        }
+    }
+    usage
+}
+
+/// GPUs of drivers without a backend of their own, such as msm, panfrost,
+/// v3d or virtio-gpu.
+pub struct DrmHandle {
+    sysfs: PathBuf,
+    procfs: PathBuf,
+    cards: Vec<DrmCard>,
+}
+
+impl DrmHandle {
+    /// Find such GPUs on this machine.
+    pub fn new() -> Option<Self> {
+        Self::discover(Path::new("/sys"), Path::new("/proc"))
+    }
+
+    /// Find such GPUs under `sysfs`; processes are read from `procfs`. Only
+    /// cards with a render node count, which leaves out display-only drivers
+    /// such as BMC consoles and simpledrm.
+    pub fn discover(sysfs: &Path, procfs: &Path) -> Option<Self> {
+        let cards: Vec<DrmCard> = find_cards(sysfs, |driver| !VENDOR_DRIVERS.contains(&driver))
+            .into_iter()
+            .filter(|card| has_render_node(&card.device))
+            .collect();
+        if cards.is_empty() {
+            return None;
+        }
+        Some(Self {
+            sysfs: sysfs.to_path_buf(),
+            procfs: procfs.to_path_buf(),
+            cards,
+        })
+    }
+}
+
+fn has_render_node(device: &Path) -> bool {
+    fs::read_dir(device.join("drm")).is_ok_and(|entries| {
+        entries
+            .flatten()
+            .any(|entry| entry.file_name().to_string_lossy().starts_with("renderD"))
+    })
+}
+
+/// Collect metrics of GPUs from their sysfs attributes and DRM clients alone.
+///
+/// `counters` keeps each DRM client's engine time between calls so
+/// utilization can be derived over `elapsed`.
+pub fn collect_drm_metrics(
+    handle: &DrmHandle,
+    system: &System,
+    users: &Users,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
+) -> Option<GpuMetrics> {
+    let usage = collect_processes(
+        &handle.procfs,
+        &handle.cards,
+        system,
+        users,
+        counters,
+        elapsed,
+    );
+    let gpus: Vec<GpuInfo> = handle
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| collect_card(i as u32, card, &usage))
+        .collect();
+
+    let mut drivers: Vec<&str> = handle.cards.iter().map(|c| c.driver.as_str()).collect();
+    drivers.sort_unstable();
+    drivers.dedup();
+    Some(GpuMetrics {
+        topology: vec![vec![None; gpus.len()]; gpus.len()],
+        gpus,
+        processes: usage.processes,
+        drivers: vec![BackendInfo {
+            backend: GpuBackend::Drm,
+            driver_version: driver_version(&handle.sysfs, &handle.procfs, drivers[0]),
+            api_version: drivers.join("/"),
+        }],
+    })
+}
+
+fn collect_card(index: u32, card: &DrmCard, usage: &DrmUsage) -> GpuInfo {
+    let clients: Vec<&GpuProcessInfo> = usage
+        .processes
+        .iter()
+        .filter(|p| p.gpu_index == index)
+        .collect();
+
+    // SoC GPUs scale their clock through devfreq, in Hz
+    let devfreq = fs::read_dir(card.device.join("devfreq"))
+        .ok()
+        .and_then(|mut entries| entries.next()?.ok())
+        .map(|entry| entry.path());
+    let mhz = |file: &str| {
+        devfreq
+            .as_ref()
+            .and_then(|dir| read_u64(&dir.join(file)))
+            .map(|hz| (hz / 1_000_000) as u32)
+    };
+    let temperature = hwmon_dir(&card.device)
+        .and_then(|dir| read_u64(&dir.join("temp1_input")))
+        .map(|millidegrees| (millidegrees / 1000) as u32);
+
+    GpuInfo {
+        index,
+        name: format!("{} GPU", card.driver),
+        backend: GpuBackend::Drm,
+        temperature: temperature.into(),
+        fan_speeds: Reading::NotSupported,
+        // The clients' engine time is all these drivers report about load
+        gpu_utilization: usage.gpu_utilization(index).into(),
+        // Memory in use is only known from the clients, the total not at all
+        memory_used: clients.iter().map(|p| p.gpu_memory).sum(),
+        sm_clock: mhz("cur_freq").into(),
+        max_sm_clock: mhz("max_freq"),
+        pcie: pcie_link(card),
+        numa_node: numa_node(&card.device),
+        ..Default::default()
+    }
+}
+
+pub fn read_string(path: &Path) -> Option<String> {
+    fs::read_to_string(path).ok().map(|s| s.trim().to_string())
+}
+
+pub fn read_u64(path: &Path) -> Option<u64> {
+    read_string(path)?.parse().ok()
+}
+
+/// A scratch `sys` and `proc` tree for the sysfs and fdinfo parsers.
+#[cfg(test)]
+pub(crate) mod fixture {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    pub struct Fixture {
+        root: PathBuf,
+    }
+
+    impl Fixture {
+        pub fn new(name: &str) -> Self {
+            let root =
+                std::env::temp_dir().join(format!("nvglances-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&root);
+            fs::create_dir_all(&root).unwrap();
+            Self { root }
+        }
+
+        /// `path`, relative to the root.
+        pub fn path(&self, path: impl AsRef<Path>) -> PathBuf {
+            self.root.join(path)
+        }
+
+        pub fn sysfs(&self) -> PathBuf {
+            self.path("sys")
+        }
+
+        pub fn procfs(&self) -> PathBuf {
+            self.path("proc")
+        }
+
+        /// Write `contents` to `path`, relative to the root.
+        pub fn write(&self, path: impl AsRef<Path>, contents: &str) {
+            let path = self.path(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(path, contents).unwrap();
+        }
+
+        /// Add `/sys/class/drm/<card>` bound to `driver` at PCI `slot`, and
+        /// return its device directory relative to the root.
+        pub fn card(&self, card: &str, driver: &str, slot: &str) -> PathBuf {
+            let device = Path::new("sys/class/drm").join(card).join("device");
+            self.write(
+                device.join("uevent"),
+                &format!("DRIVER={}\nPCI_SLOT_NAME={}\n", driver, slot),
+            );
+            device
+        }
+
+        /// Write the fdinfo of one open DRM file of `pid`.
+        pub fn fdinfo(&self, pid: u32, fd: u32, contents: &str) {
+            self.write(format!("proc/{}/fdinfo/{}", pid, fd), contents);
+        }
+    }
+
+    impl Drop for Fixture {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.root);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::fixture::Fixture;
+    use super::*;
+
+    const AMD_FDINFO: &str = "pos:\t0\nflags:\t02100002\ndrm-driver:\tamdgpu\n\
+        drm-pdev:\t0000:03:00.0\ndrm-client-id:\t7\ndrm-memory-vram:\t2048 KiB\n\
+        drm-memory-gtt:\t512 KiB\ndrm-engine-gfx:\t1000000 ns\ndrm-engine-compute:\t0 ns\n";
+
+    #[test]
+    fn cards_found_in_bus_order_without_connectors() {
+        let fixture = Fixture::new("drm-cards");
+        fixture.card("card1", "amdgpu", "0000:03:00.0");
+        fixture.card("card0", "i915", "0000:00:02.0");
+        fixture.write("sys/class/drm/card1-DP-1/device/uevent", "DRIVER=amdgpu\n");
+        fixture.write("sys/class/drm/renderD128/device/uevent", "DRIVER=amdgpu\n");
+
+        let all = find_cards(&fixture.sysfs(), |_| true);
+        let ids: Vec<&str> = all.iter().map(|c| c.bus_id.as_str()).collect();
+        assert_eq!(ids, ["0000:00:02.0", "0000:03:00.0"]);
+        assert_eq!(all[1].driver, "amdgpu");
+        assert!(all[1].device.ends_with("card1/device"));
+
+        let amd = find_cards(&fixture.sysfs(), |driver| driver == "amdgpu");
+        assert_eq!(amd.len(), 1);
+        assert!(find_cards(Path::new("/nonexistent"), |_| true).is_empty());
+    }
+
+    #[test]
+    fn fdinfo_memory_and_engines() {
+        let client = parse_fdinfo(AMD_FDINFO).unwrap();
+        assert_eq!((client.driver.as_str(), client.client_id), ("amdgpu", 7));
+        assert_eq!(client.pdev, "0000:03:00.0");
+        assert_eq!(client.memory["vram"], 2048 << 10);
+        // VRAM only; the GTT lives in system memory
+        assert_eq!(client.device_memory(), 2048 << 10);
+        assert_eq!(client.engines["gfx"].busy, 1_000_000);
+        assert!(!client.uses_compute());
+
+        // Integrated GPUs have only system memory regions
+        let igpu = parse_fdinfo(
+            "drm-driver: i915\ndrm-pdev: 0000:00:02.0\ndrm-client-id: 3\n\
+             drm-resident-system0: 4 MiB\ndrm-engine-render: 10 ns\n\
+             drm-engine-capacity-video: 2\ndrm-engine-video: 20 ns\n",
+        )
+        .unwrap();
+        assert_eq!(igpu.device_memory(), 4 << 20);
+        assert_eq!(igpu.engines["video"].capacity, 2);
+        assert!(!igpu.engines.contains_key("capacity-video"));
+
+        assert!(parse_fdinfo("pos:\t0\nflags:\t0100002\nmnt_id:\t15\n").is_none());
+    }
+
+    #[test]
+    fn memory_sizes() {
+        assert_eq!(parse_size("1234"), Some(1234));
+        assert_eq!(parse_size("3 KiB"), Some(3 << 10));
+        assert_eq!(parse_size("3 MiB"), Some(3 << 20));
+        assert_eq!(parse_size("3 GiB"), Some(3 << 30));
+        assert_eq!(parse_size("3 TiB"), None);
+        assert_eq!(parse_size("KiB"), None);
+    }
+
+    #[test]
+    fn pcie_generation_and_width() {
+        let fixture = Fixture::new("drm-pcie");
+        let device = fixture.card("card0", "amdgpu", "0000:03:00.0");
+        fixture.write(device.join("current_link_speed"), "2.5 GT/s PCIe\n");
+        fixture.write(device.join("max_link_speed"), "16.0 GT/s PCIe\n");
+        fixture.write(device.join("current_link_width"), "8\n");
+        fixture.write(device.join("max_link_width"), "16\n");
+
+        let cards = find_cards(&fixture.sysfs(), |_| true);
+        let link = pcie_link(&cards[0]);
+        assert_eq!(link.bus_id.as_deref(), Some("0000:03:00.0"));
+        assert_eq!((link.current_gen, link.max_gen), (Some(1), Some(4)));
+        assert_eq!((link.current_width, link.max_width), (Some(8), Some(16)));
+
+        fixture.write(device.join("current_link_speed"), "Unknown speed\n");
+        assert_eq!(
+            read_link_gen(&cards[0].device.join("current_link_speed")),
+            None
+        );
+    }
+
+    #[test]
+    fn engine_time_becomes_utilization_on_the_second_sample() {
+        let fixture = Fixture::new("drm-engines");
+        fixture.card("card0", "amdgpu", "0000:03:00.0");
+        let cards = find_cards(&fixture.sysfs(), |_| true);
+        let (system, users) = (System::new(), Users::new());
+        let mut counters = CounterDeltas::default();
+        let mut sample = |gfx_ns: u64, compute_ns: u64| {
+            // Two fds onto the same client must count once
+            for fd in [4, 9] {
+                fixture.fdinfo(
+                    4242,
+                    fd,
+                    &format!(
+                        "drm-driver: amdgpu\ndrm-pdev: 0000:03:00.0\ndrm-client-id: 7\n\
+                         drm-memory-vram: 1 MiB\ndrm-engine-gfx: {} ns\n\
+                         drm-engine-compute: {} ns\n",
+                        gfx_ns, compute_ns
+                    ),
+                );
+            }
+            let usage = collect_processes(
+                &fixture.procfs(),
+                &cards,
+                &system,
+                &users,
+                &mut counters,
+                Duration::from_secs(1),
+            );
+            counters.finish();
+            usage
+        };
+
+        let first = sample(1_000_000_000, 0);
+        assert_eq!(first.processes.len(), 1);
+        assert_eq!(first.processes[0].gpu_memory, 1 << 20);
+        assert_eq!(first.processes[0].sm_utilization, None);
+        assert_eq!(first.gpu_utilization(0), None);
+
+        let second = sample(1_250_000_000, 600_000_000);
+        let process = &second.processes[0];
+        assert_eq!((process.pid, process.gpu_index), (4242, 0));
+        assert_eq!(process.sm_utilization, Some(60));
+        assert_eq!(process.process_type, "C");
+        assert_eq!(second.engines[&0]["gfx"].round(), 25.0);
+        assert_eq!(second.gpu_utilization(0), Some(60));
+    }
+
+    #[test]
+    fn gpu_load_is_busiest_engine_over_all_clients() {
+        let mut usage = DrmUsage::default();
+        usage.engines.insert(
+            0,
+            HashMap::from([("gfx".to_string(), 30.0 + 25.0), ("enc".to_string(), 40.0)]),
+        );
+        usage
+            .engines
+            .insert(1, HashMap::from([("render".to_string(), 90.0 + 40.0)]));
+        assert_eq!(usage.gpu_utilization(0), Some(55));
+        assert_eq!(usage.gpu_utilization(1), Some(100));
+        assert_eq!(usage.gpu_utilization(2), None);
+    }
+}
@@ -1,12 +1,21 @@
-//! GPU metrics collection - supports NVML (Linux/Windows) and Metal (macOS).
+//! GPU metrics collection - supports NVML (Linux/Windows), amdgpu, i915/xe
+//! and other DRM drivers (Linux) and Metal (macOS).
 
 use std::collections::HashMap;
+use std::sync::mpsc::Receiver;
+use std::time::Duration;
 use sysinfo::{Pid, System, Users};
 
-use crate::types::{GpuBackend, GpuInfo, GpuMetrics};
+use crate::types::{
+    BackendInfo, CounterDeltas, GpuBackend, GpuEvent, GpuInfo, GpuMetrics, GpuSettingChange,
+    ProcessAccounting, Reading,
+};
 
 #[cfg(not(target_os = "macos"))]
-use crate::types::GpuProcessInfo;
+use crate::types::{
+    ComputeMode, GpuFault, GpuHealth, GpuProcessInfo, GpuSettings, GpuStatus, MigInstanceId,
+    NvLinkInfo, PcieLink, TempThresholds, ThrottleReason, TopologyPath,
+};
 
 // ============================================================================
 // NVML Backend (Linux/Windows)
@@ -15,28 +24,314 @@ use crate::types::GpuProcessInfo;
 #[cfg(not(target_os = "macos"))]
 mod nvml_backend {
     use super::*;
+    #[cfg(target_os = "linux")]
+    use crate::metrics::amd::AmdHandle;
+    #[cfg(target_os = "linux")]
+    use crate::metrics::drm::DrmHandle;
+    #[cfg(target_os = "linux")]
+    use crate::metrics::intel::IntelHandle;
+    use crate::metrics::nvml_ext::NvmlExt;
     use nvml_wrapper::Nvml;
+    use std::time::Instant;
+
+    /// Time between attempts to initialize NVML after a failure or a lost GPU.
+    const NVML_RETRY_INTERVAL: Duration = Duration::from_secs(10);
+
+    /// Longest time between re-initializations while a lost GPU stays lost.
+    const NVML_RETRY_MAX_INTERVAL: Duration = Duration::from_secs(320);
 
     /// GPU backend handle for NVML.
     pub struct GpuHandle {
         pub nvml: Option<Nvml>,
+        pub ext: Option<NvmlExt>,
+        pub events: Option<Receiver<GpuEvent>>,
+        /// PCIe path between each pair of NVML indices, read at init since
+        /// it does not change while NVML is initialized.
+        pcie_paths: HashMap<(u32, u32), TopologyPath>,
+        /// The event listener ended because every GPU it waited on failed.
+        events_stopped: bool,
+        /// Forced re-initializations in a row that did not bring a GPU back.
+        forced_retries: u32,
+        /// amdgpu cards.
+        #[cfg(target_os = "linux")]
+        pub amd: Option<AmdHandle>,
+        /// i915/xe cards.
+        #[cfg(target_os = "linux")]
+        pub intel: Option<IntelHandle>,
+        /// Cards of other DRM drivers.
+        #[cfg(target_os = "linux")]
+        pub drm: Option<DrmHandle>,
+        last_init: Instant,
     }
 
     impl GpuHandle {
         pub fn new() -> Self {
+            let nvml = Nvml::init().ok();
+            let ext = nvml.as_ref().and_then(|_| NvmlExt::load());
+            let events = nvml.as_ref().and_then(|_| spawn_event_listener());
+            let pcie_paths = nvml.as_ref().map(pcie_paths).unwrap_or_default();
             Self {
-                nvml: Nvml::init().ok(),
+                nvml,
+                ext,
+                events,
+                pcie_paths,
+                events_stopped: false,
+                forced_retries: 0,
+                #[cfg(target_os = "linux")]
+                amd: AmdHandle::new(),
+                #[cfg(target_os = "linux")]
+                intel: IntelHandle::new(),
+                #[cfg(target_os = "linux")]
+                drm: DrmHandle::new(),
+                last_init: Instant::now(),
+            }
+        }
+
+        /// A handle that has found no GPUs, for tests.
+        #[cfg(test)]
+        pub fn empty() -> Self {
+            Self {
+                nvml: None,
+                ext: None,
+                events: None,
+                pcie_paths: HashMap::new(),
+                events_stopped: false,
+                forced_retries: 0,
+                #[cfg(target_os = "linux")]
+                amd: None,
+                #[cfg(target_os = "linux")]
+                intel: None,
+                #[cfg(target_os = "linux")]
+                drm: None,
+                last_init: Instant::now(),
+            }
+        }
+
+        /// Initialize NVML again if it failed before, or if `force` is set
+        /// because a GPU was lost, and restart a stopped event listener.
+        /// Attempts are spaced by `NVML_RETRY_INTERVAL`, doubling up to
+        /// `NVML_RETRY_MAX_INTERVAL` while forced ones do not help.
+        pub fn retry_init(&mut self, force: bool) {
+            if !force {
+                self.forced_retries = 0;
+            }
+            let interval = (NVML_RETRY_INTERVAL * 2u32.pow(self.forced_retries.min(5)))
+                .min(NVML_RETRY_MAX_INTERVAL);
+            if self.last_init.elapsed() < interval {
+                return;
+            }
+            if self.nvml.is_some() && !force {
+                if self.events_stopped {
+                    self.last_init = Instant::now();
+                    self.events_stopped = false;
+                    self.events = spawn_event_listener();
+                }
+                return;
+            }
+            self.last_init = Instant::now();
+            if force && self.nvml.is_some() {
+                self.forced_retries += 1;
             }
+
+            // Drop the old instance first so NVML re-enumerates the devices
+            self.nvml = None;
+            self.nvml = Nvml::init().ok();
+            self.pcie_paths = self.nvml.as_ref().map(pcie_paths).unwrap_or_default();
+            if self.nvml.is_some() {
+                if self.ext.is_none() {
+                    self.ext = NvmlExt::load();
+                }
+                if self.events.is_none() {
+                    self.events_stopped = false;
+                    self.events = spawn_event_listener();
+                }
+            }
+        }
+
+        /// Take the events received since the last call, noticing when the
+        /// listener has stopped so `retry_init` starts a new one.
+        pub fn drain_events(&mut self) -> Vec<GpuEvent> {
+            use std::sync::mpsc::TryRecvError;
+
+            let Some(rx) = &self.events else {
+                return Vec::new();
+            };
+            let mut events = Vec::new();
+            loop {
+                match rx.try_recv() {
+                    Ok(event) => events.push(event),
+                    Err(TryRecvError::Empty) => break,
+                    Err(TryRecvError::Disconnected) => {
+                        self.events = None;
+                        self.events_stopped = true;
+                        break;
+                    }
+                }
+            }
+            events
+        }
+    }
+
+    /// Tell a query the GPU does not support apart from one that failed.
+    fn reading<T>(result: Result<T, nvml_wrapper::error::NvmlError>) -> Reading<T> {
+        use nvml_wrapper::error::NvmlError;
+
+        match result {
+            Ok(value) => Reading::Value(value),
+            Err(NvmlError::NotSupported | NvmlError::FunctionNotFound) => Reading::NotSupported,
+            Err(_) => Reading::Error,
+        }
+    }
+
+    /// Status for a GPU whose queries fail with this error.
+    fn status_of(error: &nvml_wrapper::error::NvmlError) -> GpuStatus {
+        use nvml_wrapper::error::NvmlError;
+
+        let fault = match error {
+            NvmlError::GpuLost => GpuFault::Lost,
+            NvmlError::NotFound => GpuFault::NotFound,
+            NvmlError::Unknown => GpuFault::Unknown,
+            _ => GpuFault::Other,
+        };
+        GpuStatus::Failed {
+            fault,
+            code: format!("{:?}", error),
         }
     }
 
+    /// Start a thread that blocks on NVML event sets and forwards events, or
+    /// `None` if no GPU reports any.
+    ///
+    /// Each GPU gets a set of its own: a failed registration releases the set
+    /// it was given, and a GPU that fails later only takes its own set down.
+    /// The sets borrow their `Nvml` instance, so the thread owns a second one
+    /// rather than tying the handle to a self-referential struct. It ends,
+    /// closing the channel, once every set has failed.
+    #[cfg(target_os = "linux")]
+    fn spawn_event_listener() -> Option<Receiver<GpuEvent>> {
+        use nvml_wrapper::bitmasks::event::EventTypes;
+        use nvml_wrapper::error::NvmlError;
+        use std::sync::mpsc;
+
+        let (tx, rx) = mpsc::channel();
+        let (ready_tx, ready_rx) = mpsc::sync_channel(1);
+
+        std::thread::Builder::new()
+            .name("nvml-events".into())
+            .spawn(move || {
+                let Ok(nvml) = Nvml::init() else {
+                    let _ = ready_tx.send(0);
+                    return;
+                };
+
+                let wanted = EventTypes::CRITICAL_XID_ERROR
+                    | EventTypes::DOUBLE_BIT_ECC_ERROR
+                    | EventTypes::SINGLE_BIT_ECC_ERROR
+                    | EventTypes::PSTATE_CHANGE
+                    | EventTypes::CLOCK_CHANGE
+                    | EventTypes::POWER_SOURCE_CHANGE;
+
+                let count = nvml.device_count().unwrap_or(0);
+                let mut sets = Vec::new();
+                for i in 0..count {
+                    let Ok(device) = nvml.device_by_index(i) else {
+                        continue;
+                    };
+                    let supported = device
+                        .supported_event_types()
+                        .map(|t| t & wanted)
+                        .unwrap_or(EventTypes::empty());
+                    if supported.is_empty() {
+                        continue;
+                    }
+                    if let Ok(set) = nvml
+                        .create_event_set()
+                        .and_then(|set| device.register_events(supported, set).map_err(|e| e.error))
+                    {
+                        sets.push(set);
+                    }
+                }
+                let _ = ready_tx.send(sets.len());
+
+                // Take turns so one second covers every set
+                let timeout = (1000 / sets.len().max(1) as u32).max(10);
+                while !sets.is_empty() {
+                    let mut i = 0;
+                    while i < sets.len() {
+                        match sets[i].wait(timeout) {
+                            Ok(data) => {
+                                if tx.send(event_of(&data)).is_err() {
+                                    return;
+                                }
+                                i += 1;
+                            }
+                            Err(NvmlError::Timeout) => i += 1,
+                            // The GPU failed; the others keep reporting
+                            Err(_) => {
+                                sets.swap_remove(i);
+                            }
+                        }
+                    }
+                }
+            })
+            .ok()?;
+
+        (ready_rx.recv().ok()? > 0).then_some(rx)
+    }
+
+    /// Translate NVML event data into a logged event.
+    #[cfg(target_os = "linux")]
+    fn event_of(data: &nvml_wrapper::struct_wrappers::event::EventData) -> GpuEvent {
+        use crate::types::GpuEventKind;
+        use nvml_wrapper::bitmasks::event::EventTypes;
+        use nvml_wrapper::enums::event::XidError;
+
+        let kind = if data.event_type.contains(EventTypes::CRITICAL_XID_ERROR) {
+            GpuEventKind::Xid(match data.event_data {
+                Some(XidError::Value(code)) => Some(code),
+                _ => None,
+            })
+        } else if data.event_type.contains(EventTypes::DOUBLE_BIT_ECC_ERROR) {
+            GpuEventKind::DoubleBitEcc
+        } else if data.event_type.contains(EventTypes::SINGLE_BIT_ECC_ERROR) {
+            GpuEventKind::SingleBitEcc
+        } else if data.event_type.contains(EventTypes::PSTATE_CHANGE) {
+            GpuEventKind::PStateChange
+        } else if data.event_type.contains(EventTypes::CLOCK_CHANGE) {
+            GpuEventKind::ClockChange
+        } else {
+            GpuEventKind::PowerSourceChange
+        };
+
+        let gpu_index = data.device.index().unwrap_or(u32::MAX);
+        GpuEvent {
+            time: chrono::Local::now(),
+            gpu_index,
+            // NVML GPUs are known by UUID, as in `GpuInfo::stable_id`
+            gpu_id: data
+                .device
+                .uuid()
+                .unwrap_or_else(|_| format!("#{}", gpu_index)),
+            kind,
+        }
+    }
+
+    /// NVML event sets are only available on Linux.
+    #[cfg(not(target_os = "linux"))]
+    fn spawn_event_listener() -> Option<Receiver<GpuEvent>> {
+        None
+    }
+
     /// Collect GPU metrics from NVML.
     pub fn collect_gpu_metrics(
         handle: &GpuHandle,
         system: &System,
         users: &Users,
+        last_nvlink_counters: &mut HashMap<(u32, u32), (u64, u64)>,
+        elapsed: Duration,
     ) -> Option<GpuMetrics> {
         let nvml = handle.nvml.as_ref()?;
+        let elapsed_secs = elapsed.as_secs_f64().max(0.001);
 
         let device_count = nvml.device_count().ok()?;
 
@@ -50,100 +345,224 @@ mod nvml_backend {
         let mut processes = Vec::new();
 
         for i in 0..device_count {
-            let Ok(device) = nvml.device_by_index(i) else {
-                continue;
+            let device = match nvml.device_by_index(i) {
+                Ok(device) => device,
+                Err(e) => {
+                    gpus.push(GpuInfo {
+                        index: i,
+                        name: "Unknown GPU".into(),
+                        backend: GpuBackend::Nvml,
+                        status: status_of(&e),
+                        ..Default::default()
+                    });
+                    continue;
+                }
             };
 
             let name = device.name().unwrap_or_else(|_| "Unknown GPU".into());
-            let temperature = device
-                .temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu)
-                .unwrap_or(0);
-            let fan_speed = device.fan_speed(0).unwrap_or(0);
-            let power_usage = device.power_usage().unwrap_or(0) / 1000;
-            let power_limit = device.power_management_limit().unwrap_or(0) / 1000;
-
-            let utilization = device.utilization_rates().unwrap_or(
-                nvml_wrapper::struct_wrappers::device::Utilization { gpu: 0, memory: 0 },
+            let uuid = device.uuid().ok();
+
+            // Every GPU reports memory info, so an error here is the device
+            // failing rather than an unsupported query; report it instead of
+            // a card full of zeros
+            let memory_info = match device.memory_info() {
+                Ok(info) => info,
+                Err(e) => {
+                    gpus.push(GpuInfo {
+                        index: i,
+                        name,
+                        backend: GpuBackend::Nvml,
+                        uuid,
+                        status: status_of(&e),
+                        ..Default::default()
+                    });
+                    continue;
+                }
+            };
+            #[cfg(target_os = "linux")]
+            let minor_number = device.minor_number().ok();
+            #[cfg(not(target_os = "linux"))]
+            let minor_number = None;
+            let temperature = reading(
+                device.temperature(nvml_wrapper::enum_wrappers::device::TemperatureSensor::Gpu),
             );
-            let memory_info =
+            let memory_temperature = memory_temperature_of(&device);
+            let threshold = |kind| device.temperature_threshold(kind).ok();
+            let thresholds = {
+                use nvml_wrapper::enum_wrappers::device::TemperatureThreshold;
+                TempThresholds {
+                    slowdown: threshold(TemperatureThreshold::Slowdown),
+                    shutdown: threshold(TemperatureThreshold::Shutdown),
+                    memory_max: threshold(TemperatureThreshold::MemoryMax),
+                }
+            };
+            let fan_speeds = match device.num_fans() {
+                Ok(count) => Reading::Value(
+                    (0..count)
+                        .filter_map(|fan| device.fan_speed(fan).ok())
+                        .collect(),
+                ),
+                // Older drivers cannot count fans but still report the first
+                Err(_) => reading(device.fan_speed(0)).map(|speed| vec![speed]),
+            };
+            let power_usage = reading(device.power_usage()).map(|mw| mw / 1000);
+            let power_limit = reading(device.power_management_limit()).map(|mw| mw / 1000);
+
+            let utilization = reading(device.utilization_rates());
+
+            let encoder = reading(device.encoder_utilization()).map(|e| e.utilization);
+            let decoder = reading(device.decoder_utilization()).map(|d| d.utilization);
+
+            // NVML reports PCIe throughput in KiB/s
+            let pcie_tx = reading(
+                device.pcie_throughput(nvml_wrapper::enum_wrappers::device::PcieUtilCounter::Send),
+            )
+            .map(|kib| kib as u64 * 1024);
+            let pcie_rx = reading(
                 device
-                    .memory_info()
-                    .unwrap_or(nvml_wrapper::struct_wrappers::device::MemoryInfo {
-                        free: 0,
-                        total: 1,
-                        used: 0,
-                    });
+                    .pcie_throughput(nvml_wrapper::enum_wrappers::device::PcieUtilCounter::Receive),
+            )
+            .map(|kib| kib as u64 * 1024);
+
+            let sm_clock =
+                reading(device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics));
+            let mem_clock =
+                reading(device.clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory));
+
+            let max_sm_clock = device
+                .max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics)
+                .ok();
+            let max_mem_clock = device
+                .max_clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory)
+                .ok();
+            let boost_clock = device
+                .max_customer_boost_clock(nvml_wrapper::enum_wrappers::device::Clock::Graphics)
+                .ok();
 
-            let encoder = device
-                .encoder_utilization()
-                .map(|e| e.utilization)
-                .unwrap_or(0);
-            let decoder = device
-                .decoder_utilization()
-                .map(|d| d.utilization)
-                .unwrap_or(0);
-
-            let pcie_tx = device
-                .pcie_throughput(nvml_wrapper::enum_wrappers::device::PcieUtilCounter::Send)
-                .unwrap_or(0);
-            let pcie_rx = device
-                .pcie_throughput(nvml_wrapper::enum_wrappers::device::PcieUtilCounter::Receive)
-                .unwrap_or(0);
-
-            let sm_clock = device
-                .clock_info(nvml_wrapper::enum_wrappers::device::Clock::Graphics)
-                .unwrap_or(0);
-            let mem_clock = device
-                .clock_info(nvml_wrapper::enum_wrappers::device::Clock::Memory)
-                .unwrap_or(0);
-
-            let pstate = device
-                .performance_state()
-                .map(|p| {
-                    use nvml_wrapper::enum_wrappers::device::PerformanceState;
-                    match p {
-                        PerformanceState::Zero => "P0",
-                        PerformanceState::One => "P1",
-                        PerformanceState::Two => "P2",
-                        PerformanceState::Three => "P3",
-                        PerformanceState::Four => "P4",
-                        PerformanceState::Five => "P5",
-                        PerformanceState::Six => "P6",
-                        PerformanceState::Seven => "P7",
-                        PerformanceState::Eight => "P8",
-                        PerformanceState::Nine => "P9",
-                        PerformanceState::Ten => "P10",
-                        PerformanceState::Eleven => "P11",
-                        PerformanceState::Twelve => "P12",
-                        PerformanceState::Thirteen => "P13",
-                        PerformanceState::Fourteen => "P14",
-                        PerformanceState::Fifteen => "P15",
-                        PerformanceState::Unknown => "P?",
+            let throttle_reasons = device
+                .current_throttle_reasons()
+                .map(throttle_reasons_from_nvml)
+                .unwrap_or_default();
+
+            let health = collect_health(&device);
+
+            let pcie = PcieLink {
+                bus_id: device.pci_info().ok().map(|p| p.bus_id),
+                current_gen: device.current_pcie_link_gen().ok(),
+                max_gen: device.max_pcie_link_gen().ok(),
+                current_width: device.current_pcie_link_width().ok(),
+                max_width: device.max_pcie_link_width().ok(),
+                replay_count: device.pcie_replay_counter().ok(),
+            };
+            let numa_node = pcie.bus_id.as_deref().and_then(numa_node_of);
+            let cpu_affinity = cpu_affinity_of(&device, system.cpus().len());
+            let nvlinks = collect_nvlinks(handle, &device, i, last_nvlink_counters, elapsed_secs);
+
+            let mig_enabled = handle.ext.as_ref().and_then(|ext| ext.mig_enabled(&device));
+            let mut mig_instances = Vec::new();
+            // Processes seen on each MIG device, for drivers that do not tag
+            // the parent's process list with instance IDs
+            let mut mig_pids: HashMap<u32, MigInstanceId> = HashMap::new();
+            if let (Some(ext), Some(true)) = (&handle.ext, mig_enabled) {
+                for (instance, mig_device) in ext.mig_instances(nvml, &device) {
+                    if let Ok(procs) = mig_device.running_compute_processes() {
+                        mig_pids.extend(procs.iter().map(|p| (p.pid, instance.id)));
                     }
-                    .to_string()
-                })
-                .unwrap_or_else(|_| "?".into());
+                    mig_instances.push(instance);
+                }
+            }
+            let mig_instance_of = |proc: &nvml_wrapper::struct_wrappers::device::ProcessInfo| match (
+                proc.gpu_instance_id,
+                proc.compute_instance_id,
+            ) {
+                (Some(gpu_instance), Some(compute_instance)) => Some(MigInstanceId {
+                    gpu_instance,
+                    compute_instance,
+                }),
+                _ => mig_pids.get(&proc.pid).copied(),
+            };
+
+            let pstate = reading(device.performance_state()).map(|p| {
+                use nvml_wrapper::enum_wrappers::device::PerformanceState;
+                match p {
+                    PerformanceState::Zero => "P0",
+                    PerformanceState::One => "P1",
+                    PerformanceState::Two => "P2",
+                    PerformanceState::Three => "P3",
+                    PerformanceState::Four => "P4",
+                    PerformanceState::Five => "P5",
+                    PerformanceState::Six => "P6",
+                    PerformanceState::Seven => "P7",
+                    PerformanceState::Eight => "P8",
+                    PerformanceState::Nine => "P9",
+                    PerformanceState::Ten => "P10",
+                    PerformanceState::Eleven => "P11",
+                    PerformanceState::Twelve => "P12",
+                    PerformanceState::Thirteen => "P13",
+                    PerformanceState::Fourteen => "P14",
+                    PerformanceState::Fifteen => "P15",
+                    PerformanceState::Unknown => "P?",
+                }
+                .to_string()
+            });
 
             gpus.push(GpuInfo {
                 index: i,
                 name,
+                backend: GpuBackend::Nvml,
+                uuid,
+                serial: device.serial().ok(),
+                board_part_number: device.board_part_number().ok(),
+                vbios_version: device.vbios_version().ok(),
+                minor_number,
                 temperature,
-                fan_speed,
+                memory_temperature,
+                thresholds,
+                fan_speeds,
                 power_usage,
                 power_limit,
-                gpu_utilization: utilization.gpu,
-                memory_utilization: utilization.memory,
+                gpu_utilization: utilization.as_ref().map(|u| u.gpu),
+                memory_utilization: utilization.as_ref().map(|u| u.memory),
                 memory_used: memory_info.used,
                 memory_total: memory_info.total,
                 encoder_utilization: encoder,
                 decoder_utilization: decoder,
-                pcie_rx: pcie_rx as u64 * 1024,
-                pcie_tx: pcie_tx as u64 * 1024,
+                pcie_rx,
+                pcie_tx,
                 sm_clock,
                 mem_clock,
+                max_sm_clock,
+                max_mem_clock,
+                boost_clock,
                 pstate,
+                throttle_reasons,
+                health,
+                mig_enabled,
+                mig_instances,
+                pcie,
+                numa_node,
+                cpu_affinity,
+                nvlinks,
+                energy_consumption: device.total_energy_consumption().ok(),
+                settings: collect_settings(&device),
+                status: GpuStatus::Ok,
             });
 
+            // SM utilization of each process, averaged over the samples NVML
+            // took since the last refresh
+            let since = std::time::SystemTime::now()
+                .checked_sub(elapsed)
+                .and_then(|t| t.duration_since(std::time::UNIX_EPOCH).ok())
+                .map(|d| d.as_micros() as u64);
+            let mut sm_samples: HashMap<u32, (u32, u32)> = HashMap::new();
+            for sample in device.process_utilization_stats(since).unwrap_or_default() {
+                let (total, count) = sm_samples.entry(sample.pid).or_default();
+                *total += sample.sm_util;
+                *count += 1;
+            }
+            let sm_utilization_of =
+                |pid: u32| sm_samples.get(&pid).map(|(total, count)| total / count);
+
             // Collect GPU processes
             if let Ok(compute_procs) = device.running_compute_processes() {
                 for proc in compute_procs {
@@ -159,9 +578,10 @@ mod nvml_backend {
                             nvml_wrapper::enums::device::UsedGpuMemory::Used(bytes) => bytes,
                             nvml_wrapper::enums::device::UsedGpuMemory::Unavailable => 0,
                         },
-                        sm_utilization: None,
+                        sm_utilization: sm_utilization_of(pid),
                         command,
                         process_type: "C".into(),
+                        mig_instance: mig_instance_of(&proc),
                     });
                 }
             }
@@ -182,23 +602,427 @@ mod nvml_backend {
                                 nvml_wrapper::enums::device::UsedGpuMemory::Used(bytes) => bytes,
                                 nvml_wrapper::enums::device::UsedGpuMemory::Unavailable => 0,
                             },
-                            sm_utilization: None,
+                            sm_utilization: sm_utilization_of(pid),
                             command,
                             process_type: "G".into(),
+                            mig_instance: mig_instance_of(&proc),
                         });
                     }
                 }
             }
         }
 
+        let topology = collect_topology(handle, &mut gpus);
+
         Some(GpuMetrics {
             gpus,
             processes,
-            driver_version,
-            api_version: cuda_version,
-            backend: GpuBackend::Nvml,
+            topology,
+            drivers: vec![BackendInfo {
+                backend: GpuBackend::Nvml,
+                driver_version,
+                api_version: cuda_version,
+            }],
+        })
+    }
+
+    /// Enumerate NVLinks with their state and data throughput.
+    fn collect_nvlinks(
+        handle: &GpuHandle,
+        device: &nvml_wrapper::Device,
+        gpu_index: u32,
+        last_counters: &mut HashMap<(u32, u32), (u64, u64)>,
+        elapsed_secs: f64,
+    ) -> Vec<NvLinkInfo> {
+        use nvml_wrapper::error::NvmlError;
+        use nvml_wrapper_sys::bindings::NVML_NVLINK_MAX_LINKS;
+
+        let mut links = Vec::new();
+        for link in 0..NVML_NVLINK_MAX_LINKS {
+            let wrapper = device.link_wrapper_for(link);
+            let active = match wrapper.is_active() {
+                Ok(active) => active,
+                // The GPU has no NVLink at all
+                Err(NvmlError::NotSupported) => break,
+                // Links past the GPU's link count
+                Err(_) => continue,
+            };
+
+            let (mut rx_rate, mut tx_rate) = (None, None);
+            if active {
+                if let Some((rx, tx)) = handle
+                    .ext
+                    .as_ref()
+                    .and_then(|ext| ext.nvlink_throughput(device, link))
+                {
+                    if let Some((last_rx, last_tx)) =
+                        last_counters.insert((gpu_index, link), (rx, tx))
+                    {
+                        rx_rate = Some(rx.saturating_sub(last_rx) as f64 / elapsed_secs);
+                        tx_rate = Some(tx.saturating_sub(last_tx) as f64 / elapsed_secs);
+                    }
+                }
+            }
+
+            links.push(NvLinkInfo {
+                link,
+                active,
+                version: wrapper.version().ok(),
+                remote_bus_id: active
+                    .then(|| wrapper.remote_pci_info().ok().map(|p| p.bus_id))
+                    .flatten(),
+                remote_gpu: None,
+                rx_rate,
+                tx_rate,
+            });
+        }
+        links
+    }
+
+    /// Resolve NVLink peers and work out the path between every GPU pair.
+    fn collect_topology(
+        handle: &GpuHandle,
+        gpus: &mut [GpuInfo],
+    ) -> Vec<Vec<Option<TopologyPath>>> {
+        let peers: Vec<(u32, Option<String>)> = gpus
+            .iter()
+            .map(|g| (g.index, g.pcie.bus_id.clone()))
+            .collect();
+        for gpu in gpus.iter_mut() {
+            for link in &mut gpu.nvlinks {
+                link.remote_gpu = peers
+                    .iter()
+                    .find(|(_, bus_id)| bus_id.is_some() && *bus_id == link.remote_bus_id)
+                    .map(|(index, _)| *index);
+            }
+        }
+
+        // Active links that do not end at a GPU go to an NVSwitch
+        let on_switch = |gpu: &GpuInfo| {
+            gpu.nvlinks
+                .iter()
+                .any(|l| l.active && l.remote_bus_id.is_some() && l.remote_gpu.is_none())
+        };
+
+        gpus.iter()
+            .map(|a| {
+                gpus.iter()
+                    .map(|b| {
+                        if a.index == b.index {
+                            return None;
+                        }
+                        let direct = a
+                            .nvlinks
+                            .iter()
+                            .filter(|l| l.active && l.remote_gpu == Some(b.index))
+                            .count() as u32;
+                        if direct > 0 {
+                            Some(TopologyPath::NvLink(direct))
+                        } else if on_switch(a) && on_switch(b) {
+                            Some(TopologyPath::NvSwitch)
+                        } else {
+                            handle.pcie_paths.get(&(a.index, b.index)).copied()
+                        }
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// PCIe path between every pair of GPUs, keyed by both orders of their
+    /// NVML indices.
+    #[cfg(target_os = "linux")]
+    fn pcie_paths(nvml: &Nvml) -> HashMap<(u32, u32), TopologyPath> {
+        let count = nvml.device_count().unwrap_or(0);
+        let mut paths = HashMap::new();
+        for a in 0..count {
+            for b in a + 1..count {
+                if let Some(path) = pcie_path(nvml, a, b) {
+                    paths.insert((a, b), path);
+                    paths.insert((b, a), path);
+                }
+            }
+        }
+        paths
+    }
+
+    /// NVML only reports topology on Linux.
+    #[cfg(not(target_os = "linux"))]
+    fn pcie_paths(_nvml: &Nvml) -> HashMap<(u32, u32), TopologyPath> {
+        HashMap::new()
+    }
+
+    /// PCIe path between two GPUs from their closest common ancestor.
+    #[cfg(target_os = "linux")]
+    fn pcie_path(nvml: &Nvml, a: u32, b: u32) -> Option<TopologyPath> {
+        use nvml_wrapper::enum_wrappers::device::TopologyLevel;
+
+        let a = nvml.device_by_index(a).ok()?;
+        let b = nvml.device_by_index(b).ok()?;
+        Some(match a.topology_common_ancestor(b).ok()? {
+            TopologyLevel::Internal => TopologyPath::Internal,
+            TopologyLevel::Single => TopologyPath::Pix,
+            TopologyLevel::Multiple => TopologyPath::Pxb,
+            TopologyLevel::HostBridge => TopologyPath::Phb,
+            TopologyLevel::Node => TopologyPath::Node,
+            TopologyLevel::System => TopologyPath::Sys,
         })
     }
+
+    /// NUMA node of a PCI device, from sysfs.
+    #[cfg(target_os = "linux")]
+    fn numa_node_of(bus_id: &str) -> Option<u32> {
+        // NVML uses an 8-digit domain, sysfs a 4-digit lowercase one
+        let bus_id = bus_id.to_lowercase();
+        let sysfs_id = bus_id.get(bus_id.len().saturating_sub(12)..)?;
+        let node =
+            std::fs::read_to_string(format!("/sys/bus/pci/devices/{}/numa_node", sysfs_id)).ok()?;
+        // -1 means the platform has no NUMA information
+        node.trim().parse::<i32>().ok()?.try_into().ok()
+    }
+
+    #[cfg(not(target_os = "linux"))]
+    fn numa_node_of(_bus_id: &str) -> Option<u32> {
+        None
+    }
+
+    /// CPUs with affinity to the GPU, formatted as a range list.
+    fn cpu_affinity_of(device: &nvml_wrapper::Device, cpu_count: usize) -> Option<String> {
+        let bits = std::mem::size_of::<std::os::raw::c_ulong>() * 8;
+        let words = cpu_count.div_ceil(bits).max(1);
+        let mask = device.cpu_affinity(words).ok()?;
+
+        let cpus: Vec<usize> = mask
+            .iter()
+            .enumerate()
+            .flat_map(|(word, &value)| {
+                (0..bits)
+                    .filter(move |bit| value & (1 << bit) != 0)
+                    .map(move |bit| word * bits + bit)
+            })
+            .collect();
+        if cpus.is_empty() {
+            return None;
+        }
+
+        let mut ranges: Vec<String> = Vec::new();
+        let mut start = cpus[0];
+        let mut prev = cpus[0];
+        for &cpu in cpus.iter().skip(1).chain(std::iter::once(&usize::MAX)) {
+            if cpu != prev + 1 {
+                ranges.push(if start == prev {
+                    start.to_string()
+                } else {
+                    format!("{}-{}", start, prev)
+                });
+                start = cpu;
+            }
+            prev = cpu;
+        }
+        Some(ranges.join(","))
+    }
+
+    /// Read persistence and compute mode, application clocks and power limits.
+    fn collect_settings(device: &nvml_wrapper::Device) -> GpuSettings {
+        use nvml_wrapper::enum_wrappers::device::{Clock, ComputeMode as NvmlComputeMode};
+
+        let constraints = device.power_management_limit_constraints().ok();
+        GpuSettings {
+            persistence_mode: device.is_in_persistent_mode().ok(),
+            compute_mode: device.compute_mode().ok().map(|mode| match mode {
+                NvmlComputeMode::Default => ComputeMode::Default,
+                NvmlComputeMode::ExclusiveThread => ComputeMode::ExclusiveThread,
+                NvmlComputeMode::ExclusiveProcess => ComputeMode::ExclusiveProcess,
+                NvmlComputeMode::Prohibited => ComputeMode::Prohibited,
+            }),
+            display_active: device.is_display_active().ok(),
+            app_sm_clock: device.applications_clock(Clock::Graphics).ok(),
+            app_mem_clock: device.applications_clock(Clock::Memory).ok(),
+            default_app_sm_clock: device.default_applications_clock(Clock::Graphics).ok(),
+            default_app_mem_clock: device.default_applications_clock(Clock::Memory).ok(),
+            power_limit_min: constraints.as_ref().map(|c| c.min_limit / 1000),
+            power_limit_max: constraints.as_ref().map(|c| c.max_limit / 1000),
+            power_limit_default: device
+                .power_management_limit_default()
+                .ok()
+                .map(|mw| mw / 1000),
+        }
+    }
+
+    /// Apply a device setting. Most changes need root.
+    pub fn apply_gpu_setting(
+        handle: &GpuHandle,
+        gpu_uuid: &str,
+        change: GpuSettingChange,
+    ) -> anyhow::Result<()> {
+        use anyhow::Context;
+        use nvml_wrapper::enum_wrappers::device::ComputeMode as NvmlComputeMode;
+        use nvml_wrapper::enums::device::GpuLockedClocksSetting;
+        use nvml_wrapper::error::NvmlError;
+
+        let nvml = handle.nvml.as_ref().context("NVML is not available")?;
+        let mut device = nvml.device_by_uuid(gpu_uuid)?;
+        let result = match change {
+            GpuSettingChange::PowerLimit(watts) => device.set_power_management_limit(watts * 1000),
+            GpuSettingChange::ComputeMode(mode) => device.set_compute_mode(match mode {
+                ComputeMode::Default => NvmlComputeMode::Default,
+                ComputeMode::ExclusiveThread => NvmlComputeMode::ExclusiveThread,
+                ComputeMode::ExclusiveProcess => NvmlComputeMode::ExclusiveProcess,
+                ComputeMode::Prohibited => NvmlComputeMode::Prohibited,
+            }),
+            GpuSettingChange::LockClocks(Some(mhz)) => {
+                device.set_gpu_locked_clocks(GpuLockedClocksSetting::Numeric {
+                    min_clock_mhz: mhz,
+                    max_clock_mhz: mhz,
+                })
+            }
+            GpuSettingChange::LockClocks(None) => device.reset_gpu_locked_clocks(),
+        };
+
+        match result {
+            Ok(()) => Ok(()),
+            Err(NvmlError::NoPermission) => anyhow::bail!("permission denied, run as root"),
+            Err(NvmlError::NotSupported) => anyhow::bail!("not supported on this GPU"),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Lifetime statistics of a finished process, if accounting mode is on.
+    pub fn process_accounting(
+        handle: &GpuHandle,
+        gpu_uuid: &str,
+        pid: u32,
+    ) -> Option<ProcessAccounting> {
+        let device = handle.nvml.as_ref()?.device_by_uuid(gpu_uuid).ok()?;
+        if !device.is_accounting_enabled().ok()? {
+            return None;
+        }
+        // NVML keeps the most recent process with this PID
+        let stats = device.accounting_stats_for(pid).ok()?;
+        if stats.is_running {
+            return None;
+        }
+        let start = chrono::DateTime::from_timestamp_micros(stats.start_time as i64)?;
+        Some(ProcessAccounting {
+            start: start.with_timezone(&chrono::Local),
+            duration: Duration::from_millis(stats.time),
+            max_memory: stats.max_memory_usage,
+            gpu_utilization: stats.gpu_utilization,
+        })
+    }
+
+    /// Memory temperature, which this wrapper only exposes as a field value.
+    fn memory_temperature_of(device: &nvml_wrapper::Device) -> Option<u32> {
+        use nvml_wrapper::enums::device::SampleValue;
+        use nvml_wrapper::structs::device::FieldId;
+        use nvml_wrapper::sys_exports::field_id::NVML_FI_DEV_MEMORY_TEMP;
+
+        let samples = device
+            .field_values_for(&[FieldId(NVML_FI_DEV_MEMORY_TEMP)])
+            .ok()?;
+        match samples.into_iter().next()?.ok()?.value {
+            // Boards without a sensor report zero rather than an error
+            Ok(SampleValue::U32(v)) if v > 0 => Some(v),
+            Ok(SampleValue::U64(v)) if v > 0 => Some(v as u32),
+            Ok(SampleValue::I64(v)) if v > 0 => Some(v as u32),
+            _ => None,
+        }
+    }
+
+    /// Read ECC counters, page retirement and row remapping state.
+    fn collect_health(device: &nvml_wrapper::Device) -> GpuHealth {
+        use nvml_wrapper::enum_wrappers::device::{EccCounter, MemoryError, RetirementCause};
+        use nvml_wrapper::enums::device::SampleValue;
+        use nvml_wrapper::structs::device::FieldId;
+        use nvml_wrapper::sys_exports::field_id::{
+            NVML_FI_DEV_REMAPPED_COR, NVML_FI_DEV_REMAPPED_FAILURE, NVML_FI_DEV_REMAPPED_PENDING,
+            NVML_FI_DEV_REMAPPED_UNC,
+        };
+
+        let ecc = device.is_ecc_enabled().ok();
+        let ecc_errors = |error, counter| device.total_ecc_errors(error, counter).ok();
+        let retired = |cause| device.retired_pages(cause).ok().map(|pages| pages.len());
+
+        let mut health = GpuHealth {
+            ecc_enabled: ecc.as_ref().map(|e| e.currently_enabled),
+            ecc_pending: ecc.as_ref().map(|e| e.pending_enabled),
+            volatile_corrected: ecc_errors(MemoryError::Corrected, EccCounter::Volatile),
+            volatile_uncorrected: ecc_errors(MemoryError::Uncorrected, EccCounter::Volatile),
+            aggregate_corrected: ecc_errors(MemoryError::Corrected, EccCounter::Aggregate),
+            aggregate_uncorrected: ecc_errors(MemoryError::Uncorrected, EccCounter::Aggregate),
+            retired_pages_sbe: retired(RetirementCause::MultipleSingleBitEccErrors),
+            retired_pages_dbe: retired(RetirementCause::DoubleBitEccError),
+            retirement_pending: device.are_pages_pending_retired().ok(),
+            ..Default::default()
+        };
+
+        // Row remapping is only exposed through field values in this wrapper
+        let fields = [
+            NVML_FI_DEV_REMAPPED_COR,
+            NVML_FI_DEV_REMAPPED_UNC,
+            NVML_FI_DEV_REMAPPED_PENDING,
+            NVML_FI_DEV_REMAPPED_FAILURE,
+        ]
+        .map(FieldId);
+        if let Ok(samples) = device.field_values_for(&fields) {
+            for sample in samples.into_iter().flatten() {
+                let value = match sample.value {
+                    Ok(SampleValue::U32(v)) => v as u64,
+                    Ok(SampleValue::U64(v)) => v,
+                    Ok(SampleValue::I64(v)) => v.max(0) as u64,
+                    Ok(SampleValue::F64(v)) => v.max(0.0) as u64,
+                    Err(_) => continue,
+                };
+                match sample.field.0 {
+                    NVML_FI_DEV_REMAPPED_COR => health.remapped_rows_correctable = Some(value),
+                    NVML_FI_DEV_REMAPPED_UNC => health.remapped_rows_uncorrectable = Some(value),
+                    NVML_FI_DEV_REMAPPED_PENDING => health.remap_pending = Some(value != 0),
+                    NVML_FI_DEV_REMAPPED_FAILURE => health.remap_failure = Some(value != 0),
+                    _ => {}
+                }
+            }
+        }
+
+        health
+    }
+
+    /// Convert NVML clock throttle reason flags to our own list.
+    fn throttle_reasons_from_nvml(
+        reasons: nvml_wrapper::bitmasks::device::ThrottleReasons,
+    ) -> Vec<ThrottleReason> {
+        use nvml_wrapper::bitmasks::device::ThrottleReasons;
+
+        [
+            (ThrottleReasons::GPU_IDLE, ThrottleReason::Idle),
+            (
+                ThrottleReasons::APPLICATIONS_CLOCKS_SETTING,
+                ThrottleReason::ApplicationsClocks,
+            ),
+            (ThrottleReasons::SW_POWER_CAP, ThrottleReason::PowerCap),
+            (ThrottleReasons::HW_SLOWDOWN, ThrottleReason::HwSlowdown),
+            (ThrottleReasons::SYNC_BOOST, ThrottleReason::SyncBoost),
+            (
+                ThrottleReasons::SW_THERMAL_SLOWDOWN,
+                ThrottleReason::SwThermal,
+            ),
+            (
+                ThrottleReasons::HW_THERMAL_SLOWDOWN,
+                ThrottleReason::HwThermal,
+            ),
+            (
+                ThrottleReasons::HW_POWER_BRAKE_SLOWDOWN,
+                ThrottleReason::PowerBrake,
+            ),
+            (
+                ThrottleReasons::DISPLAY_CLOCK_SETTING,
+                ThrottleReason::DisplayClocks,
+            ),
+        ]
+        .into_iter()
+        .filter(|(flag, _)| reasons.contains(*flag))
+        .map(|(_, reason)| reason)
+        .collect()
+    }
 }
 
 // ============================================================================
@@ -214,14 +1038,33 @@ mod metal_backend {
     /// GPU backend handle for Metal.
     pub struct GpuHandle {
         pub devices: Vec<Device>,
+        pub events: Option<Receiver<GpuEvent>>,
     }
 
     impl GpuHandle {
         pub fn new() -> Self {
             Self {
                 devices: Device::all(),
+                events: None,
             }
         }
+
+        /// A handle that has found no GPUs, for tests.
+        #[cfg(test)]
+        pub fn empty() -> Self {
+            Self {
+                devices: Vec::new(),
+                events: None,
+            }
+        }
+
+        /// Metal devices are listed once at startup and cannot be lost.
+        pub fn retry_init(&mut self, _force: bool) {}
+
+        /// Metal reports no driver events.
+        pub fn drain_events(&mut self) -> Vec<GpuEvent> {
+            Vec::new()
+        }
     }
 
     /// Get macOS GPU driver info via system_profiler.
@@ -250,13 +1093,6 @@ mod metal_backend {
         ("N/A".to_string(), 0, 0)
     }
 
-    /// Get GPU utilization from powermetrics (requires sudo, so we estimate instead).
-    fn estimate_gpu_utilization() -> u32 {
-        // On macOS, getting real GPU utilization requires elevated privileges.
-        // We return 0 as a placeholder - the memory usage is more reliable.
-        0
-    }
-
     /// Collect GPU metrics from Metal.
     pub fn collect_gpu_metrics(
         handle: &GpuHandle,
@@ -279,32 +1115,53 @@ mod metal_backend {
 
             // Calculate memory utilization percentage
             let memory_utilization = if memory_total > 0 {
-                ((memory_used as f64 / memory_total as f64) * 100.0) as u32
+                Reading::Value(((memory_used as f64 / memory_total as f64) * 100.0) as u32)
             } else {
-                0
+                Reading::NotSupported
             };
 
-            // Metal doesn't provide these metrics directly
-            let gpu_utilization = estimate_gpu_utilization();
-
             gpus.push(GpuInfo {
                 index: i as u32,
                 name,
-                temperature: 0, // Not available via Metal API
-                fan_speed: 0,   // Not available via Metal API
-                power_usage: 0, // Not available via Metal API
-                power_limit: 0, // Not available via Metal API
-                gpu_utilization,
+                backend: GpuBackend::Metal,
+                uuid: None,
+                serial: None,
+                board_part_number: None,
+                vbios_version: None,
+                minor_number: None,
+                // Metal does not report sensors, clocks or engine utilization;
+                // reading GPU utilization on macOS requires elevated privileges
+                temperature: Reading::NotSupported,
+                memory_temperature: None,
+                thresholds: Default::default(),
+                fan_speeds: Reading::NotSupported,
+                power_usage: Reading::NotSupported,
+                power_limit: Reading::NotSupported,
+                gpu_utilization: Reading::NotSupported,
                 memory_utilization,
                 memory_used,
                 memory_total,
-                encoder_utilization: 0, // Not available via Metal API
-                decoder_utilization: 0, // Not available via Metal API
-                pcie_rx: 0,             // Not applicable for integrated GPUs
-                pcie_tx: 0,             // Not applicable for integrated GPUs
-                sm_clock: 0,            // Not available via Metal API
-                mem_clock: 0,           // Not available via Metal API
-                pstate: "N/A".to_string(),
+                encoder_utilization: Reading::NotSupported,
+                decoder_utilization: Reading::NotSupported,
+                pcie_rx: Reading::NotSupported, // Not applicable for integrated GPUs
+                pcie_tx: Reading::NotSupported,
+                sm_clock: Reading::NotSupported,
+                mem_clock: Reading::NotSupported,
+                max_sm_clock: None,
+                max_mem_clock: None,
+                boost_clock: None,
+                pstate: Reading::NotSupported,
+                throttle_reasons: Vec::new(),
+                health: Default::default(),
+                mig_enabled: None,
+                mig_instances: Vec::new(),
+                pcie: Default::default(),
+                numa_node: None,
+                cpu_affinity: None,
+                nvlinks: Vec::new(),
+                energy_consumption: None,
+                settings: Default::default(),
+                status: Default::default(),
             });
         }
 
@@ -333,9 +1190,12 @@ mod metal_backend {
         Some(GpuMetrics {
             gpus,
             processes,
-            driver_version,
-            api_version,
-            backend: GpuBackend::Metal,
+            topology: Vec::new(),
+            drivers: vec![BackendInfo {
+                backend: GpuBackend::Metal,
+                driver_version,
+                api_version,
+            }],
         })
     }
 }
@@ -346,7 +1206,11 @@ mod metal_backend {
 
 /// Get process info from sysinfo by PID.
 #[allow(dead_code)]
-fn get_process_info(system: &System, users: &Users, pid: u32) -> (String, String, String) {
+pub(super) fn get_process_info(
+    system: &System,
+    users: &Users,
+    pid: u32,
+) -> (String, String, String) {
     let sys_pid = Pid::from_u32(pid);
     if let Some(proc) = system.process(sys_pid) {
         let user_map: HashMap<_, _> = users
@@ -388,13 +1252,46 @@ pub use nvml_backend::GpuHandle;
 pub use metal_backend::GpuHandle;
 
 /// Collect GPU metrics using the appropriate backend for the platform.
+///
+/// `last_nvlink_counters` keeps the cumulative NVLink counters and `counters`
+/// those of the DRM backends between calls, so throughput, utilization and
+/// power can be derived over `elapsed`.
 #[cfg(not(target_os = "macos"))]
 pub fn collect_gpu_metrics(
     handle: &GpuHandle,
     system: &System,
     users: &Users,
+    last_nvlink_counters: &mut HashMap<(u32, u32), (u64, u64)>,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
 ) -> Option<GpuMetrics> {
-    nvml_backend::collect_gpu_metrics(handle, system, users)
+    let mut metrics =
+        nvml_backend::collect_gpu_metrics(handle, system, users, last_nvlink_counters, elapsed);
+
+    // GPUs of every vendor go in one list after the NVIDIA ones, so NVML
+    // indices stay valid for settings and events
+    #[cfg(target_os = "linux")]
+    {
+        let others = [
+            handle.amd.as_ref().and_then(|amd| {
+                super::amd::collect_amd_metrics(amd, system, users, counters, elapsed)
+            }),
+            handle.intel.as_ref().and_then(|intel| {
+                super::intel::collect_intel_metrics(intel, system, users, counters, elapsed)
+            }),
+            handle.drm.as_ref().and_then(|drm| {
+                super::drm::collect_drm_metrics(drm, system, users, counters, elapsed)
+            }),
+        ];
+        for other in others.into_iter().flatten() {
+            match metrics {
+                Some(ref mut metrics) => metrics.merge(other),
+                None => metrics = Some(other),
+            }
+        }
+    }
+    counters.finish();
+    metrics
 }
 
 #[cfg(target_os = "macos")]
@@ -402,6 +1299,53 @@ pub fn collect_gpu_metrics(
     handle: &GpuHandle,
     system: &System,
     users: &Users,
+    _last_nvlink_counters: &mut HashMap<(u32, u32), (u64, u64)>,
+    _counters: &mut CounterDeltas,
+    _elapsed: Duration,
 ) -> Option<GpuMetrics> {
     metal_backend::collect_gpu_metrics(handle, system, users)
 }
+
+/// Change a device setting on the GPU with this UUID.
+#[cfg(not(target_os = "macos"))]
+pub fn apply_gpu_setting(
+    handle: &GpuHandle,
+    gpu_uuid: &str,
+    change: GpuSettingChange,
+) -> anyhow::Result<()> {
+    nvml_backend::apply_gpu_setting(handle, gpu_uuid, change)
+}
+
+#[cfg(target_os = "macos")]
+pub fn apply_gpu_setting(
+    _handle: &GpuHandle,
+    _gpu_uuid: &str,
+    _change: GpuSettingChange,
+) -> anyhow::Result<()> {
+    anyhow::bail!("device settings are not available on Metal")
+}
+
+/// NVML accounting statistics of a process that ran on the GPU with this
+/// UUID, once it has finished. `None` when accounting mode is off.
+#[cfg(not(target_os = "macos"))]
+pub fn process_accounting(
+    handle: &GpuHandle,
+    gpu_uuid: &str,
+    pid: u32,
+) -> Option<ProcessAccounting> {
+    nvml_backend::process_accounting(handle, gpu_uuid, pid)
+}
+
+#[cfg(target_os = "macos")]
+pub fn process_accounting(
+    _handle: &GpuHandle,
+    _gpu_uuid: &str,
+    _pid: u32,
+) -> Option<ProcessAccounting> {
+    None
+}
+
+/// Take the driver events received since the last call.
+pub fn drain_gpu_events(handle: &mut GpuHandle) -> Vec<GpuEvent> {
+    handle.drain_events()
+}
@@ -0,0 +1,373 @@
+//! Intel GPU metrics from i915/xe sysfs, hwmon/RAPL and DRM fdinfo (Linux).
+//!
+//! Intel GPUs report little directly: utilization is derived from RC6
+//! (idle) residency and power from energy counters, both between refreshes.
+//! Both roots are configurable so the parsers can run against a copy of the
+//! tree.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use sysinfo::{System, Users};
+
+use super::drm::{self, read_string, read_u64, DrmCard};
+use crate::types::{
+    BackendInfo, CounterDeltas, GpuBackend, GpuInfo, GpuMetrics, GpuSettings, Reading,
+};
+
+/// Intel devices and where to read them from.
+pub struct IntelHandle {
+    sysfs: PathBuf,
+    procfs: PathBuf,
+    cards: Vec<DrmCard>,
+}
+
+impl IntelHandle {
+    /// Find i915 and xe cards on this machine.
+    pub fn new() -> Option<Self> {
+        Self::discover(Path::new("/sys"), Path::new("/proc"))
+    }
+
+    /// Find i915 and xe cards under `sysfs`; processes are read from `procfs`.
+    pub fn discover(sysfs: &Path, procfs: &Path) -> Option<Self> {
+        let cards = drm::find_cards(sysfs, |driver| matches!(driver, "i915" | "xe"));
+        if cards.is_empty() {
+            return None;
+        }
+        Some(Self {
+            sysfs: sysfs.to_path_buf(),
+            procfs: procfs.to_path_buf(),
+            cards,
+        })
+    }
+}
+
+/// Collect metrics of every i915/xe card.
+///
+/// `counters` keeps RC6 residency, energy and each DRM client's engine time
+/// between calls so utilization and power can be derived over `elapsed`.
+pub fn collect_intel_metrics(
+    handle: &IntelHandle,
+    system: &System,
+    users: &Users,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
+) -> Option<GpuMetrics> {
+    let processes = drm::collect_processes(
+        &handle.procfs,
+        &handle.cards,
+        system,
+        users,
+        counters,
+        elapsed,
+    )
+    .processes;
+    let gpus: Vec<GpuInfo> = handle
+        .cards
+        .iter()
+        .enumerate()
+        .map(|(i, card)| {
+            let mut gpu = collect_card(handle, i as u32, card, counters, elapsed);
+            // Neither driver reports memory use per device; add up the clients
+            gpu.memory_used = processes
+                .iter()
+                .filter(|p| p.gpu_index == gpu.index)
+                .map(|p| p.gpu_memory)
+                .sum();
+            gpu
+        })
+        .collect();
+
+    let mut drivers: Vec<&str> = handle.cards.iter().map(|c| c.driver.as_str()).collect();
+    drivers.sort_unstable();
+    drivers.dedup();
+    Some(GpuMetrics {
+        topology: vec![vec![None; gpus.len()]; gpus.len()],
+        gpus,
+        processes,
+        drivers: vec![BackendInfo {
+            backend: GpuBackend::Intel,
+            driver_version: drm::driver_version(&handle.sysfs, &handle.procfs, drivers[0]),
+            api_version: drivers.join("/"),
+        }],
+    })
+}
+
+fn collect_card(
+    handle: &IntelHandle,
+    index: u32,
+    card: &DrmCard,
+    counters: &mut CounterDeltas,
+    elapsed: Duration,
+) -> GpuInfo {
+    let device = &card.device;
+    let xe = card.driver == "xe";
+    // xe keeps per-GT attributes under the tile, i915 on the card itself
+    let gt = device.join("tile0/gt0");
+    let elapsed_ms = elapsed.as_millis().max(1) as u64;
+
+    let (sm_clock, max_sm_clock) = if xe {
+        (
+            read_u64(&gt.join("freq0/act_freq")),
+            read_u64(&gt.join("freq0/rp0_freq")),
+        )
+    } else {
+        (
+            read_u64(&card.card.join("gt_act_freq_mhz")),
+            read_u64(&card.card.join("gt_RP0_freq_mhz")),
+        )
+    };
+
+    // Busy is whatever time the GPU did not spend in RC6 (powered down)
+    let rc6 = if xe {
+        read_u64(&gt.join("gtidle/idle_residency_ms"))
+    } else {
+        read_u64(&card.card.join("power/rc6_residency_ms"))
+    };
+    let gpu_utilization = rc6
+        .and_then(|ms| counters.delta(format!("intel/{}/rc6", card.bus_id), ms))
+        .map(|idle_ms| 100u64.saturating_sub(idle_ms * 100 / elapsed_ms) as u32);
+
+    // Discrete GPUs have their own energy counter, integrated ones share the
+    // package's and report through the RAPL uncore domain
+    let hwmon = drm::hwmon_dir(device);
+    let hwmon_value = |file: &str| hwmon.as_ref().and_then(|dir| read_u64(&dir.join(file)));
+    let microwatts_to_watts = |file: &str| hwmon_value(file).map(|uw| (uw / 1_000_000) as u32);
+    // The hwmon counter is 64-bit; the RAPL one wraps at its range
+    let energy = hwmon_value("energy1_input")
+        .map(|uj| (uj, None))
+        .or_else(|| rapl_uncore_energy(&handle.sysfs));
+    let power_usage = energy
+        .and_then(|(uj, range)| {
+            counters.wrapping_delta(format!("intel/{}/energy", card.bus_id), uj, range)
+        })
+        .map(|uj| (uj / 1000 / elapsed_ms) as u32);
+
+    GpuInfo {
+        index,
+        name: read_string(&device.join("device"))
+            .map(|id| format!("Intel GPU {}", id.trim_start_matches("0x")))
+            .unwrap_or_else(|| "Intel GPU".into()),
+        backend: GpuBackend::Intel,
+        temperature: hwmon.as_deref().and_then(read_temperature).into(),
+        fan_speeds: Reading::NotSupported,
+        power_usage: power_usage.into(),
+        power_limit: microwatts_to_watts("power1_max").into(),
+        gpu_utilization: gpu_utilization.into(),
+        memory_utilization: Reading::NotSupported,
+        // Local memory of discrete GPUs; integrated ones use system memory
+        memory_total: read_u64(&device.join("tile0/physical_vram_size_bytes"))
+            .or_else(|| read_u64(&card.card.join("lmem_total_bytes")))
+            .unwrap_or(0),
+        encoder_utilization: Reading::NotSupported,
+        decoder_utilization: Reading::NotSupported,
+        pcie_rx: Reading::NotSupported,
+        pcie_tx: Reading::NotSupported,
+        sm_clock: sm_clock.map(|mhz| mhz as u32).into(),
+        mem_clock: Reading::NotSupported,
+        max_sm_clock: max_sm_clock.map(|mhz| mhz as u32),
+        pstate: Reading::NotSupported,
+        pcie: drm::pcie_link(card),
+        numa_node: drm::numa_node(device),
+        cpu_affinity: read_string(&device.join("local_cpulist")),
+        // The RAPL counter wraps, so only the hwmon one is a total since boot
+        energy_consumption: hwmon_value("energy1_input").map(|uj| uj / 1000),
+        settings: GpuSettings {
+            power_limit_default: microwatts_to_watts("power1_rated_max"),
+            ..Default::default()
+        },
+        ..Default::default()
+    }
+}
+
+/// GPU temperature in °C: the `pkg` sensor where labeled, else the first.
+fn read_temperature(hwmon: &Path) -> Option<u32> {
+    let inputs: Vec<(Option<String>, u64)> = (1..=4)
+        .filter_map(|n| {
+            let input = read_u64(&hwmon.join(format!("temp{}_input", n)))?;
+            Some((read_string(&hwmon.join(format!("temp{}_label", n))), input))
+        })
+        .collect();
+    let (_, millidegrees) = inputs
+        .iter()
+        .find(|(label, _)| label.as_deref() == Some("pkg"))
+        .or(inputs.first())?;
+    Some((millidegrees / 1000) as u32)
+}
+
+/// Energy counter of the RAPL domain that covers an integrated GPU and the
+/// value it wraps at, in µJ.
+fn rapl_uncore_energy(sysfs: &Path) -> Option<(u64, Option<u64>)> {
+    let domain = fs::read_dir(sysfs.join("class/powercap"))
+        .ok()?
+        .flatten()
+        .map(|entry| entry.path())
+        .find(|path| read_string(&path.join("name")).as_deref() == Some("uncore"))?;
+    Some((
+        read_u64(&domain.join("energy_uj"))?,
+        read_u64(&domain.join("max_energy_range_uj")),
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::drm::fixture::Fixture;
+    use super::*;
+
+    /// Wraparound point of the RAPL counter on a typical client CPU.
+    const RAPL_RANGE: u64 = 262_143_328_850;
+
+    /// Collect one refresh a second after the previous one.
+    fn sample(handle: &IntelHandle, counters: &mut CounterDeltas) -> GpuMetrics {
+        let metrics = collect_intel_metrics(
+            handle,
+            &System::new(),
+            &Users::new(),
+            counters,
+            Duration::from_secs(1),
+        )
+        .unwrap();
+        counters.finish();
+        metrics
+    }
+
+    #[test]
+    fn discover_i915_and_xe_cards() {
+        let fixture = Fixture::new("intel-discover");
+        fixture.card("card0", "amdgpu", "0000:03:00.0");
+        assert!(IntelHandle::discover(&fixture.sysfs(), &fixture.procfs()).is_none());
+
+        fixture.card("card1", "i915", "0000:00:02.0");
+        fixture.card("card2", "xe", "0000:03:00.0");
+        let handle = IntelHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let drivers: Vec<&str> = handle.cards.iter().map(|c| c.driver.as_str()).collect();
+        assert_eq!(drivers, ["i915", "xe"]);
+    }
+
+    #[test]
+    fn i915_rc6_utilization_and_rapl_power() {
+        let fixture = Fixture::new("intel-i915");
+        let device = fixture.card("card0", "i915", "0000:00:02.0");
+        let card = device.parent().unwrap();
+        fixture.write(device.join("device"), "0xa7a0\n");
+        fixture.write(card.join("gt_act_freq_mhz"), "1100\n");
+        fixture.write(card.join("gt_RP0_freq_mhz"), "1450\n");
+        let rapl = "sys/class/powercap/intel-rapl:0:1";
+        fixture.write(format!("{}/name", rapl), "uncore\n");
+        fixture.write(
+            format!("{}/max_energy_range_uj", rapl),
+            &format!("{}\n", RAPL_RANGE),
+        );
+        let handle = IntelHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let mut counters = CounterDeltas::default();
+        let mut refresh = |rc6_ms: u64, energy_uj: u64| {
+            fixture.write(
+                card.join("power/rc6_residency_ms"),
+                &format!("{}\n", rc6_ms),
+            );
+            fixture.write(format!("{}/energy_uj", rapl), &format!("{}\n", energy_uj));
+            sample(&handle, &mut counters).gpus.remove(0)
+        };
+
+        let gpu = refresh(5000, RAPL_RANGE - 12_000_000);
+        assert_eq!(gpu.name, "Intel GPU a7a0");
+        assert_eq!(gpu.sm_clock.value(), Some(1100));
+        assert_eq!(gpu.max_sm_clock, Some(1450));
+        assert_eq!(gpu.gpu_utilization.value(), None);
+        assert_eq!(gpu.power_usage.value(), None);
+        // Only hwmon counts from boot; RAPL wraps
+        assert_eq!(gpu.energy_consumption, None);
+
+        // 250 ms of the last second in RC6, 10 J used
+        let gpu = refresh(5250, RAPL_RANGE - 2_000_000);
+        assert_eq!(gpu.gpu_utilization.value(), Some(75));
+        assert_eq!(gpu.power_usage.value(), Some(10));
+
+        // The counter wrapped: 2 J up to the range and 4 J past zero
+        let gpu = refresh(6250, 4_000_000);
+        assert_eq!(gpu.gpu_utilization.value(), Some(0));
+        assert_eq!(gpu.power_usage.value(), Some(6));
+    }
+
+    #[test]
+    fn xe_frequency_vram_and_hwmon_energy() {
+        let fixture = Fixture::new("intel-xe");
+        let device = fixture.card("card0", "xe", "0000:03:00.0");
+        let gt = device.join("tile0/gt0");
+        let hwmon = device.join("hwmon/hwmon4");
+        fixture.write(gt.join("freq0/act_freq"), "2050\n");
+        fixture.write(gt.join("freq0/rp0_freq"), "2400\n");
+        fixture.write(
+            device.join("tile0/physical_vram_size_bytes"),
+            "17179869184\n",
+        );
+        fixture.write(hwmon.join("temp1_label"), "vram\n");
+        fixture.write(hwmon.join("temp1_input"), "51000\n");
+        fixture.write(hwmon.join("temp2_label"), "pkg\n");
+        fixture.write(hwmon.join("temp2_input"), "63000\n");
+        fixture.write(hwmon.join("power1_max"), "190000000\n");
+        // i915's attributes must not be picked up for xe
+        fixture.write(device.parent().unwrap().join("gt_act_freq_mhz"), "300\n");
+        let handle = IntelHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let mut counters = CounterDeltas::default();
+        let mut refresh = |idle_ms: u64, energy_uj: u64| {
+            fixture.write(
+                gt.join("gtidle/idle_residency_ms"),
+                &format!("{}\n", idle_ms),
+            );
+            fixture.write(hwmon.join("energy1_input"), &format!("{}\n", energy_uj));
+            sample(&handle, &mut counters).gpus.remove(0)
+        };
+
+        let gpu = refresh(100, 900_000_000);
+        assert_eq!(gpu.sm_clock.value(), Some(2050));
+        assert_eq!(gpu.max_sm_clock, Some(2400));
+        assert_eq!(gpu.memory_total, 16 << 30);
+        assert_eq!(gpu.temperature.value(), Some(63));
+        assert_eq!(gpu.power_limit.value(), Some(190));
+        assert_eq!(gpu.energy_consumption, Some(900_000));
+
+        let gpu = refresh(700, 1_020_000_000);
+        assert_eq!(gpu.gpu_utilization.value(), Some(40));
+        assert_eq!(gpu.power_usage.value(), Some(120));
+
+        // A counter that went backwards, e.g. after a driver reload, is skipped
+        let gpu = refresh(1700, 5_000_000);
+        assert_eq!(gpu.power_usage.value(), None);
+    }
+
+    #[test]
+    fn xe_cycles_give_engine_utilization() {
+        let fixture = Fixture::new("intel-cycles");
+        fixture.card("card0", "xe", "0000:03:00.0");
+        let handle = IntelHandle::discover(&fixture.sysfs(), &fixture.procfs()).unwrap();
+        let mut counters = CounterDeltas::default();
+        let mut refresh = |rcs: u64, ccs: u64, total: u64| {
+            fixture.fdinfo(
+                900,
+                3,
+                &format!(
+                    "drm-driver:\txe\ndrm-pdev:\t0000:03:00.0\ndrm-client-id:\t2\n\
+                     drm-resident-vram0:\t64 MiB\ndrm-resident-system:\t8 MiB\n\
+                     drm-cycles-rcs:\t{}\ndrm-total-cycles-rcs:\t{}\n\
+                     drm-engine-capacity-ccs:\t2\n\
+                     drm-cycles-ccs:\t{}\ndrm-total-cycles-ccs:\t{}\n",
+                    rcs, total, ccs, total
+                ),
+            );
+            sample(&handle, &mut counters)
+        };
+
+        let metrics = refresh(1000, 0, 50_000);
+        assert_eq!(metrics.gpus[0].memory_used, 64 << 20);
+        assert_eq!(metrics.processes[0].sm_utilization, None);
+        assert_eq!(metrics.processes[0].process_type, "G");
+
+        // rcs busy 300 of 1000 cycles; ccs 1000 over two engines of 1000
+        let metrics = refresh(1300, 1000, 51_000);
+        let process = &metrics.processes[0];
+        assert_eq!((process.pid, process.gpu_index), (900, 0));
+        assert_eq!(process.sm_utilization, Some(50));
+        assert_eq!(process.process_type, "C");
+    }
+}
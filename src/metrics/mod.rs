@@ -1,7 +1,19 @@
 //! Metrics collection modules.
 
+#[cfg(target_os = "linux")]
+mod amd;
+#[cfg(target_os = "linux")]
+mod drm;
 mod gpu;
+#[cfg(target_os = "linux")]
+mod intel;
+#[cfg(not(target_os = "macos"))]
+mod nvml_ext;
 mod system;
+mod workload;
 
-pub use gpu::{collect_gpu_metrics, GpuHandle};
+pub use gpu::{
+    apply_gpu_setting, collect_gpu_metrics, drain_gpu_events, process_accounting, GpuHandle,
+};
 pub use system::collect_system_metrics;
+pub use workload::WorkloadCache;
@@ -0,0 +1,214 @@
+//! NVML queries that nvml-wrapper does not cover.
+//!
+//! MIG enumeration and link-scoped field values go through the raw bindings.
+//! NVML keeps its state per process, so a second handle to the library sees
+//! the devices initialized by `Nvml::init`.
+
+use std::ffi::CStr;
+use std::mem;
+
+use nvml_wrapper::{Device, Nvml};
+use nvml_wrapper_sys::bindings::field_id::{
+    NVML_FI_DEV_NVLINK_THROUGHPUT_DATA_RX, NVML_FI_DEV_NVLINK_THROUGHPUT_DATA_TX,
+};
+use nvml_wrapper_sys::bindings::{
+    nvmlDeviceAttributes_t, nvmlDevice_t, nvmlFieldValue_t,
+    nvmlReturn_enum_NVML_SUCCESS as NVML_SUCCESS,
+    nvmlValueType_enum_NVML_VALUE_TYPE_UNSIGNED_LONG as VALUE_TYPE_ULONG,
+    nvmlValueType_enum_NVML_VALUE_TYPE_UNSIGNED_LONG_LONG as VALUE_TYPE_ULONGLONG, NvmlLib,
+    NVML_DEVICE_MIG_ENABLE,
+};
+
+use crate::types::{MigInstance, MigInstanceId};
+
+#[cfg(target_os = "windows")]
+const LIB_PATH: &str = "nvml.dll";
+#[cfg(not(target_os = "windows"))]
+const LIB_PATH: &str = "libnvidia-ml.so";
+
+/// Raw NVML entry points.
+pub struct NvmlExt {
+    lib: NvmlLib,
+}
+
+impl NvmlExt {
+    /// Load a second handle to the NVML library.
+    pub fn load() -> Option<Self> {
+        let lib = unsafe { NvmlLib::new(LIB_PATH) }.ok()?;
+        Some(Self { lib })
+    }
+
+    /// Whether the driver exports every call used for MIG enumeration.
+    fn has_mig_api(&self) -> bool {
+        self.lib.nvmlDeviceGetMigMode.is_ok()
+            && self.lib.nvmlDeviceGetMaxMigDeviceCount.is_ok()
+            && self.lib.nvmlDeviceGetMigDeviceHandleByIndex.is_ok()
+            && self.lib.nvmlDeviceGetGpuInstanceId.is_ok()
+            && self.lib.nvmlDeviceGetComputeInstanceId.is_ok()
+            && self.lib.nvmlDeviceGetAttributes_v2.is_ok()
+            && self.lib.nvmlDeviceGetUUID.is_ok()
+    }
+
+    /// Current MIG mode, or `None` if the device does not support MIG.
+    pub fn mig_enabled(&self, device: &Device) -> Option<bool> {
+        if !self.has_mig_api() {
+            return None;
+        }
+        let mut current = 0;
+        let mut pending = 0;
+        let ret = unsafe {
+            self.lib
+                .nvmlDeviceGetMigMode(device.handle(), &mut current, &mut pending)
+        };
+        (ret == NVML_SUCCESS).then_some(current == NVML_DEVICE_MIG_ENABLE)
+    }
+
+    /// Enumerate the MIG devices of a GPU with MIG mode enabled.
+    ///
+    /// Each instance is returned together with a `Device` for its MIG handle,
+    /// which supports the regular memory and process queries.
+    pub fn mig_instances<'nvml>(
+        &self,
+        nvml: &'nvml Nvml,
+        device: &Device<'nvml>,
+    ) -> Vec<(MigInstance, Device<'nvml>)> {
+        let parent = unsafe { device.handle() };
+
+        let mut max = 0;
+        if unsafe { self.lib.nvmlDeviceGetMaxMigDeviceCount(parent, &mut max) } != NVML_SUCCESS {
+            return Vec::new();
+        }
+
+        let mut instances = Vec::new();
+        for index in 0..max {
+            let mut handle: nvmlDevice_t = unsafe { mem::zeroed() };
+            let ret = unsafe {
+                self.lib
+                    .nvmlDeviceGetMigDeviceHandleByIndex(parent, index, &mut handle)
+            };
+            // Unpopulated slots report NotFound
+            if ret != NVML_SUCCESS {
+                continue;
+            }
+
+            let Some(id) = self.instance_id(handle) else {
+                continue;
+            };
+            let attributes = self.attributes(handle);
+            let mig_device = unsafe { Device::new(handle, nvml) };
+            let (memory_used, memory_total) = mig_device
+                .memory_info()
+                .map(|m| (m.used, m.total))
+                .unwrap_or_default();
+
+            let gpu_slices = attributes.map_or(0, |a| a.gpuInstanceSliceCount);
+            let compute_slices = attributes.map_or(0, |a| a.computeInstanceSliceCount);
+            let memory_mb = attributes.map_or(memory_total / (1024 * 1024), |a| a.memorySizeMB);
+            let memory_gb = (memory_mb as f64 / 1024.0).round() as u64;
+            // Compute instances smaller than their GPU instance get a `1c.` prefix
+            let mut profile = format!("{}g.{}gb", gpu_slices, memory_gb);
+            if compute_slices > 0 && compute_slices < gpu_slices {
+                profile = format!("{}c.{}", compute_slices, profile);
+            }
+
+            instances.push((
+                MigInstance {
+                    id,
+                    profile,
+                    uuid: self.uuid(handle).unwrap_or_default(),
+                    sm_count: attributes.map_or(0, |a| a.multiprocessorCount),
+                    memory_used,
+                    memory_total,
+                },
+                mig_device,
+            ));
+        }
+
+        instances
+    }
+
+    fn instance_id(&self, handle: nvmlDevice_t) -> Option<MigInstanceId> {
+        let mut gpu_instance = 0;
+        let mut compute_instance = 0;
+        unsafe {
+            if self
+                .lib
+                .nvmlDeviceGetGpuInstanceId(handle, &mut gpu_instance)
+                != NVML_SUCCESS
+                || self
+                    .lib
+                    .nvmlDeviceGetComputeInstanceId(handle, &mut compute_instance)
+                    != NVML_SUCCESS
+            {
+                return None;
+            }
+        }
+        Some(MigInstanceId {
+            gpu_instance,
+            compute_instance,
+        })
+    }
+
+    fn attributes(&self, handle: nvmlDevice_t) -> Option<nvmlDeviceAttributes_t> {
+        let mut attributes: nvmlDeviceAttributes_t = unsafe { mem::zeroed() };
+        let ret = unsafe { self.lib.nvmlDeviceGetAttributes_v2(handle, &mut attributes) };
+        (ret == NVML_SUCCESS).then_some(attributes)
+    }
+
+    fn uuid(&self, handle: nvmlDevice_t) -> Option<String> {
+        let mut buf = [0 as std::os::raw::c_char; 96];
+        let ret = unsafe {
+            self.lib
+                .nvmlDeviceGetUUID(handle, buf.as_mut_ptr(), buf.len() as u32)
+        };
+        if ret != NVML_SUCCESS {
+            return None;
+        }
+        let uuid = unsafe { CStr::from_ptr(buf.as_ptr()) };
+        Some(uuid.to_string_lossy().into_owned())
+    }
+
+    /// Cumulative NVLink data throughput of one link as `(rx, tx)` in bytes.
+    pub fn nvlink_throughput(&self, device: &Device, link: u32) -> Option<(u64, u64)> {
+        if self.lib.nvmlDeviceGetFieldValues.is_err() {
+            return None;
+        }
+
+        let mut values: [nvmlFieldValue_t; 2] = unsafe { mem::zeroed() };
+        values[0].fieldId = NVML_FI_DEV_NVLINK_THROUGHPUT_DATA_RX;
+        values[1].fieldId = NVML_FI_DEV_NVLINK_THROUGHPUT_DATA_TX;
+        for value in &mut values {
+            value.scopeId = link;
+        }
+
+        let ret = unsafe {
+            self.lib.nvmlDeviceGetFieldValues(
+                device.handle(),
+                values.len() as i32,
+                values.as_mut_ptr(),
+            )
+        };
+        if ret != NVML_SUCCESS {
+            return None;
+        }
+
+        // Counters are reported in KiB
+        let read = |value: &nvmlFieldValue_t| -> Option<u64> {
+            if value.nvmlReturn != NVML_SUCCESS {
+                return None;
+            }
+            // c_ulong is only 32 bits on Windows
+            #[allow(clippy::unnecessary_cast)]
+            let kib = unsafe {
+                match value.valueType {
+                    VALUE_TYPE_ULONGLONG => value.value.ullVal,
+                    VALUE_TYPE_ULONG => value.value.ulVal as u64,
+                    _ => value.value.uiVal as u64,
+                }
+            };
+            Some(kib * 1024)
+        };
+
+        Some((read(&values[0])?, read(&values[1])?))
+    }
+}
@@ -141,6 +141,8 @@ pub fn collect_system_metrics(
                 cmd.join(" ")
             };
 
+            let disk_usage = proc.disk_usage();
+
             ProcessInfo {
                 pid: pid.as_u32(),
                 name: proc.name().to_string_lossy().to_string(),
@@ -150,6 +152,12 @@ pub fn collect_system_metrics(
                 memory_bytes: proc.memory(),
                 status,
                 command,
+                threads: proc.tasks().map(|t| t.len()),
+                start_time: proc.start_time(),
+                disk_read_rate: disk_usage.read_bytes as f64 / elapsed_secs,
+                disk_write_rate: disk_usage.written_bytes as f64 / elapsed_secs,
+                // Filled in by `WorkloadCache`, which keeps it across refreshes
+                workload: Default::default(),
             }
         })
         .collect();
@@ -0,0 +1,201 @@
+//! Container and batch job a process belongs to, from `/proc/<pid>/cgroup`
+//! and `/proc/<pid>/environ` (Linux).
+//!
+//! Neither changes while a process runs, so results are cached by PID and
+//! start time. Elsewhere the files do not exist and both stay empty.
+
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::types::{ProcessInfo, Workload};
+
+/// Cgroup name prefixes container runtimes put before the container ID.
+const RUNTIME_PREFIXES: [&str; 4] = ["docker-", "cri-containerd-", "crio-", "libpod-"];
+
+/// Environment variables batch schedulers set to the job ID.
+const JOB_VARIABLES: [&str; 3] = ["SLURM_JOB_ID", "PBS_JOBID", "LSB_JOBID"];
+
+/// Workloads by PID, with the start time they were read for so a reused PID
+/// is read again.
+#[derive(Default)]
+pub struct WorkloadCache {
+    entries: HashMap<u32, (u64, Workload)>,
+}
+
+impl WorkloadCache {
+    /// Workload of a process, read from `procfs` the first time it is seen.
+    pub fn get(&mut self, procfs: &Path, pid: u32, start_time: u64) -> Workload {
+        match self.entries.get(&pid) {
+            Some((start, workload)) if *start == start_time => workload.clone(),
+            _ => {
+                let workload = read_workload(procfs, pid);
+                self.entries.insert(pid, (start_time, workload.clone()));
+                workload
+            }
+        }
+    }
+
+    /// Fill in the workload of every process and forget processes that are
+    /// gone.
+    pub fn update(&mut self, processes: &mut [ProcessInfo]) {
+        for process in processes.iter_mut() {
+            process.workload = self.get(Path::new("/proc"), process.pid, process.start_time);
+        }
+        let alive: HashSet<u32> = processes.iter().map(|p| p.pid).collect();
+        self.retain(&alive);
+    }
+
+    /// Drop the entries of processes that are gone.
+    fn retain(&mut self, alive: &HashSet<u32>) {
+        self.entries.retain(|pid, _| alive.contains(pid));
+    }
+}
+
+/// Read the container and job of a process.
+fn read_workload(procfs: &Path, pid: u32) -> Workload {
+    let dir = procfs.join(pid.to_string());
+    let cgroup = fs::read_to_string(dir.join("cgroup")).unwrap_or_default();
+    // Only readable for our own processes unless running as root
+    let environ = fs::read(dir.join("environ")).unwrap_or_default();
+    Workload {
+        container: container_id(&cgroup),
+        job: job_from_environ(&environ).or_else(|| job_from_cgroup(&cgroup)),
+    }
+}
+
+/// Short (12 character) ID of the container whose cgroup a process is in.
+///
+/// Docker, containerd, CRI-O and Podman name the cgroup after the 64 digit
+/// container ID, e.g. `/docker/<id>` or `.../cri-containerd-<id>.scope`.
+fn container_id(cgroup: &str) -> Option<String> {
+    cgroup_components(cgroup).rev().find_map(|component| {
+        let name = component.trim_end_matches(".scope");
+        let id = RUNTIME_PREFIXES
+            .iter()
+            .find_map(|prefix| name.strip_prefix(prefix))
+            .unwrap_or(name);
+        (id.len() == 64 && id.chars().all(|c| c.is_ascii_hexdigit())).then(|| id[..12].to_string())
+    })
+}
+
+/// Job ID from a Slurm cgroup path such as `/slurm/uid_1000/job_4242/step_0`.
+fn job_from_cgroup(cgroup: &str) -> Option<String> {
+    cgroup_components(cgroup).rev().find_map(|component| {
+        let id = component.strip_prefix("job_")?;
+        (!id.is_empty() && id.chars().all(|c| c.is_ascii_digit())).then(|| id.to_string())
+    })
+}
+
+/// Job ID from the scheduler variables in a NUL-separated environment block.
+fn job_from_environ(environ: &[u8]) -> Option<String> {
+    let vars: HashMap<&str, &str> = environ
+        .split(|&b| b == 0)
+        .filter_map(|var| std::str::from_utf8(var).ok()?.split_once('='))
+        .collect();
+    JOB_VARIABLES
+        .iter()
+        .find_map(|name| vars.get(name).filter(|v| !v.is_empty()))
+        .map(|v| v.to_string())
+}
+
+/// Path components of every hierarchy in a `/proc/<pid>/cgroup` file, whose
+/// lines read `id:controllers:path`.
+fn cgroup_components(cgroup: &str) -> impl DoubleEndedIterator<Item = &str> {
+    cgroup
+        .lines()
+        .filter_map(|line| line.splitn(3, ':').nth(2))
+        .flat_map(|path| path.split('/'))
+        .filter(|c| !c.is_empty())
+        .collect::<Vec<_>>()
+        .into_iter()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const ID: &str = "3f2a9c1b7d4e5f6071829a4b5c6d7e8f90112233445566778899aabbccddeeff";
+
+    #[test]
+    fn container_from_runtime_cgroups() {
+        let cases = [
+            format!("0::/system.slice/docker-{}.scope", ID),
+            format!("12:memory:/docker/{}", ID),
+            format!(
+                "0::/kubepods.slice/kubepods-burstable.slice/kubepods-burstable-pod1a2b.slice/cri-containerd-{}.scope",
+                ID
+            ),
+            format!("0::/machine.slice/libpod-{}.scope/container", ID),
+            format!("0::/kubepods/besteffort/pod1a2b/crio-{}.scope", ID),
+        ];
+        for cgroup in cases {
+            assert_eq!(
+                container_id(&cgroup).as_deref(),
+                Some(&ID[..12]),
+                "{}",
+                cgroup
+            );
+        }
+    }
+
+    #[test]
+    fn no_container_on_the_host() {
+        assert_eq!(
+            container_id("0::/user.slice/user-1000.slice/session-2.scope"),
+            None
+        );
+        assert_eq!(container_id("0::/system.slice/docker.service"), None);
+        assert_eq!(container_id(""), None);
+    }
+
+    #[test]
+    fn job_from_slurm_cgroup() {
+        let v1 =
+            "4:cpuset:/slurm/uid_1000/job_4242/step_0\n3:memory:/slurm/uid_1000/job_4242/step_0";
+        assert_eq!(job_from_cgroup(v1).as_deref(), Some("4242"));
+        let v2 = "0::/system.slice/slurmstepd.scope/job_77/step_batch/user/task_0";
+        assert_eq!(job_from_cgroup(v2).as_deref(), Some("77"));
+        assert_eq!(job_from_cgroup("0::/user.slice"), None);
+        assert_eq!(job_from_cgroup("0::/job_"), None);
+    }
+
+    #[test]
+    fn job_from_scheduler_environment() {
+        let environ = b"HOME=/home/a\0SLURM_JOB_ID=9001\0PATH=/bin\0";
+        assert_eq!(job_from_environ(environ).as_deref(), Some("9001"));
+        assert_eq!(
+            job_from_environ(b"PBS_JOBID=123.pbs01\0").as_deref(),
+            Some("123.pbs01")
+        );
+        assert_eq!(job_from_environ(b"SLURM_JOB_ID=\0HOME=/root\0"), None);
+        assert_eq!(job_from_environ(b""), None);
+    }
+
+    #[test]
+    fn workload_read_from_procfs() {
+        let root = std::env::temp_dir().join(format!("nvglances-workload-{}", std::process::id()));
+        let dir = root.join("31");
+        fs::create_dir_all(&dir).unwrap();
+        fs::write(
+            dir.join("cgroup"),
+            format!("0::/system.slice/docker-{}.scope\n", ID),
+        )
+        .unwrap();
+        fs::write(dir.join("environ"), b"SLURM_JOB_ID=55\0").unwrap();
+
+        let mut cache = WorkloadCache::default();
+        let workload = cache.get(&root, 31, 100);
+        assert_eq!(workload.container.as_deref(), Some(&ID[..12]));
+        assert_eq!(workload.job.as_deref(), Some("55"));
+
+        // Cached for the same start time, read again for a reused PID
+        fs::write(dir.join("environ"), b"SLURM_JOB_ID=56\0").unwrap();
+        assert_eq!(cache.get(&root, 31, 100).job.as_deref(), Some("55"));
+        assert_eq!(cache.get(&root, 31, 200).job.as_deref(), Some("56"));
+
+        cache.retain(&HashSet::new());
+        assert!(cache.entries.is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,5 +1,7 @@
 //! Data types and structures used throughout nvglances.
 
+use std::collections::{HashMap, HashSet};
+
 /// CPU core information.
 #[derive(Clone, Default)]
 #[allow(dead_code)]
@@ -52,29 +54,663 @@ pub struct ProcessInfo {
     pub memory_bytes: u64,
     pub status: String,
     pub command: String,
+    pub threads: Option<usize>,
+    pub start_time: u64,
+    pub disk_read_rate: f64,
+    pub disk_write_rate: f64,
+    pub workload: Workload,
 }
 
-/// GPU information from NVML.
+/// Container and batch job a process runs in.
+#[derive(Clone, Default)]
+pub struct Workload {
+    /// Short container ID.
+    pub container: Option<String>,
+    /// Scheduler job ID, e.g. Slurm's.
+    pub job: Option<String>,
+}
+
+/// GPU information from NVML or another backend.
 #[derive(Clone, Default)]
 #[allow(dead_code)]
 pub struct GpuInfo {
     pub index: u32,
     pub name: String,
-    pub temperature: u32,
-    pub fan_speed: u32,
-    pub power_usage: u32,
-    pub power_limit: u32,
-    pub gpu_utilization: u32,
-    pub memory_utilization: u32,
+    /// Backend that reports this GPU.
+    pub backend: GpuBackend,
+    /// Identity that survives reboots and re-enumeration, unlike `index`.
+    pub uuid: Option<String>,
+    pub serial: Option<String>,
+    pub board_part_number: Option<String>,
+    pub vbios_version: Option<String>,
+    /// Minor number of the `/dev/nvidiaN` device node (Linux).
+    pub minor_number: Option<u32>,
+    pub temperature: Reading<u32>,
+    /// Memory (HBM/GDDR) temperature, where the board has a sensor.
+    pub memory_temperature: Option<u32>,
+    pub thresholds: TempThresholds,
+    /// Speed of each fan in percent; empty for passively cooled boards.
+    pub fan_speeds: Reading<Vec<u32>>,
+    /// Power draw and limit in watts.
+    pub power_usage: Reading<u32>,
+    pub power_limit: Reading<u32>,
+    pub gpu_utilization: Reading<u32>,
+    pub memory_utilization: Reading<u32>,
+    pub memory_used: u64,
+    pub memory_total: u64,
+    pub encoder_utilization: Reading<u32>,
+    pub decoder_utilization: Reading<u32>,
+    /// PCIe throughput in bytes per second.
+    pub pcie_rx: Reading<u64>,
+    pub pcie_tx: Reading<u64>,
+    pub sm_clock: Reading<u32>,
+    pub mem_clock: Reading<u32>,
+    pub max_sm_clock: Option<u32>,
+    pub max_mem_clock: Option<u32>,
+    pub boost_clock: Option<u32>,
+    pub pstate: Reading<String>,
+    pub throttle_reasons: Vec<ThrottleReason>,
+    pub health: GpuHealth,
+    /// MIG mode, or `None` if the GPU does not support MIG.
+    pub mig_enabled: Option<bool>,
+    pub mig_instances: Vec<MigInstance>,
+    pub pcie: PcieLink,
+    pub numa_node: Option<u32>,
+    /// CPUs close to the GPU as a range list, e.g. `0-15,32-47`.
+    pub cpu_affinity: Option<String>,
+    pub nvlinks: Vec<NvLinkInfo>,
+    /// Energy counter in millijoules since the driver was loaded.
+    pub energy_consumption: Option<u64>,
+    pub settings: GpuSettings,
+    pub status: GpuStatus,
+}
+
+/// A GPU reading that may be missing, so a sensor the GPU lacks is not
+/// mistaken for a reading of zero.
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub enum Reading<T> {
+    Value(T),
+    /// The GPU, driver or backend does not report this.
+    #[default]
+    NotSupported,
+    /// The query failed.
+    Error,
+}
+
+impl<T> Reading<T> {
+    pub fn value(self) -> Option<T> {
+        match self {
+            Reading::Value(v) => Some(v),
+            _ => None,
+        }
+    }
+
+    pub fn as_ref(&self) -> Reading<&T> {
+        match self {
+            Reading::Value(v) => Reading::Value(v),
+            Reading::NotSupported => Reading::NotSupported,
+            Reading::Error => Reading::Error,
+        }
+    }
+
+    pub fn map<U>(self, f: impl FnOnce(T) -> U) -> Reading<U> {
+        match self {
+            Reading::Value(v) => Reading::Value(f(v)),
+            Reading::NotSupported => Reading::NotSupported,
+            Reading::Error => Reading::Error,
+        }
+    }
+
+    /// The value formatted by `f`, or `N/A` / `ERR`.
+    pub fn display(&self, f: impl FnOnce(&T) -> String) -> String {
+        match self {
+            Reading::Value(v) => f(v),
+            Reading::NotSupported => "N/A".to_string(),
+            Reading::Error => "ERR".to_string(),
+        }
+    }
+}
+
+/// A value the backend reads where it exists, so `None` means not supported.
+impl<T> From<Option<T>> for Reading<T> {
+    fn from(value: Option<T>) -> Self {
+        value.map_or(Reading::NotSupported, Reading::Value)
+    }
+}
+
+impl<T: std::fmt::Display> Reading<T> {
+    /// The value followed by `unit`, e.g. `65°C`, or `N/A` / `ERR`.
+    pub fn label(&self, unit: &str) -> String {
+        self.display(|v| format!("{}{}", v, unit))
+    }
+}
+
+/// Whether a GPU answers queries. Metrics of a GPU that does not are left at
+/// their defaults and must not be shown as readings.
+#[derive(Clone, Default, PartialEq, Eq)]
+pub enum GpuStatus {
+    #[default]
+    Ok,
+    /// Queries fail; `code` names the driver's error for the badge.
+    Failed { fault: GpuFault, code: String },
+}
+
+/// What a driver error means for the GPU it came from.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GpuFault {
+    /// The GPU fell off the bus.
+    Lost,
+    /// The GPU no longer enumerates.
+    NotFound,
+    /// The driver failed on the GPU for no stated reason.
+    Unknown,
+    /// Any other error, such as missing permissions.
+    Other,
+}
+
+impl GpuStatus {
+    pub fn is_ok(&self) -> bool {
+        *self == GpuStatus::Ok
+    }
+
+    /// Badge text, e.g. `LOST: GpuLost`.
+    pub fn label(&self) -> String {
+        match self {
+            GpuStatus::Ok => "OK".to_string(),
+            GpuStatus::Failed {
+                fault: GpuFault::Lost | GpuFault::NotFound,
+                code,
+            } => format!("LOST: {}", code),
+            GpuStatus::Failed { code, .. } => format!("ERROR: {}", code),
+        }
+    }
+}
+
+impl GpuInfo {
+    /// Whether initializing NVML again may bring this GPU back: it fell off
+    /// the bus, no longer enumerates (NVML only counts devices at init) or
+    /// NVML failed on it for no stated reason. GPUs of other backends are not
+    /// helped by it.
+    pub fn needs_nvml_reinit(&self) -> bool {
+        self.backend == GpuBackend::Nvml
+            && matches!(
+                self.status,
+                GpuStatus::Failed {
+                    fault: GpuFault::Lost | GpuFault::NotFound | GpuFault::Unknown,
+                    ..
+                }
+            )
+    }
+
+    /// Key for per-GPU state that must not follow a changing index: the UUID,
+    /// else the PCI bus ID, or the index for GPUs with neither.
+    pub fn stable_id(&self) -> String {
+        match (&self.uuid, &self.pcie.bus_id) {
+            (Some(uuid), _) => uuid.clone(),
+            (None, Some(bus_id)) => bus_id.clone(),
+            (None, None) => format!("#{}", self.index),
+        }
+    }
+}
+
+/// Driver settings of a GPU, shown in the settings panel.
+#[derive(Clone, Default)]
+pub struct GpuSettings {
+    pub persistence_mode: Option<bool>,
+    pub compute_mode: Option<ComputeMode>,
+    /// Whether a display is initialized on the GPU.
+    pub display_active: Option<bool>,
+    /// Application clocks in MHz, the target clocks while compute runs.
+    pub app_sm_clock: Option<u32>,
+    pub app_mem_clock: Option<u32>,
+    pub default_app_sm_clock: Option<u32>,
+    pub default_app_mem_clock: Option<u32>,
+    /// Power limit range and default in watts.
+    pub power_limit_min: Option<u32>,
+    pub power_limit_max: Option<u32>,
+    pub power_limit_default: Option<u32>,
+}
+
+/// Who may create CUDA contexts on a GPU.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum ComputeMode {
+    Default,
+    ExclusiveThread,
+    ExclusiveProcess,
+    Prohibited,
+}
+
+impl ComputeMode {
+    /// Modes that can be selected; exclusive-thread is no longer supported.
+    pub const SELECTABLE: [ComputeMode; 3] = [
+        ComputeMode::Default,
+        ComputeMode::ExclusiveProcess,
+        ComputeMode::Prohibited,
+    ];
+
+    pub fn label(self) -> &'static str {
+        match self {
+            ComputeMode::Default => "Default",
+            ComputeMode::ExclusiveThread => "Exclusive thread",
+            ComputeMode::ExclusiveProcess => "Exclusive process",
+            ComputeMode::Prohibited => "Prohibited",
+        }
+    }
+
+    /// Next (or previous) selectable mode.
+    pub fn cycle(self, forward: bool) -> Self {
+        let modes = Self::SELECTABLE;
+        let pos = modes.iter().position(|&m| m == self).unwrap_or(0);
+        let next = if forward {
+            (pos + 1) % modes.len()
+        } else {
+            (pos + modes.len() - 1) % modes.len()
+        };
+        modes[next]
+    }
+}
+
+/// A device setting change waiting for confirmation.
+#[derive(Clone, Copy, PartialEq)]
+pub enum GpuSettingChange {
+    /// Power limit in watts.
+    PowerLimit(u32),
+    ComputeMode(ComputeMode),
+    /// Lock the SM clock at this many MHz, or `None` to unlock it.
+    LockClocks(Option<u32>),
+}
+
+impl GpuSettingChange {
+    /// What the change does, for the confirmation dialog and status line.
+    pub fn describe(self) -> String {
+        match self {
+            GpuSettingChange::PowerLimit(watts) => format!("Set power limit to {} W", watts),
+            GpuSettingChange::ComputeMode(mode) => {
+                format!("Set compute mode to {}", mode.label())
+            }
+            GpuSettingChange::LockClocks(Some(mhz)) => format!("Lock SM clock at {} MHz", mhz),
+            GpuSettingChange::LockClocks(None) => "Unlock SM clock".to_string(),
+        }
+    }
+}
+
+/// Temperatures in °C at which the driver steps in.
+#[derive(Clone, Copy, Default)]
+pub struct TempThresholds {
+    /// Clocks are reduced above this.
+    pub slowdown: Option<u32>,
+    /// The GPU powers off above this.
+    pub shutdown: Option<u32>,
+    /// Highest memory temperature for normal operation.
+    pub memory_max: Option<u32>,
+}
+
+impl TempThresholds {
+    /// Where the GPU core temperature turns critical.
+    pub fn gpu_limit(&self) -> Option<u32> {
+        self.slowdown.or(self.shutdown)
+    }
+}
+
+/// PCIe bus address and link training state.
+#[derive(Clone, Default)]
+pub struct PcieLink {
+    pub bus_id: Option<String>,
+    pub current_gen: Option<u32>,
+    pub max_gen: Option<u32>,
+    pub current_width: Option<u32>,
+    pub max_width: Option<u32>,
+    /// PCIe replays since the driver loaded; each is a retransmitted packet.
+    pub replay_count: Option<u32>,
+}
+
+impl PcieLink {
+    /// `Gen4 x16` style label for the current link.
+    pub fn current_label(&self) -> String {
+        link_label(self.current_gen, self.current_width)
+    }
+
+    /// `Gen4 x16` style label for the best link the slot and GPU support.
+    pub fn max_label(&self) -> String {
+        link_label(self.max_gen, self.max_width)
+    }
+
+    /// Whether the link trained to fewer lanes than supported. Width does not
+    /// change at runtime, so this always points at a hardware problem.
+    pub fn width_degraded(&self) -> bool {
+        matches!((self.current_width, self.max_width), (Some(c), Some(m)) if c < m)
+    }
+
+    /// Whether the link runs below its maximum generation. GPUs drop the
+    /// generation on their own when idle, so this only matters under load.
+    pub fn gen_degraded(&self) -> bool {
+        matches!((self.current_gen, self.max_gen), (Some(c), Some(m)) if c < m)
+    }
+}
+
+fn link_label(generation: Option<u32>, width: Option<u32>) -> String {
+    match (generation, width) {
+        (Some(g), Some(w)) => format!("Gen{} x{}", g, w),
+        (Some(g), None) => format!("Gen{}", g),
+        (None, Some(w)) => format!("x{}", w),
+        (None, None) => "-".into(),
+    }
+}
+
+/// PCIe replay counts observed for one GPU since nvglances started.
+#[derive(Clone, Copy)]
+pub struct PcieReplayTracker {
+    /// Count at the first sample.
+    pub baseline: u32,
+    pub last: u32,
+    pub last_increase: Option<std::time::Instant>,
+}
+
+impl PcieReplayTracker {
+    /// Replays since nvglances started.
+    pub fn since_start(&self) -> u32 {
+        self.last.saturating_sub(self.baseline)
+    }
+}
+
+/// State of one NVLink of a GPU.
+#[derive(Clone)]
+pub struct NvLinkInfo {
+    pub link: u32,
+    pub active: bool,
+    pub version: Option<u32>,
+    pub remote_bus_id: Option<String>,
+    /// Index of the GPU at the other end, if the link goes straight to a GPU.
+    pub remote_gpu: Option<u32>,
+    /// Data throughput in bytes per second.
+    pub rx_rate: Option<f64>,
+    pub tx_rate: Option<f64>,
+}
+
+/// Path between two GPUs, in nvidia-smi `topo -m` terms.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum TopologyPath {
+    /// Direct NVLink with this many links.
+    NvLink(u32),
+    /// NVLink through an NVSwitch.
+    NvSwitch,
+    /// Same board (multi-GPU cards).
+    Internal,
+    /// At most one PCIe switch.
+    Pix,
+    /// Multiple PCIe switches, no host bridge.
+    Pxb,
+    /// Through a PCIe host bridge.
+    Phb,
+    /// Across host bridges within a NUMA node.
+    Node,
+    /// Across NUMA nodes (SMP interconnect).
+    Sys,
+}
+
+impl TopologyPath {
+    pub fn label(self) -> String {
+        match self {
+            TopologyPath::NvLink(n) => format!("NV{}", n),
+            TopologyPath::NvSwitch => "NVS".into(),
+            TopologyPath::Internal => "INT".into(),
+            TopologyPath::Pix => "PIX".into(),
+            TopologyPath::Pxb => "PXB".into(),
+            TopologyPath::Phb => "PHB".into(),
+            TopologyPath::Node => "NODE".into(),
+            TopologyPath::Sys => "SYS".into(),
+        }
+    }
+}
+
+/// GPU instance and compute instance IDs identifying a MIG slice.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MigInstanceId {
+    pub gpu_instance: u32,
+    pub compute_instance: u32,
+}
+
+impl MigInstanceId {
+    /// Compact `GI.CI` label.
+    pub fn label(self) -> String {
+        format!("{}.{}", self.gpu_instance, self.compute_instance)
+    }
+}
+
+/// One MIG device carved out of a physical GPU.
+#[derive(Clone)]
+pub struct MigInstance {
+    pub id: MigInstanceId,
+    /// Profile name in nvidia-smi style, e.g. `3g.20gb` or `1c.3g.20gb`.
+    pub profile: String,
+    /// MIG device UUID, as accepted by `CUDA_VISIBLE_DEVICES`.
+    pub uuid: String,
+    pub sm_count: u32,
     pub memory_used: u64,
     pub memory_total: u64,
-    pub encoder_utilization: u32,
-    pub decoder_utilization: u32,
-    pub pcie_rx: u64,
-    pub pcie_tx: u64,
-    pub sm_clock: u32,
-    pub mem_clock: u32,
-    pub pstate: String,
+}
+
+/// Why the GPU clocks are currently held below their maximum.
+#[derive(Clone, Copy, PartialEq, Eq, Hash)]
+pub enum ThrottleReason {
+    Idle,
+    ApplicationsClocks,
+    PowerCap,
+    HwSlowdown,
+    SyncBoost,
+    SwThermal,
+    HwThermal,
+    PowerBrake,
+    DisplayClocks,
+}
+
+impl ThrottleReason {
+    /// Short badge label.
+    pub fn label(self) -> &'static str {
+        match self {
+            ThrottleReason::Idle => "IDLE",
+            ThrottleReason::ApplicationsClocks => "APP CLK",
+            ThrottleReason::PowerCap => "PWR CAP",
+            ThrottleReason::HwSlowdown => "HW SLOW",
+            ThrottleReason::SyncBoost => "SYNC",
+            ThrottleReason::SwThermal => "SW THERM",
+            ThrottleReason::HwThermal => "HW THERM",
+            ThrottleReason::PowerBrake => "PWR BRAKE",
+            ThrottleReason::DisplayClocks => "DISPLAY",
+        }
+    }
+
+    /// Whether this reason means the GPU is being slowed down, as opposed to
+    /// idling or running at clocks someone configured on purpose.
+    pub fn is_slowdown(self) -> bool {
+        !matches!(
+            self,
+            ThrottleReason::Idle
+                | ThrottleReason::ApplicationsClocks
+                | ThrottleReason::DisplayClocks
+        )
+    }
+}
+
+/// Memory error and page retirement state reported by the driver.
+///
+/// Every counter is optional because consumer boards and older architectures
+/// support only a subset of them (row remapping is Ampere and newer, page
+/// retirement is Kepler through Turing).
+#[derive(Clone, Default)]
+pub struct GpuHealth {
+    pub ecc_enabled: Option<bool>,
+    pub ecc_pending: Option<bool>,
+    pub volatile_corrected: Option<u64>,
+    pub volatile_uncorrected: Option<u64>,
+    pub aggregate_corrected: Option<u64>,
+    pub aggregate_uncorrected: Option<u64>,
+    pub retired_pages_sbe: Option<usize>,
+    pub retired_pages_dbe: Option<usize>,
+    pub retirement_pending: Option<bool>,
+    pub remapped_rows_correctable: Option<u64>,
+    pub remapped_rows_uncorrectable: Option<u64>,
+    pub remap_pending: Option<bool>,
+    pub remap_failure: Option<bool>,
+}
+
+/// Overall health verdict for one GPU.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum HealthVerdict {
+    Healthy,
+    Warning,
+    Critical,
+    Unknown,
+}
+
+impl HealthVerdict {
+    /// Short badge label.
+    pub fn label(self) -> &'static str {
+        match self {
+            HealthVerdict::Healthy => "OK",
+            HealthVerdict::Warning => "WARN",
+            HealthVerdict::Critical => "FAIL",
+            HealthVerdict::Unknown => "N/A",
+        }
+    }
+}
+
+impl GpuHealth {
+    /// Whether the driver reported any of the health counters.
+    pub fn is_supported(&self) -> bool {
+        self.ecc_enabled.is_some()
+            || self.retirement_pending.is_some()
+            || self.remap_pending.is_some()
+    }
+
+    /// Summarize the counters into a single verdict.
+    ///
+    /// Uncorrectable errors since boot and failed row remaps mean the board
+    /// needs attention now; anything waiting on a reset or a history of
+    /// errors is a warning.
+    pub fn verdict(&self) -> HealthVerdict {
+        if !self.is_supported() {
+            return HealthVerdict::Unknown;
+        }
+
+        let nonzero = |v: Option<u64>| v.is_some_and(|v| v > 0);
+        let set = |v: Option<bool>| v.unwrap_or(false);
+
+        if set(self.remap_failure) || nonzero(self.volatile_uncorrected) {
+            HealthVerdict::Critical
+        } else if set(self.retirement_pending)
+            || set(self.remap_pending)
+            || nonzero(self.aggregate_uncorrected)
+            || nonzero(self.remapped_rows_uncorrectable)
+            || nonzero(self.volatile_corrected)
+            || self.retired_pages_dbe.is_some_and(|n| n > 0)
+        {
+            HealthVerdict::Warning
+        } else {
+            HealthVerdict::Healthy
+        }
+    }
+}
+
+/// Asynchronous event reported by the GPU driver.
+#[derive(Clone)]
+pub struct GpuEvent {
+    pub time: chrono::DateTime<chrono::Local>,
+    /// Index of the GPU when the event was reported, for display.
+    pub gpu_index: u32,
+    /// `GpuInfo::stable_id` of the GPU.
+    pub gpu_id: String,
+    pub kind: GpuEventKind,
+}
+
+/// What happened in a [`GpuEvent`].
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GpuEventKind {
+    /// Critical XID error; `None` when the driver did not report the code.
+    Xid(Option<u64>),
+    SingleBitEcc,
+    DoubleBitEcc,
+    PStateChange,
+    ClockChange,
+    PowerSourceChange,
+    /// The GPU stopped answering queries or disappeared.
+    DeviceLost,
+    /// A lost GPU answers queries again.
+    DeviceRecovered,
+}
+
+/// How urgently an event needs attention.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum EventSeverity {
+    Info,
+    Warning,
+    Critical,
+}
+
+/// XID "GPU has fallen off the bus".
+pub const XID_FALLEN_OFF_BUS: u64 = 79;
+
+impl GpuEventKind {
+    pub fn severity(self) -> EventSeverity {
+        match self {
+            // Fallen off the bus, DBE, NVLink, uncontained ECC, row remap failure, GSP errors
+            GpuEventKind::Xid(Some(48 | 64 | 74 | 79 | 95 | 119 | 120)) => EventSeverity::Critical,
+            GpuEventKind::Xid(_) | GpuEventKind::SingleBitEcc => EventSeverity::Warning,
+            GpuEventKind::DoubleBitEcc | GpuEventKind::DeviceLost => EventSeverity::Critical,
+            GpuEventKind::PStateChange
+            | GpuEventKind::ClockChange
+            | GpuEventKind::PowerSourceChange
+            | GpuEventKind::DeviceRecovered => EventSeverity::Info,
+        }
+    }
+
+    /// Short label, e.g. `XID 79`.
+    pub fn label(self) -> String {
+        match self {
+            GpuEventKind::Xid(Some(code)) => format!("XID {}", code),
+            GpuEventKind::Xid(None) => "XID ?".into(),
+            GpuEventKind::SingleBitEcc => "SBE".into(),
+            GpuEventKind::DoubleBitEcc => "DBE".into(),
+            GpuEventKind::PStateChange => "PSTATE".into(),
+            GpuEventKind::ClockChange => "CLOCK".into(),
+            GpuEventKind::PowerSourceChange => "POWER SRC".into(),
+            GpuEventKind::DeviceLost => "LOST".into(),
+            GpuEventKind::DeviceRecovered => "RECOVERED".into(),
+        }
+    }
+
+    /// Human-readable explanation.
+    pub fn description(self) -> &'static str {
+        match self {
+            GpuEventKind::Xid(Some(code)) => match code {
+                13 => "Graphics engine exception",
+                31 => "GPU memory page fault",
+                32 => "Invalid or corrupted push buffer stream",
+                43 => "GPU stopped processing",
+                45 => "Preemptive cleanup due to previous errors",
+                48 => "Double bit ECC error",
+                61 | 62 => "Internal micro-controller error",
+                63 => "ECC page retirement or row remapping recorded",
+                64 => "ECC page retirement or row remapping failure",
+                68 => "Video processor exception",
+                74 => "NVLink error",
+                79 => "GPU has fallen off the bus",
+                92 => "High single-bit ECC error rate",
+                94 => "Contained ECC error",
+                95 => "Uncontained ECC error",
+                119 | 120 => "GSP firmware error",
+                _ => "Critical XID error",
+            },
+            GpuEventKind::Xid(None) => "Critical XID error",
+            GpuEventKind::SingleBitEcc => "Single bit ECC error",
+            GpuEventKind::DoubleBitEcc => "Double bit ECC error",
+            GpuEventKind::PStateChange => "Performance state changed",
+            GpuEventKind::ClockChange => "Clocks changed",
+            GpuEventKind::PowerSourceChange => "Power source changed",
+            GpuEventKind::DeviceLost => "GPU stopped responding or disappeared",
+            GpuEventKind::DeviceRecovered => "GPU is responding again",
+        }
+    }
 }
 
 /// GPU process information.
@@ -88,6 +724,81 @@ pub struct GpuProcessInfo {
     pub sm_utilization: Option<u32>,
     pub command: String,
     pub process_type: String,
+    pub mig_instance: Option<MigInstanceId>,
+}
+
+/// A GPU process while it runs, summarized across refreshes so it can be
+/// reported once it is gone.
+#[derive(Clone)]
+pub struct TrackedGpuProcess {
+    pub name: String,
+    pub user: String,
+    pub command: String,
+    /// GPU indices, for display.
+    pub gpus: Vec<u32>,
+    /// `GpuInfo::stable_id` of the same GPUs, which indices may not keep.
+    pub gpu_ids: Vec<String>,
+    pub first_seen: chrono::DateTime<chrono::Local>,
+    pub last_seen: chrono::DateTime<chrono::Local>,
+    /// Highest GPU memory held at once, over all its GPUs.
+    pub peak_memory: u64,
+    pub sm_total: u64,
+    pub sm_samples: u32,
+}
+
+impl TrackedGpuProcess {
+    pub fn new(process: &GpuProcessInfo, now: chrono::DateTime<chrono::Local>) -> Self {
+        Self {
+            name: process.name.clone(),
+            user: process.user.clone(),
+            command: process.command.clone(),
+            gpus: Vec::new(),
+            gpu_ids: Vec::new(),
+            first_seen: now,
+            last_seen: now,
+            peak_memory: 0,
+            sm_total: 0,
+            sm_samples: 0,
+        }
+    }
+}
+
+/// Lifetime statistics NVML accounting mode keeps for a process.
+#[derive(Clone, Copy)]
+pub struct ProcessAccounting {
+    pub start: chrono::DateTime<chrono::Local>,
+    pub duration: std::time::Duration,
+    pub max_memory: Option<u64>,
+    /// Percent of the process's lifetime a kernel was running on the GPU.
+    pub gpu_utilization: Option<u32>,
+}
+
+/// A GPU process that has exited or released its GPUs.
+#[derive(Clone)]
+pub struct FinishedGpuProcess {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub command: String,
+    pub gpus: Vec<u32>,
+    pub start: chrono::DateTime<chrono::Local>,
+    pub end: chrono::DateTime<chrono::Local>,
+    pub peak_memory: u64,
+    pub avg_sm_utilization: Option<u32>,
+    /// Figures come from NVML accounting rather than our own samples.
+    pub accounted: bool,
+}
+
+/// A process holding GPU memory while the GPU does next to no work.
+#[derive(Clone)]
+pub struct IdleGpuProcess {
+    pub pid: u32,
+    pub name: String,
+    pub user: String,
+    pub command: String,
+    pub gpu_index: u32,
+    pub gpu_memory: u64,
+    pub idle_for: std::time::Duration,
 }
 
 /// Aggregated system metrics.
@@ -118,6 +829,80 @@ pub enum GpuBackend {
     None,
     Nvml,
     Metal,
+    /// amdgpu sysfs and DRM fdinfo (Linux).
+    Amd,
+    /// i915/xe sysfs and DRM fdinfo (Linux).
+    Intel,
+    /// DRM fdinfo of any other kernel driver (Linux).
+    Drm,
+}
+
+impl GpuBackend {
+    /// Short tag shown on GPU cards and in the header.
+    pub fn label(&self) -> &'static str {
+        match self {
+            GpuBackend::None => "GPU",
+            GpuBackend::Nvml => "NVIDIA",
+            GpuBackend::Metal => "Metal",
+            GpuBackend::Amd => "AMD",
+            GpuBackend::Intel => "Intel",
+            GpuBackend::Drm => "DRM",
+        }
+    }
+
+    /// Name of the compute API whose version the backend reports.
+    pub fn api_label(&self) -> &'static str {
+        match self {
+            GpuBackend::Nvml => "CUDA",
+            GpuBackend::Metal => "API",
+            GpuBackend::Amd => "ROCm",
+            GpuBackend::Intel | GpuBackend::Drm => "DRM",
+            GpuBackend::None => "GPU",
+        }
+    }
+}
+
+/// Driver and API versions of one backend.
+#[derive(Clone)]
+pub struct BackendInfo {
+    pub backend: GpuBackend,
+    pub driver_version: String,
+    pub api_version: String,
+}
+
+/// Last values of cumulative counters, such as engine busy time, so the
+/// collectors can turn them into rates between refreshes.
+#[derive(Default)]
+pub struct CounterDeltas {
+    last: HashMap<String, u64>,
+    current: HashMap<String, u64>,
+}
+
+#[cfg_attr(target_os = "macos", allow(dead_code))]
+impl CounterDeltas {
+    /// How much the counter `key` grew since the previous refresh, or `None`
+    /// the first time it is seen.
+    pub fn delta(&mut self, key: String, value: u64) -> Option<u64> {
+        let last = self.last.get(&key).copied();
+        self.current.insert(key, value);
+        last.map(|last| value.saturating_sub(last))
+    }
+
+    /// Like `delta`, for a counter that wraps back to zero past `range`. A
+    /// counter that went backwards without a known range gives `None`.
+    pub fn wrapping_delta(&mut self, key: String, value: u64, range: Option<u64>) -> Option<u64> {
+        let last = self.last.get(&key).copied();
+        self.current.insert(key, value);
+        match last? {
+            last if value >= last => Some(value - last),
+            last => range.map(|range| range.saturating_sub(last) + value),
+        }
+    }
+
+    /// End a refresh, forgetting counters that were not seen in it.
+    pub fn finish(&mut self) {
+        self.last = std::mem::take(&mut self.current);
+    }
 }
 
 /// Aggregated GPU metrics.
@@ -125,19 +910,217 @@ pub enum GpuBackend {
 pub struct GpuMetrics {
     pub gpus: Vec<GpuInfo>,
     pub processes: Vec<GpuProcessInfo>,
-    pub driver_version: String,
-    pub api_version: String,
-    pub backend: GpuBackend,
+    /// Path between each pair of GPUs, indexed by position in `gpus`;
+    /// `None` on the diagonal and where the driver cannot tell.
+    pub topology: Vec<Vec<Option<TopologyPath>>>,
+    /// Every backend that found GPUs, NVML first.
+    pub drivers: Vec<BackendInfo>,
+}
+
+impl GpuMetrics {
+    /// Whether every GPU comes from Metal, which tracks no processes.
+    pub fn is_metal(&self) -> bool {
+        !self.drivers.is_empty() && self.drivers.iter().all(|d| d.backend == GpuBackend::Metal)
+    }
+
+    /// Append the GPUs of another backend, renumbered to follow ours.
+    #[cfg_attr(not(target_os = "linux"), allow(dead_code))]
+    pub fn merge(&mut self, other: GpuMetrics) {
+        let offset = self.gpus.iter().map(|g| g.index + 1).max().unwrap_or(0);
+        let before = self.gpus.len();
+
+        self.gpus.extend(other.gpus.into_iter().map(|mut gpu| {
+            gpu.index += offset;
+            gpu
+        }));
+        self.processes
+            .extend(other.processes.into_iter().map(|mut process| {
+                process.gpu_index += offset;
+                process
+            }));
+
+        // Backends know nothing of each other's GPUs, so no paths between them
+        let total = self.gpus.len();
+        for row in &mut self.topology {
+            row.resize(total, None);
+        }
+        self.topology.extend(other.topology.into_iter().map(|row| {
+            let mut padded = vec![None; before];
+            padded.extend(row);
+            padded.resize(total, None);
+            padded
+        }));
+        self.drivers.extend(other.drivers);
+    }
+
+    /// Keep only the GPUs with these indices, in the given order, along with
+    /// their processes and topology.
+    pub fn select(&mut self, order: &[u32]) {
+        let positions: Vec<usize> = order
+            .iter()
+            .filter_map(|&index| self.gpus.iter().position(|g| g.index == index))
+            .collect();
+
+        self.topology = positions
+            .iter()
+            .map(|&i| {
+                positions
+                    .iter()
+                    .map(|&j| {
+                        self.topology
+                            .get(i)
+                            .and_then(|row| row.get(j))
+                            .copied()
+                            .flatten()
+                    })
+                    .collect()
+            })
+            .collect();
+        self.gpus = positions.iter().map(|&i| self.gpus[i].clone()).collect();
+        self.processes.retain(|p| order.contains(&p.gpu_index));
+    }
+}
+
+/// One entry of a GPU selection list such as `0,GPU-3f2a,0000:3b:00.0`.
+#[derive(Clone, PartialEq, Eq)]
+pub enum GpuSelector {
+    Index(u32),
+    /// Full UUID or a unique prefix of it, lowercase.
+    Uuid(String),
+    /// PCI bus ID normalized to `dddd:bb:dd.f`.
+    BusId(String),
+}
+
+impl GpuSelector {
+    /// Parse a comma-separated list, skipping empty entries.
+    pub fn parse_list(list: &str) -> Vec<GpuSelector> {
+        list.split(',')
+            .map(str::trim)
+            .filter(|s| !s.is_empty())
+            .map(|s| {
+                if let Ok(index) = s.parse() {
+                    GpuSelector::Index(index)
+                } else if s.contains(':') {
+                    GpuSelector::BusId(normalize_bus_id(s))
+                } else {
+                    GpuSelector::Uuid(s.to_lowercase())
+                }
+            })
+            .collect()
+    }
+
+    /// Whether this entry picks the given GPU.
+    pub fn matches(&self, gpu: &GpuInfo) -> bool {
+        match self {
+            GpuSelector::Index(index) => gpu.index == *index,
+            GpuSelector::Uuid(prefix) => gpu.uuid.as_ref().is_some_and(|uuid| {
+                let uuid = uuid.to_lowercase();
+                // Accept UUIDs written without the `GPU-` prefix too
+                uuid.starts_with(prefix.as_str())
+                    || uuid
+                        .strip_prefix("gpu-")
+                        .is_some_and(|u| u.starts_with(prefix.as_str()))
+            }),
+            GpuSelector::BusId(bus_id) => gpu
+                .pcie
+                .bus_id
+                .as_deref()
+                .is_some_and(|id| normalize_bus_id(id) == *bus_id),
+        }
+    }
+
+    /// The entry as the user wrote it, give or take case and zero padding.
+    pub fn label(&self) -> String {
+        match self {
+            GpuSelector::Index(index) => index.to_string(),
+            GpuSelector::Uuid(uuid) => uuid.clone(),
+            GpuSelector::BusId(bus_id) => bus_id.clone(),
+        }
+    }
+}
+
+/// Normalize a PCI bus ID so `3B:00.0`, `0000:3b:00.0` and NVML's
+/// `00000000:3B:00.0` compare equal.
+fn normalize_bus_id(id: &str) -> String {
+    let id = id.trim().to_lowercase();
+    let (domain, rest) = match id.split_once(':') {
+        Some((domain, rest)) if rest.contains(':') => (domain, rest),
+        _ => ("0", id.as_str()),
+    };
+    let domain = u32::from_str_radix(domain, 16).unwrap_or(0);
+    format!("{:04x}:{}", domain, rest)
+}
+
+/// Energy used by each GPU over a user-marked span of time.
+#[derive(Clone)]
+pub struct EnergyWindow {
+    pub name: String,
+    pub started: chrono::DateTime<chrono::Local>,
+    pub ended: Option<chrono::DateTime<chrono::Local>>,
+    /// Joules consumed per GPU, by `GpuInfo::stable_id`, while the window
+    /// was open.
+    pub joules: HashMap<String, f64>,
+}
+
+impl EnergyWindow {
+    pub fn new(name: String) -> Self {
+        Self {
+            name,
+            started: chrono::Local::now(),
+            ended: None,
+            joules: HashMap::new(),
+        }
+    }
+
+    pub fn is_open(&self) -> bool {
+        self.ended.is_none()
+    }
+
+    /// Joules consumed by all GPUs together.
+    pub fn total(&self) -> f64 {
+        self.joules.values().sum()
+    }
 }
 
 /// Historical data for graphs.
 pub struct HistoryData {
     pub cpu_history: Vec<f64>,
     pub memory_history: Vec<f64>,
-    pub gpu_util_history: Vec<Vec<f64>>,
-    pub gpu_mem_history: Vec<Vec<f64>>,
+    // Per-GPU series, keyed by `GpuInfo::stable_id`; NaN marks a sample
+    // without a reading
+    pub gpu_util_history: HashMap<String, Vec<f64>>,
+    pub gpu_mem_history: HashMap<String, Vec<f64>>,
+    // Power draw as a percentage of the power limit
+    pub gpu_power_history: HashMap<String, Vec<f64>>,
+    pub gpu_temp_history: HashMap<String, Vec<f64>>,
+    pub gpu_sm_clock_history: HashMap<String, Vec<f64>>,
+    // PCIe throughput in bytes per second
+    pub gpu_pcie_rx_history: HashMap<String, Vec<f64>>,
+    pub gpu_pcie_tx_history: HashMap<String, Vec<f64>>,
     pub network_rx_history: Vec<f64>,
     pub network_tx_history: Vec<f64>,
+    pub process_history: HashMap<u32, ProcessHistory>,
+}
+
+/// Number of samples kept per process.
+pub const PROCESS_HISTORY_LEN: usize = 60;
+
+/// Rolling history for a single process.
+#[derive(Clone, Default)]
+pub struct ProcessHistory {
+    pub cpu: Vec<f64>,
+    pub rss: Vec<f64>,
+    pub gpu_memory: Vec<f64>,
+}
+
+impl ProcessHistory {
+    /// Append a sample, dropping the oldest once the buffer is full.
+    fn push(buffer: &mut Vec<f64>, value: f64) {
+        if buffer.len() >= PROCESS_HISTORY_LEN {
+            buffer.remove(0);
+        }
+        buffer.push(value);
+    }
 }
 
 impl Default for HistoryData {
@@ -152,10 +1135,16 @@ impl HistoryData {
         Self {
             cpu_history: vec![0.0; 60],
             memory_history: vec![0.0; 60],
-            gpu_util_history: Vec::new(),
-            gpu_mem_history: Vec::new(),
+            gpu_util_history: HashMap::new(),
+            gpu_mem_history: HashMap::new(),
+            gpu_power_history: HashMap::new(),
+            gpu_temp_history: HashMap::new(),
+            gpu_sm_clock_history: HashMap::new(),
+            gpu_pcie_rx_history: HashMap::new(),
+            gpu_pcie_tx_history: HashMap::new(),
             network_rx_history: vec![0.0; 60],
             network_tx_history: vec![0.0; 60],
+            process_history: HashMap::new(),
         }
     }
 
@@ -171,22 +1160,49 @@ impl HistoryData {
         self.memory_history.push(value);
     }
 
+    /// Append a value to one GPU's buffer in a per-GPU series.
+    fn push_gpu_series(series: &mut HashMap<String, Vec<f64>>, gpu_id: &str, value: f64) {
+        let buffer = series
+            .entry(gpu_id.to_string())
+            .or_insert_with(|| vec![0.0; 60]);
+        buffer.remove(0);
+        buffer.push(value);
+    }
+
     /// Push a GPU utilization value for a specific GPU.
-    pub fn push_gpu_util(&mut self, gpu_idx: usize, value: f64) {
-        while self.gpu_util_history.len() <= gpu_idx {
-            self.gpu_util_history.push(vec![0.0; 60]);
-        }
-        self.gpu_util_history[gpu_idx].remove(0);
-        self.gpu_util_history[gpu_idx].push(value);
+    pub fn push_gpu_util(&mut self, gpu_id: &str, value: f64) {
+        Self::push_gpu_series(&mut self.gpu_util_history, gpu_id, value);
     }
 
     /// Push a GPU memory usage value for a specific GPU.
-    pub fn push_gpu_mem(&mut self, gpu_idx: usize, value: f64) {
-        while self.gpu_mem_history.len() <= gpu_idx {
-            self.gpu_mem_history.push(vec![0.0; 60]);
+    pub fn push_gpu_mem(&mut self, gpu_id: &str, value: f64) {
+        Self::push_gpu_series(&mut self.gpu_mem_history, gpu_id, value);
+    }
+
+    /// Push power (percent of limit), temperature and SM clock for a GPU.
+    pub fn push_gpu_sensors(&mut self, gpu_id: &str, power_pct: f64, temp: f64, sm_clock: f64) {
+        Self::push_gpu_series(&mut self.gpu_power_history, gpu_id, power_pct);
+        Self::push_gpu_series(&mut self.gpu_temp_history, gpu_id, temp);
+        Self::push_gpu_series(&mut self.gpu_sm_clock_history, gpu_id, sm_clock);
+    }
+
+    /// Push PCIe throughput in bytes per second for a GPU.
+    pub fn push_gpu_pcie(&mut self, gpu_id: &str, rx: f64, tx: f64) {
+        Self::push_gpu_series(&mut self.gpu_pcie_rx_history, gpu_id, rx);
+        Self::push_gpu_series(&mut self.gpu_pcie_tx_history, gpu_id, tx);
+    }
+
+    /// Per-GPU history buffers for a graph metric, keyed by `GpuInfo::stable_id`.
+    pub fn gpu_series(&self, metric: GpuGraphMetric) -> &HashMap<String, Vec<f64>> {
+        match metric {
+            GpuGraphMetric::Utilization => &self.gpu_util_history,
+            GpuGraphMetric::Memory => &self.gpu_mem_history,
+            GpuGraphMetric::Power => &self.gpu_power_history,
+            GpuGraphMetric::Temperature => &self.gpu_temp_history,
+            GpuGraphMetric::SmClock => &self.gpu_sm_clock_history,
+            GpuGraphMetric::PcieRx => &self.gpu_pcie_rx_history,
+            GpuGraphMetric::PcieTx => &self.gpu_pcie_tx_history,
         }
-        self.gpu_mem_history[gpu_idx].remove(0);
-        self.gpu_mem_history[gpu_idx].push(value);
     }
 
     /// Push network throughput values.
@@ -196,6 +1212,62 @@ impl HistoryData {
         self.network_tx_history.remove(0);
         self.network_tx_history.push(tx);
     }
+
+    /// Push CPU, RSS and GPU memory values for a process.
+    pub fn push_process(&mut self, pid: u32, cpu: f64, rss: u64, gpu_memory: u64) {
+        let history = self.process_history.entry(pid).or_default();
+        ProcessHistory::push(&mut history.cpu, cpu);
+        ProcessHistory::push(&mut history.rss, rss as f64);
+        ProcessHistory::push(&mut history.gpu_memory, gpu_memory as f64);
+    }
+
+    /// Drop the history of processes that are no longer running.
+    pub fn retain_processes(&mut self, alive: &HashSet<u32>) {
+        self.process_history.retain(|pid, _| alive.contains(pid));
+    }
+}
+
+/// Metric plotted in the GPU history graph.
+#[derive(Clone, Copy, PartialEq, Eq)]
+pub enum GpuGraphMetric {
+    Utilization,
+    Memory,
+    Power,
+    Temperature,
+    SmClock,
+    PcieRx,
+    PcieTx,
+}
+
+impl GpuGraphMetric {
+    const ALL: [GpuGraphMetric; 7] = [
+        GpuGraphMetric::Utilization,
+        GpuGraphMetric::Memory,
+        GpuGraphMetric::Power,
+        GpuGraphMetric::Temperature,
+        GpuGraphMetric::SmClock,
+        GpuGraphMetric::PcieRx,
+        GpuGraphMetric::PcieTx,
+    ];
+
+    /// The metric shown after this one.
+    pub fn next(self) -> Self {
+        let pos = Self::ALL.iter().position(|&m| m == self).unwrap_or(0);
+        Self::ALL[(pos + 1) % Self::ALL.len()]
+    }
+
+    /// Graph title.
+    pub fn label(self) -> &'static str {
+        match self {
+            GpuGraphMetric::Utilization => "Utilization %",
+            GpuGraphMetric::Memory => "Memory %",
+            GpuGraphMetric::Power => "Power % of limit",
+            GpuGraphMetric::Temperature => "Temperature °C",
+            GpuGraphMetric::SmClock => "SM Clock MHz",
+            GpuGraphMetric::PcieRx => "PCIe RX",
+            GpuGraphMetric::PcieTx => "PCIe TX",
+        }
+    }
 }
 
 /// Sort column for process tables.
@@ -223,3 +1295,121 @@ pub struct KillConfirmation {
     pub name: String,
     pub signal: sysinfo::Signal,
 }
+
+/// Device setting confirmation dialog state.
+#[derive(Clone)]
+pub struct SettingConfirmation {
+    pub gpu_index: u32,
+    /// UUID of the GPU the change was confirmed for; its index may shift
+    /// before the change is applied.
+    pub gpu_uuid: String,
+    pub gpu_name: String,
+    pub change: GpuSettingChange,
+}
+
+/// Settings panel state. Edited values are only applied after confirmation.
+#[derive(Clone, Copy)]
+pub struct SettingsPanel {
+    pub gpu_index: u32,
+    pub cursor: usize,
+    /// Power limit in watts
+    pub power_limit: u32,
+    pub compute_mode: ComputeMode,
+    /// Locked SM clock in MHz, `None` when unlocked
+    pub clock_lock: Option<u32>,
+}
+
+impl SettingsPanel {
+    /// Number of editable rows (power limit, compute mode, clock lock).
+    pub const ROWS: usize = 3;
+
+    /// The change the row under the cursor would make.
+    pub fn change(&self) -> GpuSettingChange {
+        match self.cursor {
+            0 => GpuSettingChange::PowerLimit(self.power_limit),
+            1 => GpuSettingChange::ComputeMode(self.compute_mode),
+            _ => GpuSettingChange::LockClocks(self.clock_lock),
+        }
+    }
+}
+
+/// A process being followed across refreshes.
+#[derive(Clone)]
+pub struct FollowedProcess {
+    pub pid: u32,
+    pub name: String,
+    pub panel: ActivePanel,
+}
+
+/// Column chooser dialog state.
+#[derive(Clone, Copy)]
+pub struct ColumnChooser {
+    pub panel: ActivePanel,
+    pub cursor: usize,
+}
+
+/// Actions offered by the process context menu.
+#[derive(Clone, Copy, PartialEq)]
+pub enum ProcessAction {
+    Details,
+    Follow,
+    Terminate,
+    Kill,
+    Interrupt,
+}
+
+impl ProcessAction {
+    /// Menu entries in display order.
+    pub const ALL: [ProcessAction; 5] = [
+        ProcessAction::Details,
+        ProcessAction::Follow,
+        ProcessAction::Terminate,
+        ProcessAction::Kill,
+        ProcessAction::Interrupt,
+    ];
+
+    /// Menu label.
+    pub fn label(self) -> &'static str {
+        match self {
+            ProcessAction::Details => "Details",
+            ProcessAction::Follow => "Follow / unfollow",
+            ProcessAction::Terminate => "Terminate (SIGTERM)",
+            ProcessAction::Kill => "Kill (SIGKILL)",
+            ProcessAction::Interrupt => "Interrupt (SIGINT)",
+        }
+    }
+}
+
+/// Process context menu state, opened by right-clicking a row.
+#[derive(Clone, Copy)]
+pub struct ActionMenu {
+    pub x: u16,
+    pub y: u16,
+    pub cursor: usize,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn only_lost_nvml_gpus_force_reinit() {
+        let with = |backend, status| GpuInfo {
+            backend,
+            status,
+            ..Default::default()
+        };
+        let failed = |fault| GpuStatus::Failed {
+            fault,
+            code: String::new(),
+        };
+        assert!(with(GpuBackend::Nvml, failed(GpuFault::Lost)).needs_nvml_reinit());
+        assert!(with(GpuBackend::Nvml, failed(GpuFault::Unknown)).needs_nvml_reinit());
+        // Only a new NVML instance enumerates a GPU that comes back
+        assert!(with(GpuBackend::Nvml, failed(GpuFault::NotFound)).needs_nvml_reinit());
+        // Failing for a reason re-init cannot fix
+        assert!(!with(GpuBackend::Nvml, failed(GpuFault::Other)).needs_nvml_reinit());
+        assert!(!with(GpuBackend::Amd, failed(GpuFault::Unknown)).needs_nvml_reinit());
+        assert!(!with(GpuBackend::Nvml, GpuStatus::Ok).needs_nvml_reinit());
+    }
+}
@@ -0,0 +1,177 @@
+//! Process detail view with per-process history graphs.
+
+use humansize::{format_size, BINARY};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    symbols,
+    text::{Line, Span},
+    widgets::{Axis, Block, Borders, Chart, Clear, Dataset, GraphType, Paragraph},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::utils::truncate_string;
+
+/// Render the detail popup for the process selected with Enter.
+pub fn render_process_detail(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(pid) = app.detail_pid else {
+        return;
+    };
+
+    let popup = centered_rect(80, 80, area);
+    frame.render_widget(Clear, popup);
+
+    let (name, user, command) =
+        if let Some(p) = app.system_metrics.processes.iter().find(|p| p.pid == pid) {
+            (p.name.clone(), p.user.clone(), p.command.clone())
+        } else if let Some(p) = app
+            .gpu_metrics
+            .as_ref()
+            .and_then(|m| m.processes.iter().find(|p| p.pid == pid))
+        {
+            (p.name.clone(), p.user.clone(), p.command.clone())
+        } else {
+            ("?".into(), "?".into(), "?".into())
+        };
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Process {} ({}) - press any key to close",
+            pid, name
+        ))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let Some(history) = app.history.process_history.get(&pid) else {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "Process has exited",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+            Constraint::Ratio(1, 3),
+        ])
+        .split(inner);
+
+    let last = |values: &[f64]| values.last().copied().unwrap_or(0.0);
+    let first = |values: &[f64]| values.first().copied().unwrap_or(0.0);
+
+    // GPU memory growth across the whole window, the main hint of a leak
+    let gpu_growth = last(&history.gpu_memory) - first(&history.gpu_memory);
+    let growth_color = if gpu_growth > 0.0 {
+        Color::Yellow
+    } else {
+        Color::Green
+    };
+    let growth_sign = if gpu_growth < 0.0 { "-" } else { "+" };
+
+    let summary = vec![
+        Line::from(vec![
+            Span::styled("User: ", Style::default().fg(Color::Yellow)),
+            Span::styled(user, Style::default().fg(Color::Cyan)),
+            Span::raw("  "),
+            Span::styled("Command: ", Style::default().fg(Color::Yellow)),
+            Span::raw(truncate_string(
+                &command,
+                (inner.width as usize).saturating_sub(30),
+            )),
+        ]),
+        Line::from(vec![
+            Span::styled("CPU: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!("{:.1}%  ", last(&history.cpu))),
+            Span::styled("RSS: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format!(
+                "{}  ",
+                format_size(last(&history.rss) as u64, BINARY)
+            )),
+            Span::styled("GPU MEM: ", Style::default().fg(Color::Yellow)),
+            Span::raw(format_size(last(&history.gpu_memory) as u64, BINARY)),
+        ]),
+        Line::from(vec![
+            Span::styled("GPU MEM trend: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!(
+                    "{}{} over {} samples",
+                    growth_sign,
+                    format_size(gpu_growth.abs() as u64, BINARY),
+                    history.gpu_memory.len()
+                ),
+                Style::default()
+                    .fg(growth_color)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        ]),
+    ];
+    frame.render_widget(Paragraph::new(summary), chunks[0]);
+
+    render_history_chart(frame, chunks[1], "CPU %", &history.cpu, Color::Cyan, |v| {
+        format!("{:.0}%", v)
+    });
+    render_history_chart(frame, chunks[2], "RSS", &history.rss, Color::Green, |v| {
+        format_size(v as u64, BINARY)
+    });
+    render_history_chart(
+        frame,
+        chunks[3],
+        "GPU Memory",
+        &history.gpu_memory,
+        Color::Magenta,
+        |v| format_size(v as u64, BINARY),
+    );
+}
+
+/// Render one auto-scaled history chart.
+fn render_history_chart(
+    frame: &mut Frame,
+    area: Rect,
+    title: &str,
+    values: &[f64],
+    color: Color,
+    format_label: impl Fn(f64) -> String,
+) {
+    let data: Vec<(f64, f64)> = values
+        .iter()
+        .enumerate()
+        .map(|(i, &v)| (i as f64, v))
+        .collect();
+
+    let max = values.iter().cloned().fold(0.0, f64::max).max(1.0);
+
+    let datasets = vec![Dataset::default()
+        .marker(symbols::Marker::Braille)
+        .graph_type(GraphType::Line)
+        .style(Style::default().fg(color))
+        .data(&data)];
+
+    let chart = Chart::new(datasets)
+        .block(Block::default().borders(Borders::ALL).title(title))
+        .x_axis(
+            Axis::default()
+                .bounds([0.0, (values.len().max(2) - 1) as f64])
+                .labels::<Vec<Line>>(vec![]),
+        )
+        .y_axis(
+            Axis::default()
+                .style(Style::default().fg(Color::Gray))
+                .bounds([0.0, max])
+                .labels(vec![
+                    Line::from("0"),
+                    Line::from(format_label(max / 2.0)),
+                    Line::from(format_label(max)),
+                ]),
+        );
+
+    frame.render_widget(chart, area);
+}
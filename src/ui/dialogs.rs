@@ -1,4 +1,4 @@
-//! Dialog rendering (help, kill confirmation, status).
+//! Dialog rendering (help, kill confirmation, column and GPU choosers, context menu, status).
 
 use ratatui::{
     layout::Rect,
@@ -11,6 +11,8 @@ use sysinfo::Signal;
 
 use super::layout::centered_rect;
 use crate::app::App;
+use crate::columns::{Column, ColumnLayout};
+use crate::types::{ActivePanel, ProcessAction};
 
 /// Render the status message bar.
 pub fn render_status(frame: &mut Frame, area: Rect, app: &App) {
@@ -95,6 +97,182 @@ pub fn render_kill_confirm(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(paragraph, confirm_area);
 }
 
+/// Render the column chooser for the table it was opened on.
+pub fn render_column_chooser(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(chooser) = app.column_chooser else {
+        return;
+    };
+
+    let (title, mut lines) = match chooser.panel {
+        ActivePanel::CpuProcesses => (
+            "CPU Process Columns",
+            column_chooser_lines(&app.config.cpu_columns, chooser.cursor),
+        ),
+        ActivePanel::GpuProcesses => (
+            "GPU Process Columns",
+            column_chooser_lines(&app.config.gpu_columns, chooser.cursor),
+        ),
+    };
+
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Space:Show/hide  Shift-↑↓/J/K:Move  ←/→:Width  d:Defaults  Esc:Save",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(title)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    let chooser_area = centered_rect(50, 70, area);
+
+    frame.render_widget(Clear, chooser_area);
+    frame.render_widget(paragraph, chooser_area);
+}
+
+/// One line per column with its visibility and width.
+fn column_chooser_lines<C: Column>(layout: &ColumnLayout<C>, cursor: usize) -> Vec<Line<'static>> {
+    layout
+        .columns
+        .iter()
+        .enumerate()
+        .map(|(i, setting)| {
+            let style = if i == cursor {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let check = if setting.visible { "[x]" } else { "[ ]" };
+            Line::from(Span::styled(
+                format!(
+                    " {} {:<10} {:>3}{}",
+                    check,
+                    setting.column.header(),
+                    setting.width,
+                    if setting.column.flexible() { "+" } else { "" }
+                ),
+                style,
+            ))
+        })
+        .collect()
+}
+
+/// Render the GPU chooser, listing every GPU with its display position.
+pub fn render_gpu_chooser(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(cursor) = app.gpu_chooser else {
+        return;
+    };
+
+    let mut lines: Vec<Line> = app
+        .all_gpus()
+        .iter()
+        .enumerate()
+        .map(|(i, gpu)| {
+            let style = if i == cursor {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let position = match &app.gpu_selection {
+                Some(order) => order.iter().position(|id| *id == gpu.stable_id()),
+                None => Some(i),
+            };
+            let check = match position {
+                Some(pos) => format!("[{}]", pos + 1),
+                None => "[ ]".to_string(),
+            };
+            Line::from(Span::styled(
+                format!(
+                    " {:<4} GPU {} - {} {}",
+                    check,
+                    gpu.index,
+                    gpu.name,
+                    gpu.pcie.bus_id.as_deref().unwrap_or("")
+                ),
+                style,
+            ))
+        })
+        .collect();
+
+    if lines.is_empty() {
+        lines.push(Line::from(Span::styled(
+            " No GPU detected",
+            Style::default().fg(Color::DarkGray),
+        )));
+    }
+    lines.push(Line::from(""));
+    lines.push(Line::from(Span::styled(
+        "Space:Show/hide  a:Show all  Esc:Close",
+        Style::default().fg(Color::DarkGray),
+    )));
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Select GPUs")
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let paragraph = Paragraph::new(lines)
+        .block(block)
+        .wrap(Wrap { trim: false });
+
+    let chooser_area = centered_rect(50, 50, area);
+
+    frame.render_widget(Clear, chooser_area);
+    frame.render_widget(paragraph, chooser_area);
+}
+
+/// Render the process context menu next to where it was opened.
+pub fn render_action_menu(frame: &mut Frame, area: Rect, app: &mut App) {
+    let Some(menu) = app.action_menu else {
+        return;
+    };
+
+    let width = ProcessAction::ALL
+        .iter()
+        .map(|a| a.label().len() as u16)
+        .max()
+        .unwrap_or(0)
+        + 4;
+    let height = ProcessAction::ALL.len() as u16 + 2;
+
+    // Open below the click, shifted back inside the screen if needed
+    let x = menu.x.min(area.width.saturating_sub(width));
+    let y = (menu.y + 1).min(area.height.saturating_sub(height));
+    let menu_area = Rect::new(x, y, width.min(area.width), height.min(area.height));
+    app.action_menu_area = Some(menu_area);
+
+    let lines: Vec<Line> = ProcessAction::ALL
+        .iter()
+        .enumerate()
+        .map(|(i, action)| {
+            let style = if i == menu.cursor {
+                Style::default()
+                    .bg(Color::DarkGray)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            Line::from(Span::styled(format!(" {} ", action.label()), style))
+        })
+        .collect();
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    frame.render_widget(Clear, menu_area);
+    frame.render_widget(Paragraph::new(lines).block(block), menu_area);
+}
+
 /// Render the help dialog.
 pub fn render_help(frame: &mut Frame, area: Rect) {
     let help_text = vec![
@@ -117,7 +295,13 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
         Line::from("  k/↑          Move selection up"),
         Line::from("  PgDn/PgUp    Move selection by page"),
         Line::from("  Home/End     Jump to first/last item"),
+        Line::from("  ←/→          Scroll command column"),
+        Line::from("  w            Wrap selected command"),
+        Line::from("  Enter        Show process details and history"),
+        Line::from("  f            Follow selected process across refreshes"),
         Line::from("  Mouse        Click to select, scroll to navigate"),
+        Line::from("               Click a header to sort, a GPU card to filter"),
+        Line::from("               Double-click for details, right-click for actions"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "Process Control:",
@@ -134,7 +318,7 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
         Line::from("  1            Sort by PID"),
         Line::from("  2            Sort by Name"),
         Line::from("  3            Sort by User"),
-        Line::from("  4            Sort by CPU%"),
+        Line::from("  4            Sort by CPU% (SM% in the GPU table)"),
         Line::from("  5            Sort by Memory%"),
         Line::from("  6            Sort by GPU Memory"),
         Line::from("  r            Reverse sort order"),
@@ -145,7 +329,18 @@ pub fn render_help(frame: &mut Frame, area: Rect) {
         )]),
         Line::from("  a            Toggle show all processes"),
         Line::from("  g            Toggle graphs"),
+        Line::from("  G            Cycle GPU graph metric (util, mem, power, temp, clock, PCIe)"),
         Line::from("  c            Toggle compact mode"),
+        Line::from("  o            Choose process table columns"),
+        Line::from("  H            Show GPU memory health (ECC, retired pages)"),
+        Line::from("  e            Show GPU driver events (XID errors, ECC, clocks)"),
+        Line::from("  t            Show GPU topology (NVLink, PCIe, NUMA)"),
+        Line::from("  i            Show GPU identity (UUID, serial, VBIOS, bus ID)"),
+        Line::from("  h            Show finished GPU processes (peak memory, avg SM)"),
+        Line::from("  W            Show processes holding memory on idle GPUs"),
+        Line::from("  E            Start/stop an energy accounting window"),
+        Line::from("  S            GPU settings (power limit, compute mode, clocks)"),
+        Line::from("  F            Choose which GPUs to show"),
         Line::from("  +/-          Adjust refresh rate"),
         Line::from(""),
         Line::from(vec![Span::styled(
@@ -0,0 +1,99 @@
+//! GPU driver event log (XID errors, ECC errors, clock and power changes).
+
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::types::EventSeverity;
+
+/// Color used for an event of the given severity.
+pub fn severity_style(severity: EventSeverity) -> Style {
+    match severity {
+        EventSeverity::Info => Style::default().fg(Color::DarkGray),
+        EventSeverity::Warning => Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+        EventSeverity::Critical => Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+    }
+}
+
+/// Render the scrollable events popup opened with e.
+pub fn render_events_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(80, 70, area);
+    frame.render_widget(Clear, popup);
+
+    let events = app.visible_gpu_events();
+    let scope = match app.gpu_filter_index() {
+        Some(gpu) => format!(" [GPU {}]", gpu),
+        None => String::new(),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "GPU Events{} ({}) - j/k scroll, Esc to close",
+            scope,
+            events.len()
+        ))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    if events.is_empty() {
+        let message = if app.gpu_handle.events.is_some() {
+            "No events since startup"
+        } else {
+            "Driver events are not available on this system"
+        };
+        let text = Paragraph::new(Line::from(Span::styled(
+            message,
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    let header = Row::new(vec!["TIME", "GPU", "EVENT", "DESCRIPTION"]).style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows: Vec<Row> = events
+        .iter()
+        .map(|event| {
+            let style = severity_style(event.kind.severity());
+            let gpu = if event.gpu_index == u32::MAX {
+                "?".to_string()
+            } else {
+                event.gpu_index.to_string()
+            };
+            Row::new(vec![
+                Cell::from(event.time.format("%Y-%m-%d %H:%M:%S").to_string()),
+                Cell::from(gpu),
+                Cell::from(event.kind.label()).style(style),
+                Cell::from(event.kind.description()).style(style),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(19),
+            Constraint::Length(3),
+            Constraint::Length(9),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = TableState::default().with_selected(Some(app.events_scroll));
+    frame.render_stateful_widget(table, inner, &mut state);
+}
@@ -0,0 +1,102 @@
+//! History of GPU processes that have finished.
+
+use humansize::{format_size, BINARY};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, TableState},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::utils::{format_short_duration, usage_color};
+
+/// Render the scrollable finished-process popup opened with h.
+pub fn render_finished_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(90, 70, area);
+    frame.render_widget(Clear, popup);
+
+    let finished = &app.finished_gpu_processes;
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Finished GPU Processes ({}) - j/k scroll, Esc to close",
+            finished.len()
+        ))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    if finished.is_empty() {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No GPU process has finished since startup",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    let header = Row::new(vec![
+        "PID", "USER", "GPU", "STARTED", "ENDED", "DURATION", "PEAK MEM", "AVG SM", "SOURCE",
+        "COMMAND",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows: Vec<Row> = finished
+        .iter()
+        .map(|p| {
+            let gpus: Vec<String> = p.gpus.iter().map(|g| g.to_string()).collect();
+            let duration = (p.end - p.start).num_seconds().max(0) as u64;
+            let sm = match p.avg_sm_utilization {
+                Some(sm) => Cell::from(format!("{}%", sm))
+                    .style(Style::default().fg(usage_color(sm as f64))),
+                None => Cell::from("N/A").style(Style::default().fg(Color::DarkGray)),
+            };
+            let command = if p.command.is_empty() {
+                &p.name
+            } else {
+                &p.command
+            };
+            Row::new(vec![
+                Cell::from(p.pid.to_string()),
+                Cell::from(p.user.clone()),
+                Cell::from(gpus.join(",")),
+                Cell::from(p.start.format("%m-%d %H:%M:%S").to_string()),
+                Cell::from(p.end.format("%m-%d %H:%M:%S").to_string()),
+                Cell::from(format_short_duration(duration)),
+                Cell::from(format_size(p.peak_memory, BINARY)),
+                sm,
+                Cell::from(if p.accounted { "NVML" } else { "sampled" })
+                    .style(Style::default().fg(Color::DarkGray)),
+                Cell::from(command.clone()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(10),
+            Constraint::Length(5),
+            Constraint::Length(14),
+            Constraint::Length(14),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Length(6),
+            Constraint::Length(7),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header)
+    .row_highlight_style(Style::default().add_modifier(Modifier::REVERSED));
+
+    let mut state = TableState::default().with_selected(Some(app.finished_scroll));
+    frame.render_stateful_widget(table, inner, &mut state);
+}
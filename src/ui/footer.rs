@@ -41,6 +41,13 @@ pub fn render_footer(frame: &mut Frame, area: Rect, app: &App) {
                 .add_modifier(Modifier::BOLD),
         ),
         Span::raw(":Sort "),
+        Span::styled(
+            "f",
+            Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        ),
+        Span::raw(":Follow "),
         Span::styled(
             "a",
             Style::default()
@@ -1,5 +1,7 @@
 //! GPU panel rendering.
 
+use std::time::Duration;
+
 use humansize::{format_size, BINARY};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -10,21 +12,24 @@ use ratatui::{
 };
 
 use super::graphs::render_gpu_graphs;
+use super::health::verdict_style;
 use super::processes::render_gpu_processes;
 use crate::app::App;
-use crate::types::{GpuBackend, GpuInfo};
-use crate::utils::{create_bar, temp_color, usage_color};
+use crate::types::{
+    GpuBackend, GpuInfo, HealthVerdict, MigInstance, Reading, TempThresholds, ThrottleReason,
+};
+use crate::utils::{create_bar, format_energy, format_short_duration, temp_color, usage_color};
 
 /// Render the GPU panel (or no-GPU message if no GPU available).
 pub fn render_gpu_panel(frame: &mut Frame, area: Rect, app: &mut App) {
     let Some(ref gpu_metrics) = app.gpu_metrics else {
+        app.gpu_card_areas.clear();
         render_no_gpu_panel(frame, area);
         return;
     };
 
     let height = area.height as i32;
     let width = area.width as i32;
-    let gpu_count = gpu_metrics.gpus.len();
 
     // Auto-compact if terminal is very small
     let auto_compact = height < 15 || width < 50;
@@ -45,9 +50,16 @@ pub fn render_gpu_panel(frame: &mut Frame, area: Rect, app: &mut App) {
     // Calculate how many GPU cards we can show
     let reserved_height = 5 + if show_graphs_actual { graph_height } else { 0 };
     let available_for_gpus = (height - reserved_height).max(gpu_height as i32) as usize;
-    let max_gpus_to_show = (available_for_gpus / gpu_height).max(1);
-    let gpus_to_show = gpu_count.min(max_gpus_to_show);
-    let total_gpu_height = gpu_height * gpus_to_show;
+    let mut gpus_to_show = 0;
+    let mut total_gpu_height = 0;
+    for gpu in &gpu_metrics.gpus {
+        let card_height = gpu_card_height(gpu, gpu_height, use_compact);
+        if gpus_to_show > 0 && total_gpu_height + card_height > available_for_gpus {
+            break;
+        }
+        gpus_to_show += 1;
+        total_gpu_height += card_height;
+    }
 
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -65,7 +77,8 @@ pub fn render_gpu_panel(frame: &mut Frame, area: Rect, app: &mut App) {
         })
         .split(area);
 
-    render_gpu_cards_limited(frame, chunks[0], app, gpus_to_show, use_compact);
+    app.gpu_card_areas =
+        render_gpu_cards_limited(frame, chunks[0], app, gpus_to_show, gpu_height, use_compact);
 
     let mut chunk_idx = 1;
     if show_graphs_actual {
@@ -81,7 +94,7 @@ pub fn render_no_gpu_panel(frame: &mut Frame, area: Rect) {
     let text = vec![
         Line::from(""),
         Line::from(vec![Span::styled(
-            "No NVIDIA GPU Detected",
+            "No GPU Detected",
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
@@ -91,18 +104,17 @@ pub fn render_no_gpu_panel(frame: &mut Frame, area: Rect) {
             "Possible reasons:",
             Style::default().fg(Color::Cyan),
         )]),
-        Line::from("  • No NVIDIA GPU installed"),
-        Line::from("  • NVIDIA drivers not installed"),
-        Line::from("  • NVML library not available"),
-        Line::from("  • GPU in use by another process exclusively"),
+        Line::from("  • No GPU installed, or none passed through to this container/VM"),
+        Line::from("  • NVIDIA: driver or NVML library not installed"),
+        Line::from("  • AMD/Intel/other: kernel driver (amdgpu, i915, xe, ...) not loaded"),
+        Line::from("  • /sys/class/drm not readable"),
         Line::from(""),
         Line::from(vec![Span::styled(
-            "To install NVIDIA drivers:",
+            "To check the drivers:",
             Style::default().fg(Color::Cyan),
         )]),
-        Line::from("  Ubuntu/Debian: sudo apt install nvidia-driver-XXX"),
-        Line::from("  Fedora: sudo dnf install akmod-nvidia"),
-        Line::from("  Arch: sudo pacman -S nvidia"),
+        Line::from("  NVIDIA: nvidia-smi"),
+        Line::from("  AMD/Intel/other: ls /sys/class/drm; lsmod | grep -E 'amdgpu|i915|xe'"),
         Line::from(""),
         Line::from(vec![Span::styled(
             "System monitoring is fully functional.",
@@ -121,26 +133,30 @@ pub fn render_no_gpu_panel(frame: &mut Frame, area: Rect) {
 }
 
 /// Render GPU cards with a limit on number shown.
+///
+/// Returns the area of each card with its GPU index, for mouse support.
 fn render_gpu_cards_limited(
     frame: &mut Frame,
     area: Rect,
     app: &App,
     max_gpus: usize,
+    base_height: usize,
     compact: bool,
-) {
+) -> Vec<(Rect, u32)> {
     let Some(ref gpu_metrics) = app.gpu_metrics else {
-        return;
+        return Vec::new();
     };
 
     let gpu_count = gpu_metrics.gpus.len().min(max_gpus);
     if gpu_count == 0 {
-        return;
+        return Vec::new();
     }
 
-    let backend = gpu_metrics.backend;
-    let height_per_gpu = area.height as usize / gpu_count;
-    let constraints: Vec<Constraint> = (0..gpu_count)
-        .map(|_| Constraint::Length(height_per_gpu as u16))
+    let constraints: Vec<Constraint> = gpu_metrics
+        .gpus
+        .iter()
+        .take(gpu_count)
+        .map(|gpu| Constraint::Length(gpu_card_height(gpu, base_height, compact) as u16))
         .collect();
 
     let chunks = Layout::default()
@@ -148,22 +164,28 @@ fn render_gpu_cards_limited(
         .constraints(constraints)
         .split(area);
 
+    let mut card_areas = Vec::new();
     for (i, gpu) in gpu_metrics.gpus.iter().take(gpu_count).enumerate() {
         if i >= chunks.len() {
             break;
         }
-        render_gpu_card(frame, chunks[i], gpu, compact, backend);
+        render_gpu_card(frame, chunks[i], app, gpu, compact);
+        card_areas.push((chunks[i], gpu.index));
+    }
+    card_areas
+}
+
+/// Height of a GPU card including one sub-card row per MIG instance.
+fn gpu_card_height(gpu: &GpuInfo, base_height: usize, compact: bool) -> usize {
+    if compact {
+        base_height
+    } else {
+        base_height + gpu.mig_instances.len()
     }
 }
 
 /// Render a single GPU card.
-pub fn render_gpu_card(
-    frame: &mut Frame,
-    area: Rect,
-    gpu: &GpuInfo,
-    compact: bool,
-    backend: GpuBackend,
-) {
+pub fn render_gpu_card(frame: &mut Frame, area: Rect, app: &App, gpu: &GpuInfo, compact: bool) {
     let mem_pct = if gpu.memory_total > 0 {
         (gpu.memory_used as f64 / gpu.memory_total as f64) * 100.0
     } else {
@@ -171,17 +193,76 @@ pub fn render_gpu_card(
     };
 
     let card_height = area.height;
-    let is_metal = backend == GpuBackend::Metal;
+    let is_metal = gpu.backend == GpuBackend::Metal;
 
-    if is_metal {
+    if !gpu.status.is_ok() {
+        render_gpu_card_unavailable(frame, area, gpu, compact);
+    } else if is_metal {
         // Metal-specific rendering (only memory info available)
         render_gpu_card_metal(frame, area, gpu, compact, mem_pct);
     } else {
         // NVML rendering with full metrics
-        render_gpu_card_nvml(frame, area, gpu, compact, mem_pct, card_height);
+        render_gpu_card_nvml(frame, area, app, gpu, compact, mem_pct, card_height);
     }
 }
 
+/// Render the card of a GPU that stopped answering queries.
+fn render_gpu_card_unavailable(frame: &mut Frame, area: Rect, gpu: &GpuInfo, compact: bool) {
+    let badge = Span::styled(
+        format!(" {} ", gpu.status.label()),
+        Style::default()
+            .fg(Color::White)
+            .bg(Color::Red)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    if compact || area.height <= 1 {
+        let text = Line::from(vec![
+            Span::styled(
+                format!("GPU{} ", gpu.index),
+                Style::default().fg(Color::Cyan),
+            ),
+            badge,
+            Span::styled(
+                format!(" {}", gpu.name),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]);
+        frame.render_widget(Paragraph::new(text), area);
+        return;
+    }
+
+    let lines = vec![
+        Line::from(badge),
+        Line::from(Span::styled(
+            format!(
+                "{}  {}",
+                gpu.uuid.as_deref().unwrap_or("-"),
+                gpu.pcie.bus_id.as_deref().unwrap_or("-")
+            ),
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            match gpu.backend {
+                GpuBackend::Nvml if gpu.needs_nvml_reinit() => {
+                    "No readings; re-initializing NVML periodically"
+                }
+                GpuBackend::Nvml => "No readings from NVML",
+                GpuBackend::Amd | GpuBackend::Intel | GpuBackend::Drm => {
+                    "No readings from the kernel driver; read again on every refresh"
+                }
+                GpuBackend::Metal | GpuBackend::None => "No readings",
+            },
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!("GPU {} - {}", gpu.index, gpu.name))
+        .border_style(Style::default().fg(Color::Red));
+    frame.render_widget(Paragraph::new(lines).block(block), area);
+}
+
 /// Render GPU card for Metal backend (limited metrics).
 fn render_gpu_card_metal(
     frame: &mut Frame,
@@ -231,12 +312,71 @@ fn render_gpu_card_metal(
 fn render_gpu_card_nvml(
     frame: &mut Frame,
     area: Rect,
+    app: &App,
     gpu: &GpuInfo,
     compact: bool,
     mem_pct: f64,
     card_height: u16,
 ) {
-    let gpu_pct = gpu.gpu_utilization as f64;
+    // MIG sub-cards take the rows below the parent card
+    let mig_rows = if compact || card_height <= 1 {
+        0
+    } else {
+        (gpu.mig_instances.len() as u16).min(card_height.saturating_sub(3))
+    };
+    let card_height = card_height - mig_rows;
+    let parent_area = Rect {
+        height: card_height,
+        ..area
+    };
+    for (row, instance) in gpu.mig_instances.iter().take(mig_rows as usize).enumerate() {
+        let row_area = Rect {
+            y: area.y + card_height + row as u16,
+            height: 1,
+            ..area
+        };
+        render_mig_sub_card(frame, row_area, app, gpu, instance);
+    }
+    let area = parent_area;
+
+    let gpu_pct = gpu.gpu_utilization.value().unwrap_or(0) as f64;
+    let selected = app.gpu_process_filter == Some(gpu.stable_id());
+    let throttled_for = app
+        .gpu_throttle_time
+        .get(&gpu.stable_id())
+        .copied()
+        .unwrap_or_default();
+    let mut badges = throttle_badges(gpu, throttled_for);
+    let verdict = gpu.health.verdict();
+    if verdict != HealthVerdict::Unknown {
+        badges.insert(
+            0,
+            Span::styled(format!("[{}]", verdict.label()), verdict_style(verdict)),
+        );
+    }
+    badges.splice(0..0, pcie_badges(app, gpu));
+    let lost = app.gpu_fell_off_bus(gpu);
+    if lost {
+        badges.insert(
+            0,
+            Span::styled(
+                "[XID 79 OFF BUS]",
+                Style::default()
+                    .fg(Color::White)
+                    .bg(Color::Red)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+    let badges = Line::from(badges).right_aligned();
+    // Highlight the card whose processes are shown in the filtered table
+    let border_style = if lost {
+        Style::default().fg(Color::Red).add_modifier(Modifier::BOLD)
+    } else if selected {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
 
     if compact || card_height <= 1 {
         // Single line compact mode
@@ -246,42 +386,59 @@ fn render_gpu_card_nvml(
         let text = Line::from(vec![
             Span::styled(
                 format!("GPU{} ", gpu.index),
-                Style::default().fg(Color::Cyan),
+                Style::default().fg(Color::Cyan).add_modifier(if selected {
+                    Modifier::REVERSED
+                } else {
+                    Modifier::empty()
+                }),
             ),
             Span::styled(gpu_bar, Style::default().fg(usage_color(gpu_pct))),
-            Span::raw(format!(" {:3}%", gpu.gpu_utilization)),
+            Span::raw(format!(" {:>4}", gpu.gpu_utilization.label("%"))),
             Span::raw(" "),
             Span::styled("MEM ", Style::default().fg(Color::Magenta)),
             Span::styled(mem_bar, Style::default().fg(usage_color(mem_pct))),
             Span::raw(format!(" {:3}%", mem_pct as u32)),
-            Span::raw(format!(" {}°C {}W", gpu.temperature, gpu.power_usage)),
+            Span::raw(" "),
+            temperature_span(gpu, ""),
+            Span::raw(format!(" {}", gpu.power_usage.label("W"))),
+            if gpu.throttle_reasons.iter().any(|r| r.is_slowdown()) {
+                Span::styled(" THR", Style::default().fg(Color::Red))
+            } else {
+                Span::raw("")
+            },
+            if pcie_degraded(gpu) || app.pcie_replays_rising(gpu) {
+                Span::styled(" PCIe", Style::default().fg(Color::Red))
+            } else {
+                Span::raw("")
+            },
         ]);
 
         frame.render_widget(Paragraph::new(text), area);
     } else if card_height <= 3 {
         // Minimal mode with border
-        let title = format!("GPU {} - {} [{}]", gpu.index, gpu.name, gpu.pstate);
+        let title = nvml_card_title(app, gpu);
         let gpu_bar = create_bar(gpu_pct, 12);
         let mem_bar = create_bar(mem_pct, 12);
 
         let line = Line::from(vec![
             Span::styled(gpu_bar, Style::default().fg(usage_color(gpu_pct))),
-            Span::raw(format!(" {:3}% ", gpu.gpu_utilization)),
+            Span::raw(format!(" {:>4} ", gpu.gpu_utilization.label("%"))),
             Span::styled(mem_bar, Style::default().fg(usage_color(mem_pct))),
             Span::raw(format!(" {:3}% ", mem_pct as u32)),
-            Span::styled(
-                format!("{}°C ", gpu.temperature),
-                Style::default().fg(temp_color(gpu.temperature)),
-            ),
-            Span::raw(format!("{}W", gpu.power_usage)),
+            temperature_span(gpu, " "),
+            Span::raw(gpu.power_usage.label("W")),
         ]);
 
-        let block = Block::default().borders(Borders::ALL).title(title);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title(badges.clone())
+            .border_style(border_style);
         let paragraph = Paragraph::new(line).block(block);
         frame.render_widget(paragraph, area);
     } else {
         // Full mode
-        let title = format!("GPU {} - {} [{}]", gpu.index, gpu.name, gpu.pstate);
+        let title = nvml_card_title(app, gpu);
 
         let gpu_bar = create_bar(gpu_pct, 20);
         let mem_bar = create_bar(mem_pct, 20);
@@ -290,41 +447,308 @@ fn render_gpu_card_nvml(
             Line::from(vec![
                 Span::styled("GPU  ", Style::default().fg(Color::Cyan)),
                 Span::styled(gpu_bar, Style::default().fg(usage_color(gpu_pct))),
-                Span::raw(format!(" {:3}%  ", gpu.gpu_utilization)),
+                Span::raw(format!(" {:>4}  ", gpu.gpu_utilization.label("%"))),
                 Span::styled("Temp: ", Style::default().fg(Color::Yellow)),
+                temperature_span(gpu, ""),
                 Span::styled(
-                    format!("{}°C", gpu.temperature),
-                    Style::default().fg(temp_color(gpu.temperature)),
+                    threshold_label(&gpu.thresholds),
+                    Style::default().fg(Color::DarkGray),
                 ),
                 Span::raw("  "),
                 Span::styled("Fan: ", Style::default().fg(Color::Yellow)),
-                Span::raw(format!("{}%", gpu.fan_speed)),
+                Span::raw(fan_label(&gpu.fan_speeds)),
             ]),
+            Line::from(
+                [
+                    Span::styled("MEM  ", Style::default().fg(Color::Magenta)),
+                    Span::styled(mem_bar, Style::default().fg(usage_color(mem_pct))),
+                    Span::raw(format!(" {:3}%  ", mem_pct as u32)),
+                    Span::raw(format!(
+                        "{} / {}  ",
+                        format_size(gpu.memory_used, BINARY),
+                        format_size(gpu.memory_total, BINARY)
+                    )),
+                ]
+                .into_iter()
+                .chain(memory_temperature_spans(gpu))
+                .collect::<Vec<_>>(),
+            ),
             Line::from(vec![
-                Span::styled("MEM  ", Style::default().fg(Color::Magenta)),
-                Span::styled(mem_bar, Style::default().fg(usage_color(mem_pct))),
-                Span::raw(format!(" {:3}%  ", mem_pct as u32)),
+                Span::styled("Power: ", Style::default().fg(Color::Yellow)),
                 Span::raw(format!(
-                    "{} / {}",
-                    format_size(gpu.memory_used, BINARY),
-                    format_size(gpu.memory_total, BINARY)
+                    "{} / {}  ",
+                    gpu.power_usage.label("W"),
+                    gpu.power_limit.label("W")
                 )),
-            ]),
-            Line::from(vec![
-                Span::styled("Power: ", Style::default().fg(Color::Yellow)),
-                Span::raw(format!("{}W / {}W  ", gpu.power_usage, gpu.power_limit)),
+                energy_span(app, gpu),
                 Span::styled("Clocks: ", Style::default().fg(Color::Yellow)),
-                Span::raw(format!("{} MHz / {} MHz  ", gpu.sm_clock, gpu.mem_clock)),
+                Span::raw(format!(
+                    "{} / {} MHz  ",
+                    // Boost clock is what an unthrottled GPU normally reaches
+                    clock_with_max(&gpu.sm_clock, gpu.boost_clock.or(gpu.max_sm_clock)),
+                    clock_with_max(&gpu.mem_clock, gpu.max_mem_clock)
+                )),
                 Span::styled("Enc/Dec: ", Style::default().fg(Color::Yellow)),
                 Span::raw(format!(
-                    "{}% / {}%",
-                    gpu.encoder_utilization, gpu.decoder_utilization
+                    "{} / {}",
+                    gpu.encoder_utilization.label("%"),
+                    gpu.decoder_utilization.label("%")
                 )),
             ]),
         ];
 
-        let block = Block::default().borders(Borders::ALL).title(title);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title)
+            .title(badges.clone())
+            .border_style(border_style);
         let paragraph = Paragraph::new(lines).block(block);
         frame.render_widget(paragraph, area);
     }
 }
+
+/// Slowdown and shutdown temperatures after the current temperature.
+fn threshold_label(thresholds: &TempThresholds) -> String {
+    match (thresholds.slowdown, thresholds.shutdown) {
+        (Some(slowdown), Some(shutdown)) => format!(" (slow {}° off {}°)", slowdown, shutdown),
+        (Some(slowdown), None) => format!(" (slow {}°)", slowdown),
+        (None, Some(shutdown)) => format!(" (off {}°)", shutdown),
+        (None, None) => String::new(),
+    }
+}
+
+/// Speeds of every fan, or N/A for passively cooled boards.
+fn fan_label(fan_speeds: &Reading<Vec<u32>>) -> String {
+    match fan_speeds {
+        // Passively cooled boards report zero fans
+        Reading::Value(speeds) if speeds.is_empty() => "N/A".into(),
+        _ => fan_speeds.display(|speeds| {
+            speeds
+                .iter()
+                .map(|speed| format!("{}%", speed))
+                .collect::<Vec<_>>()
+                .join(" ")
+        }),
+    }
+}
+
+/// GPU temperature colored against its slowdown point; N/A or ERR in gray.
+fn temperature_span(gpu: &GpuInfo, suffix: &str) -> Span<'static> {
+    match gpu.temperature {
+        Reading::Value(temp) => Span::styled(
+            format!("{}°C{}", temp, suffix),
+            Style::default().fg(temp_color(temp, gpu.thresholds.gpu_limit())),
+        ),
+        _ => Span::styled(
+            format!("{}{}", gpu.temperature.label(""), suffix),
+            Style::default().fg(Color::DarkGray),
+        ),
+    }
+}
+
+/// Memory temperature, colored against the memory's own limit.
+fn memory_temperature_spans(gpu: &GpuInfo) -> Vec<Span<'static>> {
+    let Some(temp) = gpu.memory_temperature else {
+        return Vec::new();
+    };
+    vec![
+        Span::styled("Temp: ", Style::default().fg(Color::Yellow)),
+        Span::styled(
+            format!("{}°C", temp),
+            Style::default().fg(temp_color(temp, gpu.thresholds.memory_max)),
+        ),
+    ]
+}
+
+/// Energy used since startup, plus the open energy window if any.
+fn energy_span(app: &App, gpu: &GpuInfo) -> Span<'static> {
+    let Some(&joules) = app.gpu_energy.get(&gpu.stable_id()) else {
+        return Span::raw("");
+    };
+
+    match app.open_energy_window() {
+        Some(window) => Span::styled(
+            format!(
+                "({} / {}: {})  ",
+                format_energy(joules),
+                window.name,
+                format_energy(window.joules.get(&gpu.stable_id()).copied().unwrap_or(0.0))
+            ),
+            Style::default().fg(Color::Green),
+        ),
+        None => Span::styled(
+            format!("({})  ", format_energy(joules)),
+            Style::default().fg(Color::DarkGray),
+        ),
+    }
+}
+
+/// Card title with the performance state and MIG mode, and the vendor when
+/// GPUs come from several backends.
+fn nvml_card_title(app: &App, gpu: &GpuInfo) -> String {
+    let mig = if gpu.mig_enabled == Some(true) {
+        " [MIG]"
+    } else {
+        ""
+    };
+    let mixed = app
+        .gpu_metrics
+        .as_ref()
+        .is_some_and(|m| m.drivers.len() > 1);
+    let backend = if mixed {
+        format!(" [{}]", gpu.backend.label())
+    } else {
+        String::new()
+    };
+    format!(
+        "GPU {} - {} [{}]{}{}",
+        gpu.index,
+        gpu.name,
+        gpu.pstate.label(""),
+        mig,
+        backend
+    )
+}
+
+/// One-line sub-card for a MIG instance under its parent GPU card.
+fn render_mig_sub_card(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    gpu: &GpuInfo,
+    instance: &MigInstance,
+) {
+    let mem_pct = if instance.memory_total > 0 {
+        (instance.memory_used as f64 / instance.memory_total as f64) * 100.0
+    } else {
+        0.0
+    };
+    let process_count = app.gpu_metrics.as_ref().map_or(0, |m| {
+        m.processes
+            .iter()
+            .filter(|p| p.gpu_index == gpu.index && p.mig_instance == Some(instance.id))
+            .count()
+    });
+
+    let line = Line::from(vec![
+        Span::styled(" └ MIG ", Style::default().fg(Color::DarkGray)),
+        Span::styled(
+            format!("{:<11}", instance.profile),
+            Style::default().fg(Color::Green),
+        ),
+        Span::styled(
+            format!(
+                " GI {} CI {} ",
+                instance.id.gpu_instance, instance.id.compute_instance
+            ),
+            Style::default().fg(Color::Cyan),
+        ),
+        Span::raw(format!("{:3} SMs  ", instance.sm_count)),
+        Span::styled("MEM ", Style::default().fg(Color::Magenta)),
+        Span::styled(
+            create_bar(mem_pct, 12),
+            Style::default().fg(usage_color(mem_pct)),
+        ),
+        Span::raw(format!(
+            " {:3}% {} / {}  ",
+            mem_pct as u32,
+            format_size(instance.memory_used, BINARY),
+            format_size(instance.memory_total, BINARY)
+        )),
+        Span::styled(
+            format!("{} proc  ", process_count),
+            Style::default().fg(Color::Yellow),
+        ),
+        Span::styled(&instance.uuid, Style::default().fg(Color::DarkGray)),
+    ]);
+
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// GPU utilization above which a PCIe generation below max counts as
+/// degraded; idle GPUs downshift the link to save power.
+const PCIE_BUSY_UTILIZATION: u32 = 10;
+
+/// Whether the GPU's PCIe link trained below what it supports.
+pub fn pcie_degraded(gpu: &GpuInfo) -> bool {
+    gpu.pcie.width_degraded()
+        || (gpu.pcie.gen_degraded()
+            && gpu
+                .gpu_utilization
+                .value()
+                .is_some_and(|util| util >= PCIE_BUSY_UTILIZATION))
+}
+
+/// Badges for a degraded PCIe link and for replays piling up.
+fn pcie_badges(app: &App, gpu: &GpuInfo) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+
+    if pcie_degraded(gpu) {
+        spans.push(Span::styled(
+            format!(
+                "[PCIe {} of {}]",
+                gpu.pcie.current_label(),
+                gpu.pcie.max_label()
+            ),
+            Style::default()
+                .fg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if let Some(tracker) = app.pcie_replays.get(&gpu.stable_id()) {
+        let replays = tracker.since_start();
+        if app.pcie_replays_rising(gpu) {
+            spans.push(Span::styled(
+                format!("[REPLAY +{}]", replays),
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ));
+        } else if replays > 0 {
+            spans.push(Span::styled(
+                format!("[replay +{}]", replays),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+    }
+
+    spans
+}
+
+/// Badges for the active clock throttle reasons, plus total time throttled.
+fn throttle_badges(gpu: &GpuInfo, throttled_for: Duration) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+
+    for reason in &gpu.throttle_reasons {
+        let color = match reason {
+            ThrottleReason::HwThermal | ThrottleReason::HwSlowdown | ThrottleReason::PowerBrake => {
+                Color::Red
+            }
+            ThrottleReason::SwThermal | ThrottleReason::PowerCap => Color::Yellow,
+            ThrottleReason::SyncBoost => Color::Cyan,
+            ThrottleReason::Idle
+            | ThrottleReason::ApplicationsClocks
+            | ThrottleReason::DisplayClocks => Color::DarkGray,
+        };
+        spans.push(Span::styled(
+            format!("[{}]", reason.label()),
+            Style::default().fg(color).add_modifier(Modifier::BOLD),
+        ));
+    }
+
+    if throttled_for.as_secs() > 0 {
+        spans.push(Span::styled(
+            format!(" thr {}", format_short_duration(throttled_for.as_secs())),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    spans
+}
+
+/// Format a clock as `current/max` when the maximum is known.
+fn clock_with_max(current: &Reading<u32>, max: Option<u32>) -> String {
+    match max {
+        Some(max) => format!("{}/{}", current.label(""), max),
+        None => current.label(""),
+    }
+}
@@ -1,16 +1,19 @@
 //! History graph rendering for CPU and GPU metrics.
 
+use std::collections::HashMap;
+
+use humansize::{format_size, BINARY};
 use ratatui::{
     layout::Rect,
     style::{Color, Style},
     symbols,
     text::Line,
-    widgets::{Axis, Block, Borders, Chart, Dataset},
+    widgets::{Axis, Block, Borders, Chart, Dataset, GraphType},
     Frame,
 };
 
 use crate::app::App;
-use crate::types::GpuBackend;
+use crate::types::{GpuGraphMetric, GpuInfo};
 
 /// Render CPU and memory history graph.
 pub fn render_cpu_mem_graph(frame: &mut Frame, area: Rect, app: &App) {
@@ -75,34 +78,19 @@ pub fn render_gpu_graphs(frame: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
-    let is_metal = gpu_metrics.backend == GpuBackend::Metal;
+    let is_metal = gpu_metrics.is_metal();
     let mut datasets = Vec::new();
     let colors = [Color::Cyan, Color::Magenta, Color::Green, Color::Yellow];
+    // One line per GPU, for as many GPUs as there are colors
+    let shown: Vec<&GpuInfo> = gpu_metrics.gpus.iter().take(colors.len()).collect();
 
     if is_metal {
         // On Metal, show memory usage history instead of GPU utilization
-        let mem_data: Vec<Vec<(f64, f64)>> = app
-            .history
-            .gpu_mem_history
-            .iter()
-            .map(|h| h.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect())
-            .collect();
+        let mem_data = gpu_points(&app.history.gpu_mem_history, &shown);
 
-        for (i, data) in mem_data.iter().enumerate() {
-            if i >= 4 {
-                break;
-            }
-            datasets.push(
-                Dataset::default()
-                    .name(format!("GPU{}", i))
-                    .marker(symbols::Marker::Braille)
-                    .graph_type(ratatui::widgets::GraphType::Line)
-                    .style(Style::default().fg(colors[i % colors.len()]))
-                    .data(data),
-            );
-        }
+        datasets.extend(gpu_datasets(&mem_data, &shown, &colors));
 
-        let gpu_legend: Vec<String> = (0..mem_data.len().min(4))
+        let gpu_legend: Vec<String> = (0..shown.len())
             .map(|i| {
                 let color_name = match i {
                     0 => "cyan",
@@ -111,7 +99,7 @@ pub fn render_gpu_graphs(frame: &mut Frame, area: Rect, app: &App) {
                     3 => "yellow",
                     _ => "?",
                 };
-                format!("GPU{}={}", i, color_name)
+                format!("GPU{}={}", shown[i].index, color_name)
             })
             .collect();
         let legend = gpu_legend.join(", ");
@@ -136,29 +124,14 @@ pub fn render_gpu_graphs(frame: &mut Frame, area: Rect, app: &App) {
 
         frame.render_widget(chart, area);
     } else {
-        // On NVML, show GPU utilization history
-        let util_data: Vec<Vec<(f64, f64)>> = app
-            .history
-            .gpu_util_history
-            .iter()
-            .map(|h| h.iter().enumerate().map(|(i, &v)| (i as f64, v)).collect())
-            .collect();
+        // On NVML, show the metric selected with G
+        let metric = app.gpu_graph_metric;
+        let series = app.history.gpu_series(metric);
+        let data = gpu_points(series, &shown);
 
-        for (i, data) in util_data.iter().enumerate() {
-            if i >= 4 {
-                break;
-            }
-            datasets.push(
-                Dataset::default()
-                    .name(format!("GPU{}", i))
-                    .marker(symbols::Marker::Braille)
-                    .graph_type(ratatui::widgets::GraphType::Line)
-                    .style(Style::default().fg(colors[i % colors.len()]))
-                    .data(data),
-            );
-        }
+        datasets.extend(gpu_datasets(&data, &shown, &colors));
 
-        let gpu_legend: Vec<String> = (0..util_data.len().min(4))
+        let gpu_legend: Vec<String> = (0..shown.len())
             .map(|i| {
                 let color_name = match i {
                     0 => "cyan",
@@ -167,17 +140,52 @@ pub fn render_gpu_graphs(frame: &mut Frame, area: Rect, app: &App) {
                     3 => "yellow",
                     _ => "?",
                 };
-                format!("GPU{}={}", i, color_name)
+                format!("GPU{}={}", shown[i].index, color_name)
             })
             .collect();
         let legend = gpu_legend.join(", ");
 
+        let peak = data
+            .iter()
+            .flatten()
+            .flatten()
+            .map(|&(_, v)| v)
+            .fold(0.0, f64::max);
+        let (max, labels) = match metric {
+            // Power can briefly exceed 100% of the limit, temperature 100°C
+            GpuGraphMetric::Utilization
+            | GpuGraphMetric::Memory
+            | GpuGraphMetric::Power
+            | GpuGraphMetric::Temperature => {
+                let max = peak.max(100.0);
+                (max, value_labels(max, |v| format!("{:.0}", v)))
+            }
+            GpuGraphMetric::SmClock => {
+                // Scale to the fastest boost clock so a drop stands out
+                let boost = gpu_metrics
+                    .gpus
+                    .iter()
+                    .filter_map(|g| g.boost_clock.or(g.max_sm_clock))
+                    .max()
+                    .unwrap_or(0) as f64;
+                let max = peak.max(boost).max(1.0);
+                (max, value_labels(max, |v| format!("{:.0}", v)))
+            }
+            GpuGraphMetric::PcieRx | GpuGraphMetric::PcieTx => {
+                let max = peak.max(1024.0);
+                (
+                    max,
+                    value_labels(max, |v| format!("{}/s", format_size(v as u64, BINARY))),
+                )
+            }
+        };
+
         let chart = Chart::new(datasets)
-            .block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title(format!("GPU History ({})", legend)),
-            )
+            .block(Block::default().borders(Borders::ALL).title(format!(
+                "GPU History: {} ({}) [G: next]",
+                metric.label(),
+                legend
+            )))
             .x_axis(
                 Axis::default()
                     .bounds([0.0, 59.0])
@@ -186,10 +194,78 @@ pub fn render_gpu_graphs(frame: &mut Frame, area: Rect, app: &App) {
             .y_axis(
                 Axis::default()
                     .style(Style::default().fg(Color::Gray))
-                    .bounds([0.0, 100.0])
-                    .labels(vec![Line::from("0"), Line::from("50"), Line::from("100")]),
+                    .bounds([0.0, max])
+                    .labels(labels),
             );
 
         frame.render_widget(chart, area);
     }
 }
+
+/// Y axis labels at zero, half and full scale.
+fn value_labels(max: f64, format: impl Fn(f64) -> String) -> Vec<Line<'static>> {
+    vec![
+        Line::from("0"),
+        Line::from(format(max / 2.0)),
+        Line::from(format(max)),
+    ]
+}
+
+/// Chart points for each shown GPU, looked up by stable ID so a GPU keeps its
+/// own history when GPUs are hidden or reordered. Samples without a reading
+/// (NaN) split the points into runs, so the line has a gap there.
+fn gpu_points(series: &HashMap<String, Vec<f64>>, gpus: &[&GpuInfo]) -> Vec<Vec<Vec<(f64, f64)>>> {
+    gpus.iter()
+        .map(|gpu| {
+            let mut runs: Vec<Vec<(f64, f64)>> = Vec::new();
+            let mut run = Vec::new();
+            for (i, &v) in series
+                .get(&gpu.stable_id())
+                .into_iter()
+                .flatten()
+                .enumerate()
+            {
+                if v.is_nan() {
+                    if !run.is_empty() {
+                        runs.push(std::mem::take(&mut run));
+                    }
+                } else {
+                    run.push((i as f64, v));
+                }
+            }
+            if !run.is_empty() {
+                runs.push(run);
+            }
+            runs
+        })
+        .collect()
+}
+
+/// A dataset for each run of each GPU's points, named once for the legend.
+/// A run of one sample has no line to draw, so it is drawn as a dot.
+fn gpu_datasets<'a>(
+    points: &'a [Vec<Vec<(f64, f64)>>],
+    gpus: &[&GpuInfo],
+    colors: &[Color],
+) -> Vec<Dataset<'a>> {
+    let mut datasets = Vec::new();
+    for (i, runs) in points.iter().enumerate() {
+        for (r, run) in runs.iter().enumerate() {
+            let graph_type = if run.len() == 1 {
+                GraphType::Scatter
+            } else {
+                GraphType::Line
+            };
+            let mut dataset = Dataset::default()
+                .marker(symbols::Marker::Braille)
+                .graph_type(graph_type)
+                .style(Style::default().fg(colors[i % colors.len()]))
+                .data(run);
+            if r == 0 {
+                dataset = dataset.name(format!("GPU{}", gpus[i].index));
+            }
+            datasets.push(dataset);
+        }
+    }
+    datasets
+}
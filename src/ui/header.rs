@@ -10,7 +10,7 @@ use ratatui::{
 };
 
 use crate::app::App;
-use crate::types::GpuBackend;
+use crate::types::{EventSeverity, GpuEventKind, XID_FALLEN_OFF_BUS};
 use crate::utils::format_duration;
 
 /// Render the header bar with system and GPU info.
@@ -19,26 +19,66 @@ pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
     let now = Local::now();
 
     let gpu_info = if let Some(ref gm) = app.gpu_metrics {
-        let api_label = match gm.backend {
-            GpuBackend::Nvml => "CUDA",
-            GpuBackend::Metal => "API",
-            GpuBackend::None => "GPU",
+        let selection = match &app.gpu_selection {
+            Some(order) => format!(" | GPUs: {} of {}", order.len(), app.all_gpus().len()),
+            None => String::new(),
         };
-        format!(
-            " | Driver: {} | {}: {}",
-            gm.driver_version, api_label, gm.api_version
-        )
+        let drivers = match gm.drivers.as_slice() {
+            [] => String::new(),
+            [only] => format!(
+                " | Driver: {} | {}: {}",
+                only.driver_version,
+                only.backend.api_label(),
+                only.api_version
+            ),
+            // One short entry per vendor when GPUs come from several backends
+            several => several
+                .iter()
+                .map(|d| {
+                    format!(
+                        " | {}: {} ({} {})",
+                        d.backend.label(),
+                        d.driver_version,
+                        d.backend.api_label(),
+                        d.api_version
+                    )
+                })
+                .collect(),
+        };
+        format!("{}{}", drivers, selection)
     } else {
         String::new()
     };
 
-    let header = Line::from(vec![
-        Span::styled(
-            "nvglances",
-            Style::default()
-                .fg(Color::Cyan)
-                .add_modifier(Modifier::BOLD),
-        ),
+    let mut spans = vec![Span::styled(
+        "nvglances",
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+
+    // Driver alerts go first so a narrow terminal cannot cut them off
+    if let Some(event) = app.header_alert() {
+        let mut style = Style::default()
+            .fg(Color::White)
+            .add_modifier(Modifier::BOLD);
+        style = match event.kind.severity() {
+            EventSeverity::Critical => style.bg(Color::Red).add_modifier(Modifier::SLOW_BLINK),
+            _ => style.bg(Color::Yellow).fg(Color::Black),
+        };
+        spans.push(Span::raw(" "));
+        spans.push(Span::styled(
+            format!(
+                " {} GPU {}: {} (e: events) ",
+                event.kind.label(),
+                event.gpu_index,
+                event.kind.description()
+            ),
+            style,
+        ));
+    }
+
+    spans.extend([
         Span::raw(" | "),
         Span::styled(
             &app.system_metrics.hostname,
@@ -69,5 +109,13 @@ pub fn render_header(frame: &mut Frame, area: Rect, app: &App) {
         ),
     ]);
 
-    frame.render_widget(Paragraph::new(header), area);
+    // A GPU that fell off the bus turns the whole header red
+    let mut paragraph = Paragraph::new(Line::from(spans));
+    if app
+        .header_alert()
+        .is_some_and(|e| e.kind == GpuEventKind::Xid(Some(XID_FALLEN_OFF_BUS)))
+    {
+        paragraph = paragraph.style(Style::default().bg(Color::Red));
+    }
+    frame.render_widget(paragraph, area);
 }
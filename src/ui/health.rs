@@ -0,0 +1,187 @@
+//! GPU memory health panel (ECC errors, page retirement, row remapping).
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::types::{GpuHealth, HealthVerdict};
+
+/// Color used for a health verdict badge.
+pub fn verdict_style(verdict: HealthVerdict) -> Style {
+    let color = match verdict {
+        HealthVerdict::Healthy => Color::Green,
+        HealthVerdict::Warning => Color::Yellow,
+        HealthVerdict::Critical => Color::Red,
+        HealthVerdict::Unknown => Color::DarkGray,
+    };
+    Style::default().fg(color).add_modifier(Modifier::BOLD)
+}
+
+/// Render the health popup opened with H.
+pub fn render_health_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(90, 60, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("GPU Memory Health - press any key to close")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let gpus = app
+        .gpu_metrics
+        .as_ref()
+        .map(|m| m.gpus.as_slice())
+        .unwrap_or_default();
+    if gpus.is_empty() {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No GPU detected",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(3)])
+        .split(inner);
+
+    let header = Row::new(vec![
+        "GPU",
+        "NAME",
+        "STATUS",
+        "ECC",
+        "VOL SBE",
+        "VOL DBE",
+        "AGG SBE",
+        "AGG DBE",
+        "RETIRED SBE/DBE",
+        "RET PEND",
+        "REMAP COR/UNC",
+        "REMAP PEND",
+        "REMAP FAIL",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows: Vec<Row> = gpus
+        .iter()
+        .map(|gpu| {
+            let h = &gpu.health;
+            let verdict = h.verdict();
+            Row::new(vec![
+                Cell::from(gpu.index.to_string()),
+                Cell::from(gpu.name.clone()),
+                Cell::from(verdict.label()).style(verdict_style(verdict)),
+                Cell::from(ecc_mode(h)),
+                count_cell(h.volatile_corrected, Color::Yellow),
+                count_cell(h.volatile_uncorrected, Color::Red),
+                count_cell(h.aggregate_corrected, Color::Yellow),
+                count_cell(h.aggregate_uncorrected, Color::Yellow),
+                Cell::from(pair(
+                    h.retired_pages_sbe.map(|n| n as u64),
+                    h.retired_pages_dbe.map(|n| n as u64),
+                )),
+                flag_cell(h.retirement_pending, Color::Yellow),
+                Cell::from(pair(
+                    h.remapped_rows_correctable,
+                    h.remapped_rows_uncorrectable,
+                )),
+                flag_cell(h.remap_pending, Color::Yellow),
+                flag_cell(h.remap_failure, Color::Red),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Min(12),
+            Constraint::Length(6),
+            Constraint::Length(8),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(7),
+            Constraint::Length(15),
+            Constraint::Length(8),
+            Constraint::Length(13),
+            Constraint::Length(10),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header);
+    frame.render_widget(table, chunks[0]);
+
+    let legend = vec![
+        Line::from(Span::styled(
+            "SBE/DBE: single/double bit (corrected/uncorrected) errors. VOL: since last driver load, AGG: lifetime.",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "Pending retirement or remapping takes effect after a GPU reset. A remap failure means the board should be replaced.",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "-: not supported on this GPU",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(legend), chunks[1]);
+}
+
+/// ECC mode, noting a mode change that waits for a reboot.
+fn ecc_mode(health: &GpuHealth) -> String {
+    match (health.ecc_enabled, health.ecc_pending) {
+        (Some(current), Some(pending)) if current != pending => {
+            format!("{}->{}", on_off(current), on_off(pending))
+        }
+        (Some(current), _) => on_off(current).to_string(),
+        (None, _) => "-".to_string(),
+    }
+}
+
+fn on_off(enabled: bool) -> &'static str {
+    if enabled {
+        "On"
+    } else {
+        "Off"
+    }
+}
+
+/// Error counter, highlighted when non-zero.
+fn count_cell(value: Option<u64>, color: Color) -> Cell<'static> {
+    match value {
+        Some(0) => Cell::from("0"),
+        Some(n) => Cell::from(n.to_string()).style(Style::default().fg(color)),
+        None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+    }
+}
+
+/// Yes/no flag, highlighted when set.
+fn flag_cell(value: Option<bool>, color: Color) -> Cell<'static> {
+    match value {
+        Some(true) => {
+            Cell::from("Yes").style(Style::default().fg(color).add_modifier(Modifier::BOLD))
+        }
+        Some(false) => Cell::from("No"),
+        None => Cell::from("-").style(Style::default().fg(Color::DarkGray)),
+    }
+}
+
+fn pair(a: Option<u64>, b: Option<u64>) -> String {
+    let show = |v: Option<u64>| v.map_or("-".to_string(), |n| n.to_string());
+    format!("{}/{}", show(a), show(b))
+}
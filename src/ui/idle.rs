@@ -0,0 +1,83 @@
+//! Processes holding GPU memory while their GPU sits idle.
+
+use humansize::{format_size, BINARY};
+use ratatui::{
+    layout::{Constraint, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::utils::format_short_duration;
+
+/// Render the wasting-GPUs popup opened with W.
+pub fn render_idle_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(80, 60, area);
+    frame.render_widget(Clear, popup);
+
+    let idle = app.idle_gpu_processes();
+    let held: u64 = idle.iter().map(|p| p.gpu_memory).sum();
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(
+            "Wasting GPUs - idle for {}+, {} held - press any key to close",
+            format_short_duration(app.idle_after.as_secs()),
+            format_size(held, BINARY)
+        ))
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    if idle.is_empty() {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No process is holding memory on an idle GPU",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    let header = Row::new(vec!["PID", "USER", "GPU", "IDLE FOR", "MEMORY", "COMMAND"]).style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows: Vec<Row> = idle
+        .iter()
+        .map(|p| {
+            let command = if p.command.is_empty() {
+                &p.name
+            } else {
+                &p.command
+            };
+            Row::new(vec![
+                Cell::from(p.pid.to_string()),
+                Cell::from(p.user.clone()),
+                Cell::from(p.gpu_index.to_string()),
+                Cell::from(format_short_duration(p.idle_for.as_secs()))
+                    .style(Style::default().fg(Color::Yellow)),
+                Cell::from(format_size(p.gpu_memory, BINARY)),
+                Cell::from(command.clone()),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(7),
+            Constraint::Length(12),
+            Constraint::Length(3),
+            Constraint::Length(8),
+            Constraint::Length(10),
+            Constraint::Min(20),
+        ],
+    )
+    .header(header);
+
+    frame.render_widget(table, inner);
+}
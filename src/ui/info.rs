@@ -0,0 +1,104 @@
+//! GPU identity popup (UUID, serial, board part number, VBIOS, bus ID).
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+
+/// Render the GPU info popup opened with i.
+pub fn render_info_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(95, 60, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("GPU Info - press any key to close")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let gpus = app.all_gpus();
+    if gpus.is_empty() {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No GPU detected",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    }
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(2)])
+        .split(inner);
+
+    let header = Row::new(vec![
+        "GPU",
+        "NAME",
+        "UUID",
+        "SERIAL",
+        "BOARD PART",
+        "VBIOS",
+        "PCI BUS ID",
+        "MINOR",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let text = |value: &Option<String>| value.clone().unwrap_or_else(|| "-".to_string());
+    let rows: Vec<Row> = gpus
+        .iter()
+        .map(|gpu| {
+            Row::new(vec![
+                Cell::from(gpu.index.to_string()),
+                Cell::from(gpu.name.clone()),
+                Cell::from(text(&gpu.uuid)).style(Style::default().fg(Color::Cyan)),
+                Cell::from(text(&gpu.serial)),
+                Cell::from(text(&gpu.board_part_number)),
+                Cell::from(text(&gpu.vbios_version)),
+                Cell::from(text(&gpu.pcie.bus_id)),
+                Cell::from(
+                    gpu.minor_number
+                        .map_or("-".to_string(), |minor| minor.to_string()),
+                ),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Min(12),
+            Constraint::Length(40),
+            Constraint::Length(14),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(16),
+            Constraint::Length(5),
+        ],
+    )
+    .header(header);
+    frame.render_widget(table, chunks[0]);
+
+    let legend = vec![
+        Line::from(Span::styled(
+            "GPU indices follow the driver's enumeration and can change after a reboot; the UUID and serial do not.",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "MINOR: N in /dev/nvidiaN. -: not reported by this GPU or platform",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(legend), chunks[1]);
+}
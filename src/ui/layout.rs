@@ -5,11 +5,22 @@ use ratatui::{
     Frame,
 };
 
-use super::dialogs::{render_help, render_kill_confirm, render_status};
+use super::details::render_process_detail;
+use super::dialogs::{
+    render_action_menu, render_column_chooser, render_gpu_chooser, render_help,
+    render_kill_confirm, render_status,
+};
+use super::events::render_events_panel;
+use super::finished::render_finished_panel;
 use super::footer::render_footer;
 use super::gpu::render_gpu_panel;
 use super::header::render_header;
+use super::health::render_health_panel;
+use super::idle::render_idle_panel;
+use super::info::render_info_panel;
+use super::settings::render_settings_panel;
 use super::system::render_system_panel;
+use super::topology::render_topology_panel;
 use crate::app::App;
 
 /// Main UI rendering function.
@@ -25,6 +36,46 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
         return;
     }
 
+    if app.detail_pid.is_some() {
+        render_process_detail(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_health {
+        render_health_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_events {
+        render_events_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_topology {
+        render_topology_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_finished {
+        render_finished_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_idle {
+        render_idle_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.show_info {
+        render_info_panel(frame, frame.area(), app);
+        return;
+    }
+
+    if app.settings_panel.is_some() {
+        render_settings_panel(frame, frame.area(), app);
+        return;
+    }
+
     // Main layout - add extra row for status message if present
     let has_status = app.status_message.is_some();
     let main_chunks = Layout::default()
@@ -64,6 +115,19 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
 
     render_system_panel(frame, h_chunks[0], app);
     render_gpu_panel(frame, h_chunks[1], app);
+
+    // Drawn over the tables so column changes preview live
+    if app.column_chooser.is_some() {
+        render_column_chooser(frame, frame.area(), app);
+    }
+
+    if app.gpu_chooser.is_some() {
+        render_gpu_chooser(frame, frame.area(), app);
+    }
+
+    if app.action_menu.is_some() {
+        render_action_menu(frame, frame.area(), app);
+    }
 }
 
 /// Create a centered rectangle for dialogs.
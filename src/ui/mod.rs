@@ -1,12 +1,20 @@
 //! User interface rendering modules.
 
+mod details;
 mod dialogs;
+mod events;
+mod finished;
 mod footer;
 mod gpu;
 mod graphs;
 mod header;
+mod health;
+mod idle;
+mod info;
 mod layout;
 mod processes;
+mod settings;
 mod system;
+mod topology;
 
 pub use layout::render_ui;
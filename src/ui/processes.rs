@@ -2,19 +2,23 @@
 
 use humansize::{format_size, BINARY};
 use ratatui::{
-    layout::{Alignment, Margin, Rect},
+    layout::{Alignment, Constraint, Flex, Layout, Margin, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{
         Block, Borders, Cell, Paragraph, Row, Scrollbar, ScrollbarOrientation, ScrollbarState,
-        Table,
+        Table, TableState,
     },
     Frame,
 };
 
 use crate::app::App;
-use crate::types::{ActivePanel, GpuBackend, SortColumn};
-use crate::utils::{truncate_string, usage_color};
+use crate::columns::{Column, ColumnLayout, CpuColumn, GpuColumn};
+use crate::types::{ActivePanel, GpuProcessInfo, ProcessInfo, SortColumn};
+use crate::utils::{skip_width, sparkline, truncate_string, usage_color, wrap_to_width};
+
+/// Most lines a wrapped command may take up.
+const MAX_WRAPPED_LINES: usize = 8;
 
 /// Render the CPU process table.
 pub fn render_cpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
@@ -24,57 +28,47 @@ pub fn render_cpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
     let procs = app.get_sorted_cpu_processes();
     let is_active = app.active_panel == ActivePanel::CpuProcesses;
 
-    let sort_indicator = |col: SortColumn| -> &str {
-        if app.cpu_sort == col {
-            if app.sort_ascending {
-                "▲"
-            } else {
-                "▼"
-            }
-        } else {
-            ""
-        }
-    };
+    let constraints = column_constraints(&app.config.cpu_columns);
+    app.cpu_column_areas = column_areas(&constraints, area);
 
-    let header = Row::new(vec![
-        format!("PID{}", sort_indicator(SortColumn::Pid)),
-        format!("USER{}", sort_indicator(SortColumn::User)),
-        format!("CPU%{}", sort_indicator(SortColumn::Cpu)),
-        format!("MEM%{}", sort_indicator(SortColumn::Memory)),
-        "MEM".into(),
-        "STATUS".into(),
-        format!("NAME{}", sort_indicator(SortColumn::Name)),
-        "COMMAND".into(),
-    ])
-    .style(
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
-    );
+    let layout = &app.config.cpu_columns;
+    let header = header_row(layout, app.cpu_sort, app.sort_ascending);
+    let command_width = command_column_width(layout, &app.cpu_column_areas);
+    let selected = app.cpu_process_state.selected();
 
+    let mut selected_height = 1;
     let rows: Vec<Row> = procs
         .iter()
-        .map(|p| {
-            let cpu_color = usage_color(p.cpu_usage as f64);
-            let mem_color = usage_color(p.memory_usage as f64);
-
-            Row::new(vec![
-                Cell::from(format!("{}", p.pid)),
-                Cell::from(p.user.clone()).style(Style::default().fg(Color::Cyan)),
-                Cell::from(format!("{:.1}", p.cpu_usage)).style(Style::default().fg(cpu_color)),
-                Cell::from(format!("{:.1}", p.memory_usage)).style(Style::default().fg(mem_color)),
-                Cell::from(format_size(p.memory_bytes, BINARY)),
-                Cell::from(p.status.clone()),
-                Cell::from(p.name.clone()).style(Style::default().fg(Color::Green)),
-                Cell::from(truncate_string(&p.command, 40)),
-            ])
+        .enumerate()
+        .map(|(i, p)| {
+            let wrap = app.wrap_command && is_active && selected == Some(i);
+            let command = command_text(&p.command, app.cpu_hscroll, command_width, wrap);
+            let height = command.height() as u16;
+            if wrap {
+                selected_height = height;
+            }
+            let cells = layout.visible().map(|c| match c.column {
+                CpuColumn::Command => Cell::from(command.clone()),
+                column => cpu_cell(column, p, app),
+            });
+            Row::new(cells).height(height)
         })
         .collect();
 
+    if is_active {
+        app.selected_row_height = selected_height;
+    }
+
+    let follow_label = match app.followed {
+        Some(ref f) if f.panel == ActivePanel::CpuProcesses => format!(" [FOLLOW {}]", f.pid),
+        _ => String::new(),
+    };
     let title = format!(
-        "CPU Processes ({}) [{}]",
+        "CPU Processes ({}) [{}]{}{}",
         procs.len(),
-        if is_active { "ACTIVE" } else { "inactive" }
+        if is_active { "ACTIVE" } else { "inactive" },
+        scroll_label(app.cpu_hscroll),
+        follow_label
     );
     let border_style = if is_active {
         Style::default().fg(Color::Cyan)
@@ -82,31 +76,23 @@ pub fn render_cpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
         Style::default().fg(Color::DarkGray)
     };
 
-    let table = Table::new(
-        rows,
-        [
-            ratatui::layout::Constraint::Length(7),
-            ratatui::layout::Constraint::Length(10),
-            ratatui::layout::Constraint::Length(6),
-            ratatui::layout::Constraint::Length(6),
-            ratatui::layout::Constraint::Length(9),
-            ratatui::layout::Constraint::Length(8),
-            ratatui::layout::Constraint::Length(15),
-            ratatui::layout::Constraint::Min(20),
-        ],
-    )
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .border_style(border_style),
-    )
-    .header(header)
-    .row_highlight_style(
-        Style::default()
-            .bg(Color::DarkGray)
-            .add_modifier(Modifier::BOLD),
-    );
+    let table = Table::new(rows, constraints)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(border_style),
+        )
+        .header(header)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if app.is_following(ActivePanel::CpuProcesses) {
+        center_selection(&mut app.cpu_process_state, area, procs.len());
+    }
 
     frame.render_stateful_widget(table, area, &mut app.cpu_process_state);
 
@@ -143,7 +129,7 @@ pub fn render_gpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
     let is_metal = app
         .gpu_metrics
         .as_ref()
-        .map(|m| m.backend == GpuBackend::Metal)
+        .map(|m| m.is_metal())
         .unwrap_or(false);
 
     if is_metal {
@@ -177,58 +163,52 @@ pub fn render_gpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
         return;
     }
 
-    let sort_indicator = |col: SortColumn| -> &str {
-        if app.gpu_sort == col {
-            if app.sort_ascending {
-                "▲"
-            } else {
-                "▼"
-            }
-        } else {
-            ""
-        }
-    };
+    let constraints = column_constraints(&app.config.gpu_columns);
+    app.gpu_column_areas = column_areas(&constraints, area);
 
-    let header = Row::new(vec![
-        format!("PID{}", sort_indicator(SortColumn::Pid)),
-        "GPU".into(),
-        "TYPE".into(),
-        format!("USER{}", sort_indicator(SortColumn::User)),
-        format!("GPU_MEM{}", sort_indicator(SortColumn::GpuMemory)),
-        format!("NAME{}", sort_indicator(SortColumn::Name)),
-        "COMMAND".into(),
-    ])
-    .style(
-        Style::default()
-            .fg(Color::Yellow)
-            .add_modifier(Modifier::BOLD),
-    );
+    let layout = &app.config.gpu_columns;
+    let header = header_row(layout, app.gpu_sort, app.sort_ascending);
+    let command_width = command_column_width(layout, &app.gpu_column_areas);
+    let selected = app.gpu_process_state.selected();
 
+    let mut selected_height = 1;
     let rows: Vec<Row> = procs
         .iter()
-        .map(|p| {
-            let type_color = if p.process_type == "C" {
-                Color::Green
-            } else {
-                Color::Blue
-            };
-
-            Row::new(vec![
-                Cell::from(format!("{}", p.pid)),
-                Cell::from(format!("{}", p.gpu_index)),
-                Cell::from(p.process_type.clone()).style(Style::default().fg(type_color)),
-                Cell::from(p.user.clone()).style(Style::default().fg(Color::Cyan)),
-                Cell::from(format_size(p.gpu_memory, BINARY)),
-                Cell::from(p.name.clone()).style(Style::default().fg(Color::Green)),
-                Cell::from(truncate_string(&p.command, 40)),
-            ])
+        .enumerate()
+        .map(|(i, p)| {
+            let wrap = app.wrap_command && is_active && selected == Some(i);
+            let command = command_text(&p.command, app.gpu_hscroll, command_width, wrap);
+            let height = command.height() as u16;
+            if wrap {
+                selected_height = height;
+            }
+            let cells = layout.visible().map(|c| match c.column {
+                GpuColumn::Command => Cell::from(command.clone()),
+                column => gpu_cell(column, p, app),
+            });
+            Row::new(cells).height(height)
         })
         .collect();
 
+    if is_active {
+        app.selected_row_height = selected_height;
+    }
+
+    let follow_label = match app.followed {
+        Some(ref f) if f.panel == ActivePanel::GpuProcesses => format!(" [FOLLOW {}]", f.pid),
+        _ => String::new(),
+    };
+    let gpu_filter_label = match app.gpu_filter_index() {
+        Some(index) => format!(" [GPU {}]", index),
+        None => String::new(),
+    };
     let title = format!(
-        "GPU Processes ({}) [{}]",
+        "GPU Processes ({}) [{}]{}{}{}",
         procs.len(),
-        if is_active { "ACTIVE" } else { "inactive" }
+        if is_active { "ACTIVE" } else { "inactive" },
+        gpu_filter_label,
+        scroll_label(app.gpu_hscroll),
+        follow_label
     );
     let border_style = if is_active {
         Style::default().fg(Color::Cyan)
@@ -236,30 +216,23 @@ pub fn render_gpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
         Style::default().fg(Color::DarkGray)
     };
 
-    let table = Table::new(
-        rows,
-        [
-            ratatui::layout::Constraint::Length(7),
-            ratatui::layout::Constraint::Length(4),
-            ratatui::layout::Constraint::Length(5),
-            ratatui::layout::Constraint::Length(10),
-            ratatui::layout::Constraint::Length(10),
-            ratatui::layout::Constraint::Length(15),
-            ratatui::layout::Constraint::Min(20),
-        ],
-    )
-    .block(
-        Block::default()
-            .borders(Borders::ALL)
-            .title(title)
-            .border_style(border_style),
-    )
-    .header(header)
-    .row_highlight_style(
-        Style::default()
-            .bg(Color::DarkGray)
-            .add_modifier(Modifier::BOLD),
-    );
+    let table = Table::new(rows, constraints)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(title)
+                .border_style(border_style),
+        )
+        .header(header)
+        .row_highlight_style(
+            Style::default()
+                .bg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    if app.is_following(ActivePanel::GpuProcesses) {
+        center_selection(&mut app.gpu_process_state, area, procs.len());
+    }
 
     frame.render_stateful_widget(table, area, &mut app.gpu_process_state);
 
@@ -283,3 +256,208 @@ pub fn render_gpu_processes(frame: &mut Frame, area: Rect, app: &mut App) {
         );
     }
 }
+
+/// Scroll a table so the selected row sits in the middle of the visible area.
+fn center_selection(state: &mut TableState, area: Rect, len: usize) {
+    // Borders and header take three rows
+    let visible_rows = (area.height as usize).saturating_sub(3);
+    let selected = state.selected().unwrap_or(0);
+    *state.offset_mut() = selected
+        .saturating_sub(visible_rows / 2)
+        .min(len.saturating_sub(visible_rows));
+}
+
+/// Build the header row for a column layout, marking the active sort column.
+fn header_row<C: Column>(
+    layout: &ColumnLayout<C>,
+    sort: SortColumn,
+    ascending: bool,
+) -> Row<'static> {
+    let labels: Vec<String> = layout
+        .visible()
+        .map(|s| {
+            let indicator = match s.column.sort_column() {
+                Some(col) if col == sort => {
+                    if ascending {
+                        "▲"
+                    } else {
+                        "▼"
+                    }
+                }
+                _ => "",
+            };
+            format!("{}{}", s.column.header(), indicator)
+        })
+        .collect();
+
+    Row::new(labels).style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    )
+}
+
+/// Width constraints for the visible columns of a layout.
+fn column_constraints<C: Column>(layout: &ColumnLayout<C>) -> Vec<Constraint> {
+    layout
+        .visible()
+        .map(|s| {
+            if s.column.flexible() {
+                Constraint::Min(s.width)
+            } else {
+                Constraint::Length(s.width)
+            }
+        })
+        .collect()
+}
+
+/// Screen areas of each column, laid out the same way `Table` does it.
+fn column_areas(constraints: &[Constraint], area: Rect) -> Vec<Rect> {
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    Layout::horizontal(constraints.to_vec())
+        .flex(Flex::Start)
+        .spacing(1)
+        .split(inner)
+        .to_vec()
+}
+
+/// Display width of the command column, or 0 if it is hidden.
+fn command_column_width<C: Column>(layout: &ColumnLayout<C>, areas: &[Rect]) -> usize {
+    layout
+        .visible()
+        .position(|s| s.column.flexible())
+        .and_then(|i| areas.get(i))
+        .map(|a| a.width as usize)
+        .unwrap_or(0)
+}
+
+/// Command text scrolled horizontally, either cut to one line or wrapped.
+fn command_text(command: &str, hscroll: usize, width: usize, wrap: bool) -> Text<'static> {
+    if wrap {
+        let lines = wrap_to_width(command, width);
+        return Text::from(
+            lines
+                .into_iter()
+                .take(MAX_WRAPPED_LINES)
+                .map(Line::from)
+                .collect::<Vec<_>>(),
+        );
+    }
+    Text::from(truncate_string(skip_width(command, hscroll), width))
+}
+
+/// Title suffix showing how far the command column is scrolled.
+fn scroll_label(hscroll: usize) -> String {
+    if hscroll > 0 {
+        format!(" [→{}]", hscroll)
+    } else {
+        String::new()
+    }
+}
+
+/// Render one cell of the CPU process table.
+fn cpu_cell(column: CpuColumn, p: &ProcessInfo, app: &App) -> Cell<'static> {
+    let cpu_color = usage_color(p.cpu_usage as f64);
+
+    match column {
+        CpuColumn::Pid => Cell::from(format!("{}", p.pid)),
+        CpuColumn::User => Cell::from(p.user.clone()).style(Style::default().fg(Color::Cyan)),
+        CpuColumn::Cpu => {
+            Cell::from(format!("{:.1}", p.cpu_usage)).style(Style::default().fg(cpu_color))
+        }
+        CpuColumn::CpuHistory => {
+            let hist = app
+                .history
+                .process_history
+                .get(&p.pid)
+                .map(|h| sparkline(&h.cpu, 8, 100.0))
+                .unwrap_or_default();
+            Cell::from(hist).style(Style::default().fg(cpu_color))
+        }
+        CpuColumn::MemoryPercent => Cell::from(format!("{:.1}", p.memory_usage))
+            .style(Style::default().fg(usage_color(p.memory_usage as f64))),
+        CpuColumn::Memory => Cell::from(format_size(p.memory_bytes, BINARY)),
+        CpuColumn::Status => Cell::from(p.status.clone()),
+        CpuColumn::Threads => Cell::from(
+            p.threads
+                .map(|t| t.to_string())
+                .unwrap_or_else(|| "-".into()),
+        ),
+        CpuColumn::IoRead => Cell::from(format!(
+            "{}/s",
+            format_size(p.disk_read_rate as u64, BINARY)
+        )),
+        CpuColumn::IoWrite => Cell::from(format!(
+            "{}/s",
+            format_size(p.disk_write_rate as u64, BINARY)
+        )),
+        CpuColumn::StartTime => Cell::from(format_start_time(p.start_time)),
+        CpuColumn::Container => optional_cell(&p.workload.container),
+        CpuColumn::Job => optional_cell(&p.workload.job),
+        CpuColumn::Name => Cell::from(p.name.clone()).style(Style::default().fg(Color::Green)),
+        CpuColumn::Command => Cell::from(p.command.clone()),
+    }
+}
+
+/// Render one cell of the GPU process table.
+fn gpu_cell(column: GpuColumn, p: &GpuProcessInfo, app: &App) -> Cell<'static> {
+    match column {
+        GpuColumn::Pid => Cell::from(format!("{}", p.pid)),
+        GpuColumn::Gpu => Cell::from(match p.mig_instance {
+            Some(mig) => format!("{}:{}", p.gpu_index, mig.label()),
+            None => format!("{}", p.gpu_index),
+        }),
+        GpuColumn::Type => {
+            let type_color = if p.process_type == "C" {
+                Color::Green
+            } else {
+                Color::Blue
+            };
+            Cell::from(p.process_type.clone()).style(Style::default().fg(type_color))
+        }
+        GpuColumn::User => Cell::from(p.user.clone()).style(Style::default().fg(Color::Cyan)),
+        GpuColumn::Utilization => match p.sm_utilization {
+            Some(pct) => {
+                Cell::from(format!("{}", pct)).style(Style::default().fg(usage_color(pct as f64)))
+            }
+            None => Cell::from("N/A").style(Style::default().fg(Color::DarkGray)),
+        },
+        GpuColumn::GpuMemory => Cell::from(format_size(p.gpu_memory, BINARY)),
+        GpuColumn::GpuMemoryHistory => {
+            let hist = app
+                .history
+                .process_history
+                .get(&p.pid)
+                .map(|h| sparkline(&h.gpu_memory, 8, 0.0))
+                .unwrap_or_default();
+            Cell::from(hist).style(Style::default().fg(Color::Magenta))
+        }
+        GpuColumn::Container => {
+            optional_cell(&app.workload_of(p.pid).and_then(|w| w.container.clone()))
+        }
+        GpuColumn::Job => optional_cell(&app.workload_of(p.pid).and_then(|w| w.job.clone())),
+        GpuColumn::Name => Cell::from(p.name.clone()).style(Style::default().fg(Color::Green)),
+        GpuColumn::Command => Cell::from(p.command.clone()),
+    }
+}
+
+/// A cell for a value that may be missing, shown as `-`.
+fn optional_cell(value: &Option<String>) -> Cell<'static> {
+    Cell::from(value.clone().unwrap_or_else(|| "-".into()))
+}
+
+/// Format a process start time: clock time for today, date otherwise.
+fn format_start_time(epoch_secs: u64) -> String {
+    let Some(start) = chrono::DateTime::from_timestamp(epoch_secs as i64, 0) else {
+        return "-".into();
+    };
+    let start = start.with_timezone(&chrono::Local);
+    if start.date_naive() == chrono::Local::now().date_naive() {
+        start.format("%H:%M").to_string()
+    } else {
+        start.format("%b%d").to_string()
+    }
+}
@@ -0,0 +1,276 @@
+//! GPU device settings panel (persistence, compute mode, power limit, clocks).
+
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table, Wrap},
+    Frame,
+};
+
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::types::{GpuInfo, SettingsPanel};
+
+/// Render the settings popup opened with S.
+pub fn render_settings_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(panel) = app.settings_panel else {
+        return;
+    };
+
+    let popup = centered_rect(90, 70, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("GPU Settings - Esc to close")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let gpus = app
+        .gpu_metrics
+        .as_ref()
+        .map(|m| m.gpus.as_slice())
+        .unwrap_or_default();
+    let Some(gpu) = gpus.iter().find(|g| g.index == panel.gpu_index) else {
+        return;
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(gpus.len() as u16 + 2),
+            Constraint::Length(SettingsPanel::ROWS as u16 + 2),
+            Constraint::Min(0),
+            Constraint::Length(3),
+        ])
+        .split(inner);
+
+    render_settings_table(frame, chunks[0], app, gpus, panel.gpu_index);
+    render_editor(frame, chunks[1], gpu, &panel);
+
+    let mut legend = vec![
+        Line::from(Span::styled(
+            "↑/↓: select  ←/→: change value  Enter: apply  Tab: next GPU",
+            Style::default().fg(Color::DarkGray),
+        )),
+        if app.privileged {
+            Line::from(Span::styled(
+                "Changes take effect immediately and last until the driver is reloaded.",
+                Style::default().fg(Color::DarkGray),
+            ))
+        } else {
+            Line::from(Span::styled(
+                "Read-only: changing device settings requires root.",
+                Style::default().fg(Color::Yellow),
+            ))
+        },
+    ];
+    // The main status line is hidden behind the panel
+    if let Some((msg, _)) = &app.status_message {
+        legend.push(Line::from(Span::styled(
+            msg.clone(),
+            Style::default().fg(Color::Yellow),
+        )));
+    }
+    frame.render_widget(Paragraph::new(legend), chunks[3]);
+
+    if app.setting_confirm.is_some() {
+        render_setting_confirm(frame, area, app);
+    }
+}
+
+/// Current settings of every GPU, with the edited GPU highlighted.
+fn render_settings_table(
+    frame: &mut Frame,
+    area: Rect,
+    app: &App,
+    gpus: &[GpuInfo],
+    selected: u32,
+) {
+    let header = Row::new(vec![
+        "GPU",
+        "NAME",
+        "PERSIST",
+        "COMPUTE MODE",
+        "DISPLAY",
+        "APP CLOCKS (DEFAULT)",
+        "POWER LIMIT",
+        "MIN/MAX/DEFAULT",
+        "CLOCK LOCK",
+    ])
+    .style(
+        Style::default()
+            .fg(Color::Yellow)
+            .add_modifier(Modifier::BOLD),
+    );
+
+    let rows: Vec<Row> = gpus
+        .iter()
+        .map(|gpu| {
+            let s = &gpu.settings;
+            let row = Row::new(vec![
+                Cell::from(gpu.index.to_string()),
+                Cell::from(gpu.name.clone()),
+                Cell::from(on_off(s.persistence_mode)),
+                Cell::from(s.compute_mode.map_or("-", |m| m.label())),
+                Cell::from(on_off(s.display_active)),
+                Cell::from(format!(
+                    "{}/{} ({}/{}) MHz",
+                    opt(s.app_sm_clock),
+                    opt(s.app_mem_clock),
+                    opt(s.default_app_sm_clock),
+                    opt(s.default_app_mem_clock)
+                )),
+                Cell::from(gpu.power_limit.label(" W")),
+                Cell::from(format!(
+                    "{}/{}/{} W",
+                    opt(s.power_limit_min),
+                    opt(s.power_limit_max),
+                    opt(s.power_limit_default)
+                )),
+                Cell::from(
+                    app.locked_clocks
+                        .get(&gpu.stable_id())
+                        .map_or("-".to_string(), |mhz| format!("{} MHz", mhz)),
+                ),
+            ]);
+            if gpu.index == selected {
+                row.style(Style::default().add_modifier(Modifier::REVERSED))
+            } else {
+                row
+            }
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Min(12),
+            Constraint::Length(7),
+            Constraint::Length(17),
+            Constraint::Length(7),
+            Constraint::Length(24),
+            Constraint::Length(11),
+            Constraint::Length(15),
+            Constraint::Length(10),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::BOTTOM));
+    frame.render_widget(table, area);
+}
+
+/// Editable values for the selected GPU.
+fn render_editor(frame: &mut Frame, area: Rect, gpu: &GpuInfo, panel: &SettingsPanel) {
+    let values = [
+        ("Power limit", format!("{} W", panel.power_limit)),
+        ("Compute mode", panel.compute_mode.label().to_string()),
+        (
+            "SM clock lock",
+            panel
+                .clock_lock
+                .map_or("Unlocked".to_string(), |mhz| format!("{} MHz", mhz)),
+        ),
+    ];
+
+    let mut lines = vec![Line::from(Span::styled(
+        format!("GPU {} - {}", gpu.index, gpu.name),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    ))];
+    for (i, (label, value)) in values.into_iter().enumerate() {
+        let selected = i == panel.cursor;
+        let style = if selected {
+            Style::default().add_modifier(Modifier::REVERSED)
+        } else {
+            Style::default()
+        };
+        lines.push(Line::from(vec![
+            Span::styled(format!("  {:<14}", label), style),
+            Span::styled(
+                format!("◀ {} ▶", value),
+                if selected {
+                    Style::default().fg(Color::Green)
+                } else {
+                    Style::default().fg(Color::DarkGray)
+                },
+            ),
+        ]));
+    }
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+/// Confirmation before changing a device setting.
+fn render_setting_confirm(frame: &mut Frame, area: Rect, app: &App) {
+    let Some(ref confirm) = app.setting_confirm else {
+        return;
+    };
+
+    let text = vec![
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Change device setting?",
+            Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![
+            Span::raw("  GPU: "),
+            Span::styled(
+                format!("{} - {}", confirm.gpu_index, confirm.gpu_name),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]),
+        Line::from(vec![
+            Span::raw("  Change: "),
+            Span::styled(
+                confirm.change.describe(),
+                Style::default().fg(Color::Magenta),
+            ),
+        ]),
+        Line::from(""),
+        Line::from(vec![
+            Span::styled(
+                "  [Y]",
+                Style::default()
+                    .fg(Color::Green)
+                    .add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" Yes, apply it   "),
+            Span::styled(
+                "[N]",
+                Style::default().fg(Color::Red).add_modifier(Modifier::BOLD),
+            ),
+            Span::raw(" No, cancel"),
+        ]),
+        Line::from(""),
+    ];
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("Confirm Setting")
+        .border_style(Style::default().fg(Color::Red));
+
+    let paragraph = Paragraph::new(text).block(block).wrap(Wrap { trim: false });
+
+    let confirm_area = centered_rect(40, 40, area);
+
+    frame.render_widget(Clear, confirm_area);
+    frame.render_widget(paragraph, confirm_area);
+}
+
+fn on_off(value: Option<bool>) -> &'static str {
+    match value {
+        Some(true) => "On",
+        Some(false) => "Off",
+        None => "-",
+    }
+}
+
+fn opt(value: Option<u32>) -> String {
+    value.map_or("-".to_string(), |v| v.to_string())
+}
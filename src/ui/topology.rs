@@ -0,0 +1,247 @@
+//! GPU interconnect topology screen (path matrix, PCIe placement, NVLinks).
+
+use humansize::{format_size, BINARY};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Cell, Clear, Paragraph, Row, Table},
+    Frame,
+};
+
+use super::gpu::pcie_degraded;
+use super::layout::centered_rect;
+use crate::app::App;
+use crate::types::{GpuMetrics, TopologyPath};
+
+/// Render the topology popup opened with t.
+pub fn render_topology_panel(frame: &mut Frame, area: Rect, app: &App) {
+    let popup = centered_rect(90, 85, area);
+    frame.render_widget(Clear, popup);
+
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title("GPU Topology - press any key to close")
+        .border_style(Style::default().fg(Color::Cyan));
+    let inner = block.inner(popup);
+    frame.render_widget(block, popup);
+
+    let Some(metrics) = app.gpu_metrics.as_ref().filter(|m| !m.gpus.is_empty()) else {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No GPU detected",
+            Style::default().fg(Color::DarkGray),
+        )));
+        frame.render_widget(text, inner);
+        return;
+    };
+
+    let gpu_rows = metrics.gpus.len() as u16 + 3;
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(gpu_rows),
+            Constraint::Length(gpu_rows),
+            Constraint::Min(4),
+            Constraint::Length(2),
+        ])
+        .split(inner);
+
+    render_matrix(frame, chunks[0], metrics);
+    render_placement(frame, chunks[1], metrics);
+    render_nvlinks(frame, chunks[2], metrics);
+
+    let legend = vec![
+        Line::from(Span::styled(
+            "NV#: # direct NVLinks  NVS: via NVSwitch  PIX: one PCIe switch  PXB: several PCIe switches",
+            Style::default().fg(Color::DarkGray),
+        )),
+        Line::from(Span::styled(
+            "PHB: PCIe host bridge  NODE: host bridges in one NUMA node  SYS: across NUMA nodes",
+            Style::default().fg(Color::DarkGray),
+        )),
+    ];
+    frame.render_widget(Paragraph::new(legend), chunks[3]);
+}
+
+/// GPU-to-GPU path matrix, as in `nvidia-smi topo -m`.
+fn render_matrix(frame: &mut Frame, area: Rect, metrics: &GpuMetrics) {
+    let header = Row::new(
+        std::iter::once(String::new())
+            .chain(metrics.gpus.iter().map(|g| format!("GPU{}", g.index)))
+            .collect::<Vec<_>>(),
+    )
+    .style(header_style());
+
+    let rows: Vec<Row> = metrics
+        .gpus
+        .iter()
+        .enumerate()
+        .map(|(i, gpu)| {
+            let mut cells = vec![
+                Cell::from(format!("GPU{}", gpu.index)).style(Style::default().fg(Color::Cyan))
+            ];
+            for j in 0..metrics.gpus.len() {
+                let path = metrics.topology.get(i).and_then(|row| row.get(j)).copied();
+                cells.push(match path.flatten() {
+                    _ if i == j => Cell::from("X").style(Style::default().fg(Color::DarkGray)),
+                    Some(path) => Cell::from(path.label()).style(path_style(path)),
+                    None => Cell::from("?").style(Style::default().fg(Color::DarkGray)),
+                });
+            }
+            Row::new(cells)
+        })
+        .collect();
+
+    let widths = vec![Constraint::Length(6); metrics.gpus.len() + 1];
+    let table = Table::new(rows, widths)
+        .header(header)
+        .block(Block::default().borders(Borders::ALL).title("Interconnect"));
+    frame.render_widget(table, area);
+}
+
+/// PCIe address, link training and CPU placement of each GPU.
+fn render_placement(frame: &mut Frame, area: Rect, metrics: &GpuMetrics) {
+    let header = Row::new(vec![
+        "GPU",
+        "BUS ID",
+        "PCIe LINK",
+        "MAX",
+        "REPLAYS",
+        "NUMA",
+        "CPU AFFINITY",
+    ])
+    .style(header_style());
+
+    let rows: Vec<Row> = metrics
+        .gpus
+        .iter()
+        .map(|gpu| {
+            let link = &gpu.pcie;
+            let degraded = pcie_degraded(gpu);
+            Row::new(vec![
+                Cell::from(gpu.index.to_string()),
+                Cell::from(link.bus_id.clone().unwrap_or_else(|| "-".into())),
+                Cell::from(link.current_label()).style(if degraded {
+                    Style::default().fg(Color::Yellow)
+                } else {
+                    Style::default().fg(Color::Green)
+                }),
+                Cell::from(link.max_label()),
+                Cell::from(link.replay_count.map_or("-".into(), |n| n.to_string())).style(
+                    if link.replay_count.unwrap_or(0) > 0 {
+                        Style::default().fg(Color::Yellow)
+                    } else {
+                        Style::default()
+                    },
+                ),
+                Cell::from(gpu.numa_node.map_or("-".into(), |n| n.to_string())),
+                Cell::from(gpu.cpu_affinity.clone().unwrap_or_else(|| "-".into())),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(16),
+            Constraint::Length(10),
+            Constraint::Length(10),
+            Constraint::Length(7),
+            Constraint::Length(4),
+            Constraint::Min(12),
+        ],
+    )
+    .header(header)
+    .block(Block::default().borders(Borders::ALL).title("PCIe / NUMA"));
+    frame.render_widget(table, area);
+}
+
+/// Per-link NVLink state and throughput.
+fn render_nvlinks(frame: &mut Frame, area: Rect, metrics: &GpuMetrics) {
+    let block = Block::default().borders(Borders::ALL).title("NVLink");
+
+    let links: Vec<_> = metrics
+        .gpus
+        .iter()
+        .flat_map(|gpu| gpu.nvlinks.iter().map(move |link| (gpu.index, link)))
+        .collect();
+    if links.is_empty() {
+        let text = Paragraph::new(Line::from(Span::styled(
+            "No NVLink on these GPUs",
+            Style::default().fg(Color::DarkGray),
+        )))
+        .block(block);
+        frame.render_widget(text, area);
+        return;
+    }
+
+    let header = Row::new(vec![
+        "GPU", "LINK", "STATE", "VER", "REMOTE", "RX/s", "TX/s",
+    ])
+    .style(header_style());
+
+    let rate = |r: Option<f64>| {
+        r.map_or("-".into(), |r| {
+            format!("{}/s", format_size(r as u64, BINARY))
+        })
+    };
+
+    let rows: Vec<Row> = links
+        .iter()
+        .map(|(gpu_index, link)| {
+            let state = if link.active {
+                Cell::from("Up").style(Style::default().fg(Color::Green))
+            } else {
+                Cell::from("Down").style(Style::default().fg(Color::DarkGray))
+            };
+            let remote = match (link.remote_gpu, &link.remote_bus_id) {
+                (Some(gpu), _) => format!("GPU{}", gpu),
+                (None, Some(bus_id)) => format!("NVSwitch {}", bus_id),
+                (None, None) => "-".into(),
+            };
+            Row::new(vec![
+                Cell::from(gpu_index.to_string()),
+                Cell::from(link.link.to_string()),
+                state,
+                Cell::from(link.version.map_or("-".into(), |v| v.to_string())),
+                Cell::from(remote),
+                Cell::from(rate(link.rx_rate)),
+                Cell::from(rate(link.tx_rate)),
+            ])
+        })
+        .collect();
+
+    let table = Table::new(
+        rows,
+        [
+            Constraint::Length(3),
+            Constraint::Length(4),
+            Constraint::Length(5),
+            Constraint::Length(3),
+            Constraint::Min(16),
+            Constraint::Length(12),
+            Constraint::Length(12),
+        ],
+    )
+    .header(header)
+    .block(block);
+    frame.render_widget(table, area);
+}
+
+fn header_style() -> Style {
+    Style::default()
+        .fg(Color::Yellow)
+        .add_modifier(Modifier::BOLD)
+}
+
+/// NVLink paths are fastest, cross-socket PCIe the slowest.
+fn path_style(path: TopologyPath) -> Style {
+    let color = match path {
+        TopologyPath::NvLink(_) | TopologyPath::NvSwitch => Color::Green,
+        TopologyPath::Internal | TopologyPath::Pix | TopologyPath::Pxb => Color::Cyan,
+        TopologyPath::Phb | TopologyPath::Node => Color::Yellow,
+        TopologyPath::Sys => Color::Red,
+    };
+    Style::default().fg(color)
+}
@@ -1,6 +1,7 @@
 //! Utility functions for formatting and display.
 
 use ratatui::style::Color;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
 
 /// Get a color based on usage percentage.
 pub fn usage_color(pct: f64) -> Color {
@@ -15,8 +16,21 @@ pub fn usage_color(pct: f64) -> Color {
     }
 }
 
-/// Get a color based on temperature.
-pub fn temp_color(temp: u32) -> Color {
+/// Get a color based on temperature, relative to the temperature at which
+/// the device starts to slow down. Without a known limit, fixed cutoffs are used.
+pub fn temp_color(temp: u32, limit: Option<u32>) -> Color {
+    if let Some(limit) = limit {
+        return if temp >= limit {
+            Color::Red
+        } else if temp + 15 >= limit {
+            Color::Yellow
+        } else if temp + 35 >= limit {
+            Color::Cyan
+        } else {
+            Color::Green
+        };
+    }
+
     if temp >= 85 {
         Color::Red
     } else if temp >= 70 {
@@ -50,11 +64,159 @@ pub fn format_duration(secs: u64) -> String {
     }
 }
 
-/// Truncate a string to a maximum length with ellipsis.
-pub fn truncate_string(s: &str, max_len: usize) -> String {
-    if s.len() <= max_len {
-        s.to_string()
+/// Format a short span of time with second precision (e.g. `45s`, `3m12s`, `2h05m`).
+pub fn format_short_duration(secs: u64) -> String {
+    let hours = secs / 3600;
+    let mins = (secs % 3600) / 60;
+    let secs = secs % 60;
+
+    if hours > 0 {
+        format!("{}h{:02}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m{:02}s", mins, secs)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
+/// Format an amount of energy, switching from joules to Wh and kWh.
+pub fn format_energy(joules: f64) -> String {
+    let wh = joules / 3600.0;
+    if wh < 1.0 {
+        format!("{:.0} J", joules)
+    } else if wh < 1000.0 {
+        format!("{:.1} Wh", wh)
+    } else {
+        format!("{:.2} kWh", wh / 1000.0)
+    }
+}
+
+/// Truncate a string to a maximum display width with ellipsis.
+pub fn truncate_string(s: &str, max_width: usize) -> String {
+    if s.width() <= max_width {
+        return s.to_string();
+    }
+    // No room for text and an ellipsis; fill the cell with dots
+    if max_width < 3 {
+        return ".".repeat(max_width);
+    }
+
+    let budget = max_width - 3;
+    let mut truncated = String::new();
+    let mut width = 0;
+    for c in s.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > budget {
+            break;
+        }
+        truncated.push(c);
+        width += char_width;
+    }
+    truncated.push_str("...");
+    truncated
+}
+
+/// Drop the first `skip` display columns of a string.
+///
+/// A wide character straddling the boundary is dropped as well.
+pub fn skip_width(s: &str, skip: usize) -> &str {
+    let mut width = 0;
+    for (idx, c) in s.char_indices() {
+        if width >= skip {
+            return &s[idx..];
+        }
+        width += c.width().unwrap_or(0);
+    }
+    ""
+}
+
+/// Split a string into lines of at most `max_width` display columns.
+pub fn wrap_to_width(s: &str, max_width: usize) -> Vec<String> {
+    let max_width = max_width.max(1);
+    let mut lines = Vec::new();
+    let mut line = String::new();
+    let mut width = 0;
+
+    for c in s.chars() {
+        let char_width = c.width().unwrap_or(0);
+        if width + char_width > max_width && !line.is_empty() {
+            lines.push(std::mem::take(&mut line));
+            width = 0;
+        }
+        line.push(c);
+        width += char_width;
+    }
+    if !line.is_empty() || lines.is_empty() {
+        lines.push(line);
+    }
+    lines
+}
+
+/// Render the most recent values as a one-line text sparkline.
+///
+/// Values are scaled against `max`; a non-positive `max` scales against the
+/// largest visible value instead.
+pub fn sparkline(values: &[f64], width: usize, max: f64) -> String {
+    const BLOCKS: [char; 8] = ['▁', '▂', '▃', '▄', '▅', '▆', '▇', '█'];
+
+    let visible = &values[values.len().saturating_sub(width)..];
+    let max = if max > 0.0 {
+        max
     } else {
-        format!("{}...", &s[..max_len.saturating_sub(3)])
+        visible.iter().cloned().fold(0.0, f64::max)
+    };
+
+    let mut line = " ".repeat(width - visible.len());
+    for &v in visible {
+        let level = if max > 0.0 {
+            ((v / max).clamp(0.0, 1.0) * (BLOCKS.len() - 1) as f64).round() as usize
+        } else {
+            0
+        };
+        line.push(BLOCKS[level]);
+    }
+    line
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_string_fits_width() {
+        for s in [
+            "plain ascii text",
+            "日本語のテキスト",
+            "🚀🚀🚀 launch",
+            "aé日🚀b",
+        ] {
+            for width in 0..=12 {
+                let truncated = truncate_string(s, width);
+                assert!(
+                    truncated.width() <= width,
+                    "{:?} at {} gave {:?}",
+                    s,
+                    width,
+                    truncated
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn truncate_string_wide_characters() {
+        assert_eq!(truncate_string("日本語のテキスト", 7), "日本...");
+        // A wide character that does not fit is left out, not split
+        assert_eq!(truncate_string("日本語のテキスト", 8), "日本...");
+        assert_eq!(truncate_string("🚀🚀🚀 launch", 6), "🚀...");
+        assert_eq!(truncate_string("日本", 4), "日本");
+    }
+
+    #[test]
+    fn truncate_string_narrow_cells() {
+        assert_eq!(truncate_string("日本語", 0), "");
+        assert_eq!(truncate_string("日本語", 1), ".");
+        assert_eq!(truncate_string("abcdef", 2), "..");
+        assert_eq!(truncate_string("abcdef", 3), "...");
     }
 }